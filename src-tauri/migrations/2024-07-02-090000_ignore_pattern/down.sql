@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+-- Patterns can not be converted back to keywords, nothing to do.
+SELECT 1;
\ No newline at end of file
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- Keywords used to be matched as substrings of the full path,
+-- migrate them to gitignore-style patterns.
+UPDATE "ignore" SET "keyword" = TRIM(REPLACE("keyword", '\', '/'));
+
+-- Keywords with inner separators matched anywhere in the path,
+-- prefix them with `**/` so they are not anchored to the source root.
+UPDATE "ignore" SET "keyword" = '**/' || "keyword"
+  WHERE INSTR(RTRIM("keyword", '/'), '/') > 0
+    AND "keyword" NOT LIKE '/%'
+    AND "keyword" NOT LIKE '**/%';
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "procedure" DROP COLUMN "max_file_days";
+ALTER TABLE "procedure" DROP COLUMN "max_file_size";
+
+ALTER TABLE "ignore" DROP COLUMN "rule_type";
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE "ignore" ADD COLUMN "rule_type" SMALLINT NOT NULL DEFAULT 0;
+
+ALTER TABLE "procedure" ADD COLUMN "max_file_size" BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE "procedure" ADD COLUMN "max_file_days" SMALLINT NOT NULL DEFAULT 0;
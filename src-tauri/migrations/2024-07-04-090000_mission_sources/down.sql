@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "backup" DROP COLUMN "sources";
+
+ALTER TABLE "mission" DROP COLUMN "src_paths";
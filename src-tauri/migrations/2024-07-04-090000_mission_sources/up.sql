@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE "mission" ADD COLUMN "src_paths" TEXT NOT NULL DEFAULT '';
+
+ALTER TABLE "backup" ADD COLUMN "sources" TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "backup" DROP COLUMN "origin_id";
+ALTER TABLE "backup" DROP COLUMN "destination_id";
+
+DROP TABLE destination;
@@ -0,0 +1,23 @@
+-- Your SQL goes here
+CREATE TABLE "destination" (
+  "id"              INTEGER NOT NULL PRIMARY KEY,
+  "destination_id"  TEXT NOT NULL UNIQUE,
+  "mission_id"      TEXT NOT NULL,
+  "path"            TEXT NOT NULL,
+  "status"          SMALLINT NOT NULL,
+  "restrict"        SMALLINT NOT NULL,
+  "restrict_days"   SMALLINT NOT NULL,
+  "restrict_size"   BIGINT NOT NULL,
+  "last_error"      TEXT NOT NULL,
+  "last_sync"       TIMESTAMP NOT NULL,
+  "reserved_0"       TEXT NOT NULL,
+  "reserved_1"       TEXT NOT NULL,
+  "reserved_2"       TEXT NOT NULL,
+  "create_at"       TIMESTAMP NOT NULL,
+  "update_at"       TIMESTAMP NOT NULL,
+  "is_deleted"      SMALLINT NOT NULL,
+  "delete_at"       TIMESTAMP NOT NULL
+);
+
+ALTER TABLE "backup" ADD COLUMN "destination_id" TEXT NOT NULL DEFAULT '';
+ALTER TABLE "backup" ADD COLUMN "origin_id" TEXT NOT NULL DEFAULT '';
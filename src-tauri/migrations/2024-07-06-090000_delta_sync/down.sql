@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "backup" DROP COLUMN "chain_id";
+
+ALTER TABLE "procedure" DROP COLUMN "backup_mode";
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE "procedure" ADD COLUMN "backup_mode" SMALLINT NOT NULL DEFAULT 0;
+
+ALTER TABLE "backup" ADD COLUMN "chain_id" TEXT NOT NULL DEFAULT '';
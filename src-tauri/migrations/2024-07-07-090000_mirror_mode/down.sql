@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "backup" DROP COLUMN "is_mirror";
+
+ALTER TABLE "procedure" DROP COLUMN "mirror_removed";
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE "procedure" ADD COLUMN "mirror_removed" SMALLINT NOT NULL DEFAULT 0;
+
+ALTER TABLE "backup" ADD COLUMN "is_mirror" SMALLINT NOT NULL DEFAULT 0;
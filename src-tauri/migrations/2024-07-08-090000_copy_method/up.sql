@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE "backup" ADD COLUMN "copy_method" TEXT NOT NULL DEFAULT '';
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "backup" DROP COLUMN "skipped";
+
+ALTER TABLE "procedure" DROP COLUMN "error_retries";
+ALTER TABLE "procedure" DROP COLUMN "error_policy";
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE "procedure" ADD COLUMN "error_policy" SMALLINT NOT NULL DEFAULT 0;
+ALTER TABLE "procedure" ADD COLUMN "error_retries" SMALLINT NOT NULL DEFAULT 3;
+
+ALTER TABLE "backup" ADD COLUMN "skipped" TEXT NOT NULL DEFAULT '';
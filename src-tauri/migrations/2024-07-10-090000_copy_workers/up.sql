@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TABLE "procedure" ADD COLUMN "copy_workers" SMALLINT NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "procedure" DROP COLUMN "compress_priority";
+ALTER TABLE "procedure" DROP COLUMN "write_limit";
+ALTER TABLE "procedure" DROP COLUMN "read_limit";
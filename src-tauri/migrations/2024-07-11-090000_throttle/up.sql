@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE "procedure" ADD COLUMN "read_limit" BIGINT NOT NULL DEFAULT -1;
+ALTER TABLE "procedure" ADD COLUMN "write_limit" BIGINT NOT NULL DEFAULT -1;
+ALTER TABLE "procedure" ADD COLUMN "compress_priority" SMALLINT NOT NULL DEFAULT -1;
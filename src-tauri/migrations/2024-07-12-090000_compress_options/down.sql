@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "procedure" DROP COLUMN "compress_threads";
+ALTER TABLE "procedure" DROP COLUMN "dict_size";
+ALTER TABLE "procedure" DROP COLUMN "compress_method";
+ALTER TABLE "procedure" DROP COLUMN "compress_level";
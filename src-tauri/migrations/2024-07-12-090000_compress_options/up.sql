@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE "procedure" ADD COLUMN "compress_level" SMALLINT NOT NULL DEFAULT -1;
+ALTER TABLE "procedure" ADD COLUMN "compress_method" SMALLINT NOT NULL DEFAULT 0;
+ALTER TABLE "procedure" ADD COLUMN "dict_size" INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE "procedure" ADD COLUMN "compress_threads" SMALLINT NOT NULL DEFAULT 1;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "backup" DROP COLUMN "volumes";
+
+ALTER TABLE "procedure" DROP COLUMN "volume_size";
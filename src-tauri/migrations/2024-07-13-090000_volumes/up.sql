@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE "procedure" ADD COLUMN "volume_size" BIGINT NOT NULL DEFAULT 0;
+
+ALTER TABLE "backup" ADD COLUMN "volumes" INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE "backup" DROP COLUMN "is_encrypted";
+
+ALTER TABLE "procedure" DROP COLUMN "has_password";
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE "procedure" ADD COLUMN "has_password" BOOL NOT NULL DEFAULT FALSE;
+
+ALTER TABLE "backup" ADD COLUMN "is_encrypted" SMALLINT NOT NULL DEFAULT 0;
@@ -5,12 +5,14 @@
 pub mod notify;
 pub mod screensaver;
 pub mod system;
+pub mod throttle;
 pub mod watcher;
 
 use serde::{Serialize, Deserialize};
 use notify::NotifyConfig;
 use screensaver::ScreensaverConfig;
 use system::SystemConfig;
+use throttle::ThrottleConfig;
 use watcher::WatcherConfig;
 
 /// Configuration for app
@@ -27,6 +29,10 @@ pub struct AppConfig {
 
     /// App screensaver config, like `enable`...
     pub screensaver: ScreensaverConfig,    
+
+    /// App throttle config for backups, like `read_limit`...
+    #[serde(default)]
+    pub throttle: ThrottleConfig,
 }
 
 impl Default for AppConfig {
@@ -36,6 +42,7 @@ impl Default for AppConfig {
             notify: notify::NotifyConfig::default(),
             watcher: watcher::WatcherConfig::default(),
             screensaver: screensaver::ScreensaverConfig::default(),
+            throttle: throttle::ThrottleConfig::default(),
         }
     }
 }
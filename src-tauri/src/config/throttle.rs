@@ -0,0 +1,35 @@
+//! # Throttle
+//! 
+//! `throttle` module contains all configuration about resources used by backups.
+
+use serde::{Serialize, Deserialize};
+
+/// Configuration for throttle, procedures can override each item
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThrottleConfig {
+    /// Bytes per second read from sources, `0` means no limit
+    pub read_limit: u64,
+
+    /// Bytes per second written to save path, `0` means no limit
+    pub write_limit: u64,
+
+    /// Priority of compression thread
+    /// 
+    /// `0` - normal
+    /// 
+    /// `1` - below normal
+    /// 
+    /// `2` - lowest
+    pub compress_priority: i16,
+}
+
+impl ThrottleConfig {
+    /// Gets config with overrides of procedure, negative values keep the ones in this config.
+    pub fn with_overrides(&self, read_limit: i64, write_limit: i64, compress_priority: i16) -> ThrottleConfig {
+        ThrottleConfig {
+            read_limit: if read_limit < 0 { self.read_limit } else { read_limit as u64 },
+            write_limit: if write_limit < 0 { self.write_limit } else { write_limit as u64 },
+            compress_priority: if compress_priority < 0 { self.compress_priority } else { compress_priority },
+        }
+    }
+}
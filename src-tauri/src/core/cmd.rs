@@ -226,6 +226,9 @@ pub async fn sync_config(group: &str, config: crate::config::AppConfig, overwrit
             "screensaver" => {
                 cur.screensaver = config.screensaver.clone();
             },
+            "throttle" => {
+                cur.throttle = config.throttle.clone();
+            },
             _ => {
                 error!("Failed to overwrite config, errMsg: no match for group {}", group);
                 return Err(Response::<bool>::error(400, format!("no match for group {}", group)));
@@ -239,8 +242,16 @@ pub async fn sync_config(group: &str, config: crate::config::AppConfig, overwrit
 
 #[command]
 pub async fn create_record(table: &str, data: Record, state: State<'_, MissionHandlerState>) -> Result<Response<Record>, Response<bool>> {
-    use crate::db::create_db_record;
+    use crate::db::{ create_db_record, procedure::check_archive_password };
     
+    // procedure with password must create archive able to be encrypted
+    if table == "procedure" {
+        if let Err(error) = check_archive_password(&data.procedure) {
+            error!("failed to create record, errMsg: {:?}", error);
+            return Err(Response::<bool>::error(400, format!("{:?}", error)));
+        }
+    }
+
     let mut guard = state.0.lock().await;
 
     if let Some(conn) = &mut guard.db_handler {
@@ -262,8 +273,16 @@ pub async fn create_record(table: &str, data: Record, state: State<'_, MissionHa
 
 #[command]
 pub async fn update_record(table: &str, data: Record, state: State<'_, MissionHandlerState>) -> Result<Response<Record>, Response<bool>> {
-    use crate::db::update_db_record;
+    use crate::db::{ update_db_record, procedure::check_archive_password };
     
+    // procedure with password must create archive able to be encrypted
+    if table == "procedure" {
+        if let Err(error) = check_archive_password(&data.procedure) {
+            error!("failed to update record, errMsg: {:?}", error);
+            return Err(Response::<bool>::error(400, format!("{:?}", error)));
+        }
+    }
+
     let mut guard = state.0.lock().await;
 
     if let Some(conn) = &mut guard.db_handler {
@@ -374,6 +393,242 @@ pub async fn delete_backup(uuid: &str, state: State<'_, MissionHandlerState>) ->
     Err(Response::<bool>::error(503, "database unavailalbe".to_string()))
 }
 
+#[command]
+pub async fn restore_backup(uuid: &str, target: Option<&str>, state: State<'_, MissionHandlerState>) -> Result<Response<Vec<String>>, Response<bool>> {
+    use crate::db::backup::restore_backup;
+
+    let mut guard = state.0.lock().await;
+
+    if let Some(conn) = &mut guard.db_handler {
+        match restore_backup(uuid, target, conn) {
+            Ok(restored) => {
+                info!("restore backup {} to {:?}", uuid, restored);
+                return Ok(Response::success(restored));
+            },
+            Err(error) => {
+                error!("failed to restore backup, errMsg: {:?}", error);
+                return Err(Response::<bool>::error(500, format!("{:?}", error)));
+            }
+        }
+    }
+
+    Err(Response::<bool>::error(503, "database unavailalbe".to_string()))
+}
+
+#[command]
+pub async fn replicate_backup(uuid: &str, did: &str, state: State<'_, MissionHandlerState>) -> Result<Response<crate::db::backup::Backup>, Response<bool>> {
+    use crate::db::{ backup::{ query_backup_record, replicate_backup }, destination::query_destination_record };
+
+    let mut guard = state.0.lock().await;
+    let throttle = guard.config.throttle.clone();
+
+    if let Some(conn) = &mut guard.db_handler {
+        let backup = match query_backup_record(conn, Some(uuid), None) {
+            Ok(records) if records.len() > 0 => records[0].clone(),
+            _ => {
+                return Err(Response::<bool>::error(404, format!("backup {} not found", uuid)));
+            }
+        };
+        let mut destination = match query_destination_record(conn, Some(&backup.mission_id)) {
+            Ok(records) => match records.into_iter().find(|item| item.destination_id == did) {
+                Some(item) => item,
+                None => {
+                    return Err(Response::<bool>::error(404, format!("destination {} not found", did)));
+                }
+            },
+            Err(error) => {
+                error!("failed to query destination, errMsg: {:?}", error);
+                return Err(Response::<bool>::error(500, format!("{:?}", error)));
+            }
+        };
+
+        match replicate_backup(&backup, &mut destination, &throttle, conn) {
+            Ok(copy) => {
+                info!("replicate backup {} to {}", uuid, copy.save_path);
+                return Ok(Response::success(copy));
+            },
+            Err(error) => {
+                error!("failed to replicate backup, errMsg: {:?}", error);
+                return Err(Response::<bool>::error(500, format!("{:?}", error)));
+            }
+        }
+    }
+
+    Err(Response::<bool>::error(503, "database unavailalbe".to_string()))
+}
+
+#[command]
+pub async fn rescan_backups(uuid: &str, did: Option<&str>, state: State<'_, MissionHandlerState>) -> Result<Response<crate::db::backup::RescanReport>, Response<bool>> {
+    use crate::db::backup::rescan_backups;
+
+    let mut guard = state.0.lock().await;
+
+    if let Some(conn) = &mut guard.db_handler {
+        match rescan_backups(uuid, did, conn) {
+            Ok(report) => {
+                info!("rescan backups of mission {}, recover {}, orphans: {:?}", uuid, report.recovered.len(), report.orphans);
+                return Ok(Response::success(report));
+            },
+            Err(error) => {
+                error!("failed to rescan backups, errMsg: {:?}", error);
+                return Err(Response::<bool>::error(500, format!("{:?}", error)));
+            }
+        }
+    }
+
+    Err(Response::<bool>::error(503, "database unavailalbe".to_string()))
+}
+
+#[command]
+pub async fn reconcile_backups(uuid: Option<&str>, options: crate::db::backup::ReconcileOptions, state: State<'_, MissionHandlerState>) -> Result<Response<crate::db::backup::ReconcileReport>, Response<bool>> {
+    use crate::db::backup::reconcile_backups;
+
+    let mut guard = state.0.lock().await;
+
+    if let Some(conn) = &mut guard.db_handler {
+        match reconcile_backups(uuid, &options, conn) {
+            Ok(report) => {
+                info!("reconcile backups, missing {}, untracked {}, mismatched {}", report.missing.len(), report.untracked.len(), report.mismatched.len());
+                return Ok(Response::success(report));
+            },
+            Err(error) => {
+                error!("failed to reconcile backups, errMsg: {:?}", error);
+                return Err(Response::<bool>::error(500, format!("{:?}", error)));
+            }
+        }
+    }
+
+    Err(Response::<bool>::error(503, "database unavailalbe".to_string()))
+}
+
+#[command]
+pub async fn export_bundle(path: &str, options: crate::db::bundle::ExportOptions, state: State<'_, MissionHandlerState>) -> Result<Response<crate::db::bundle::Bundle>, Response<bool>> {
+    use crate::db::bundle::export_bundle;
+
+    let mut guard = state.0.lock().await;
+
+    if let Some(conn) = &mut guard.db_handler {
+        match export_bundle(path, &options, conn) {
+            Ok(bundle) => {
+                info!("export {} missions to bundle {}", bundle.missions.len(), path);
+                return Ok(Response::success(bundle));
+            },
+            Err(error) => {
+                error!("failed to export bundle, errMsg: {:?}", error);
+                return Err(Response::<bool>::error(500, format!("{:?}", error)));
+            }
+        }
+    }
+
+    Err(Response::<bool>::error(503, "database unavailalbe".to_string()))
+}
+
+#[command]
+pub async fn import_bundle(path: &str, options: crate::db::bundle::ImportOptions, state: State<'_, MissionHandlerState>) -> Result<Response<crate::db::bundle::ImportReport>, Response<bool>> {
+    use crate::db::bundle::import_bundle;
+
+    let mut guard = state.0.lock().await;
+
+    let report = match &mut guard.db_handler {
+        Some(conn) => {
+            match import_bundle(path, &options, conn) {
+                Ok(report) => report,
+                Err(error) => {
+                    error!("failed to import bundle, errMsg: {:?}", error);
+                    return Err(Response::<bool>::error(500, format!("{:?}", error)));
+                }
+            }
+        },
+        None => {
+            return Err(Response::<bool>::error(503, "database unavailalbe".to_string()));
+        }
+    };
+
+    for mission in report.created.iter() {
+        if let Err(error) = guard.create_job(mission).await {
+            error!("failed to create job for imported mission {}, errMsg: {:?}", mission.name, error);
+        }
+    }
+
+    for mission in report.updated.iter() {
+        if let Err(error) = guard.remove_job(&mission.mission_id).await {
+            error!("failed to remove job for mission {}, errMsg: {:?}", mission.name, error);
+        }
+        if let Err(error) = guard.create_job(mission).await {
+            error!("failed to create job for mission {}, errMsg: {:?}", mission.name, error);
+        }
+    }
+
+    if report.config_applied {
+        match crate::config::load_app_config() {
+            Ok(config) => {
+                guard.config = config;
+            },
+            Err(error) => {
+                error!("failed to load imported config, errMsg: {:?}", error);
+            }
+        }
+    }
+
+    info!("import bundle {}, create {}, update {}, skip {:?}", path, report.created.len(), report.updated.len(), report.skipped);
+    Ok(Response::success(report))
+}
+
+#[command]
+pub fn save_storage_credential(location: &str, access_key: &str, secret_key: &str) -> Result<Response<bool>, Response<bool>> {
+    use crate::storage::save_credential;
+
+    match save_credential(location, access_key, secret_key) {
+        Ok(()) => {
+            info!("save credential of {}", location);
+            return Ok(Response::success(true));
+        },
+        Err(error) => {
+            error!("failed to save credential of {}, errMsg: {:?}", location, error);
+            return Err(Response::<bool>::error(500, format!("{:?}", error)));
+        }
+    }
+}
+
+#[command]
+pub async fn save_archive_password(pid: &str, password: &str, state: State<'_, MissionHandlerState>) -> Result<Response<bool>, Response<bool>> {
+    use crate::db::procedure::{ query_procedure_record, save_archive_password, update_procedure_record };
+
+    if let Err(error) = save_archive_password(pid, password) {
+        error!("failed to save archive password of procedure {}, errMsg: {:?}", pid, error);
+        return Err(Response::<bool>::error(500, format!("{:?}", error)));
+    }
+    if !password.is_empty() {
+        info!("save archive password of procedure {}", pid);
+        return Ok(Response::success(true));
+    }
+
+    // empty password only stops procedure using the current one, saved versions are kept for restoring
+    let mut guard = state.0.lock().await;
+
+    if let Some(conn) = &mut guard.db_handler {
+        let mut procedure = match query_procedure_record(conn, Some(pid)) {
+            Ok(records) if !records.is_empty() => records[0].clone(),
+            _ => {
+                return Err(Response::<bool>::error(404, format!("procedure {} not found", pid)));
+            }
+        };
+        procedure.has_password = false;
+        match update_procedure_record(conn, &mut procedure) {
+            Ok(_) => {
+                info!("stop using archive password of procedure {}", pid);
+                return Ok(Response::success(true));
+            },
+            Err(error) => {
+                error!("failed to update procedure {}, errMsg: {:?}", pid, error);
+                return Err(Response::<bool>::error(500, format!("{:?}", error)));
+            }
+        }
+    }
+
+    Err(Response::<bool>::error(503, "database unavailalbe".to_string()))
+}
+
 #[command]
 pub async fn set_mission_status(uuid: &str, stat: i16, state: State<'_, MissionHandlerState>) -> Result<Response<Mission>, Response<bool>> {    
     use crate::db::mission::update_mission_status;
@@ -562,3 +817,44 @@ pub fn migrate_from_old(path: &str) -> Result<Response<crate::utils::migrate::Mi
     }
   }
 }
+
+#[command]
+pub async fn preview_ignore_rules(path: &str, procedure: crate::db::procedure::Procedure, ignores: Option<Vec<crate::db::ignore::Ignore>>, state: State<'_, MissionHandlerState>) -> Result<Response<crate::utils::explorer::RulesPreview>, Response<bool>> {
+    use crate::db::ignore::{ build_procedure_rules, get_procedure_rules };
+    use crate::utils::explorer::preview_dir_with_rules;
+
+    // unsaved ignores take place of the saved ones
+    let rules = match ignores {
+        Some(items) => build_procedure_rules(&procedure, &items),
+        None => {
+            let mut guard = state.0.lock().await;
+            match &mut guard.db_handler {
+                Some(conn) => get_procedure_rules(&procedure, conn),
+                None => {
+                    return Err(Response::<bool>::error(503, "database unavailalbe".to_string()));
+                }
+            }
+        }
+    };
+
+    // walks the whole directory, so it is kept off the async runtime
+    let (target, walk_rules) = (path.to_string(), rules.clone());
+    let result = match tauri::async_runtime::spawn_blocking(move || preview_dir_with_rules(&target, &walk_rules)).await {
+        Ok(result) => result,
+        Err(error) => {
+            error!("failed to run preview of {}, errMsg: {:?}", path, error);
+            return Err(Response::<bool>::error(500, format!("{:?}", error)));
+        }
+    };
+
+    match result {
+        Ok(preview) => {
+            debug!("preview {} with rules {:?}, {} included, {} excluded", path, rules, preview.included_count, preview.excluded_count);
+            return Ok(Response::success(preview));
+        },
+        Err(error) => {
+            error!("failed to preview {}, errMsg: {:?}", path, error);
+            return Err(Response::<bool>::error(500, format!("{:?}", error)));
+        }
+    }
+}
@@ -88,6 +88,14 @@ pub fn setup_command() -> Box<dyn Fn(tauri::Invoke<tauri::Wry>) + Send + Sync> {
         delete_record,
         clear_record,
         delete_backup,
+        restore_backup,
+        replicate_backup,
+        rescan_backups,
+        reconcile_backups,
+        export_bundle,
+        import_bundle,
+        save_storage_credential,
+        save_archive_password,
         set_mission_status,
         create_mission,
         delete_mission,
@@ -96,6 +104,7 @@ pub fn setup_command() -> Box<dyn Fn(tauri::Invoke<tauri::Wry>) + Send + Sync> {
         clean_database,
         query_log_info,
         clean_app_log,
-        migrate_from_old
+        migrate_from_old,
+        preview_ignore_rules
     ])
 }
@@ -91,8 +91,8 @@ pub struct MissionHandler {
     pub cron_jobs: HashMap<String, Uuid>,
 
     /// Monitor jobs
-    /// Mission id : Watch path
-    pub monitor_jobs: HashMap<String, String>
+    /// Mission id : Watch paths
+    pub monitor_jobs: HashMap<String, Vec<String>>
 }
 
 impl MissionHandler {
@@ -306,8 +306,10 @@ impl MissionHandler {
                             match res {
                                 Ok(_events) => {
                                     if let Some(app) = &callback_app {
+                                        // throttle follows app config loaded at the time backup runs
+                                        let throttle = app.state::<MissionHandlerState>().0.lock().await.config.throttle.clone();
                                         if let Ok(mut conn) = establish_sqlite_connection() {
-                                            match create_backup(&callback_id, &mut conn) {
+                                            match create_backup(&callback_id, &throttle, &mut conn) {
                                                 Ok(backup) => {
                                                     let _ = app.emit_all("backup", Response::success(backup));
                                                 },
@@ -374,29 +376,35 @@ impl MissionHandler {
 
         let callback_id = mission.clone().mission_id;
         let callback_app = self.app_handler.clone();
-        let create_res = Job::new(procedure.cron_expression.as_str(), move |_uuid, _l| {
+        let create_res = Job::new_async(procedure.cron_expression.as_str(), move |_uuid, _l| {
             // if let Ok(mut conn) = establish_sqlite_connection() {
             //     if let Ok(backup) = create_backup(&callback_mission.mission_id, &mut conn) {
             //         info!("create backup success: {:?}", backup);
             //     }
             // }
 
-            if let Some(app) = &callback_app {
-                if let Ok(mut conn) = establish_sqlite_connection() {
-                    match create_backup(&callback_id, &mut conn) {
-                        Ok(backup) => {
-                            let _ = app.emit_all("backup", Response::success(backup));
-                        },
-                        Err(error) => {
-                            let _ = app.emit_all("backup", Response::<bool>::error(500, format!("{:?}", error)));
+            let callback_id = callback_id.clone();
+            let callback_app = callback_app.clone();
+            Box::pin(async move {
+                if let Some(app) = &callback_app {
+                    // throttle follows app config loaded at the time backup runs
+                    let throttle = app.state::<MissionHandlerState>().0.lock().await.config.throttle.clone();
+                    if let Ok(mut conn) = establish_sqlite_connection() {
+                        match create_backup(&callback_id, &throttle, &mut conn) {
+                            Ok(backup) => {
+                                let _ = app.emit_all("backup", Response::success(backup));
+                            },
+                            Err(error) => {
+                                let _ = app.emit_all("backup", Response::<bool>::error(500, format!("{:?}", error)));
+                            }
                         }
+                    } else {
+                        let _ = app.emit_all("backup", Response::<bool>::error(500, "Failed connect to database".to_string()));
                     }
                 } else {
-                    let _ = app.emit_all("backup", Response::<bool>::error(500, "Failed connect to database".to_string()));
+                    error!("Invalid app instance when create backup");
                 }
-            } else {
-                error!("Invalid app instance when create backup");
-            }
+            })
         });     
 
         match create_res {
@@ -438,19 +446,27 @@ impl MissionHandler {
                 // let callback_mission = mission.clone();
                 // let callback_app = self.app_handler.clone();
                 // let config = &procedures[0].procedure;
-                let create_res = self.watch(&mission.src_path.as_str(), &mission.mission_id.as_str()).await;
-
-                match create_res {
-                    Ok(()) => {
-                        info!("create monitore job success for mission {}", mission.name);
-                        self.monitor_jobs.insert(mission.mission_id.clone(), mission.src_path.clone());
-                        return Ok(true);
-                    },
-                    Err(error) => {
+                let sources = mission.sources();
+                for (index, source) in sources.iter().enumerate() {
+                    if let Err(error) = self.watch(source.as_str(), &mission.mission_id.as_str()).await {
                         error!("Failed to create monitor job for mission {}, errMsg: {:?}", mission.name, error);
+
+                        // sources watched already would trigger mission not in monitor jobs
+                        if let Some(handler) = &mut self.watcher_handler {
+                            for watched in sources[..index].iter() {
+                                if let Err(error) = handler.watcher().unwatch(Path::new(watched)) {
+                                    warn!("Failed to unwatch {} for mission {}, errMsg: {:?}", watched, mission.name, error);
+                                }
+                                handler.cache().remove_root(Path::new(watched));
+                            }
+                        }
                         return Err(Error::from(ErrorKind::Interrupted));
                     }
-                }           
+                }
+
+                info!("create monitore job success for mission {}", mission.name);
+                self.monitor_jobs.insert(mission.mission_id.clone(), sources);
+                return Ok(true);
             } else {
                 error!("Failed to create monitor job for mission {}, errMsg: failed to query procedure", mission.name);
                 return Err(Error::from(ErrorKind::InvalidData));
@@ -519,18 +535,16 @@ impl MissionHandler {
 
     fn remove_monitore_job(&mut self, mission_id: &str) -> Result<bool, std::io::Error> {
         if self.monitor_jobs.contains_key(mission_id) {
-            if let Some(watch_path) = self.monitor_jobs.get(mission_id) {
+            if let Some(watch_paths) = self.monitor_jobs.get(mission_id) {
                 if let Some(handler) = &mut self.watcher_handler {
-                    match handler.watcher().unwatch(Path::new(watch_path)) {
-                        Ok(()) => {
-                            debug!("remove mission {}", mission_id);
-                            return Ok(true);
-                        },
-                        Err(error) => {
+                    for watch_path in watch_paths.iter() {
+                        if let Err(error) = handler.watcher().unwatch(Path::new(watch_path)) {
                             error!("Failed to remove cron job for mission {}, errMsg: {:?}", mission_id, error);
                             return Err(Error::from(ErrorKind::Other));
                         }
                     }
+                    debug!("remove mission {}", mission_id);
+                    return Ok(true);
                 }
             }
         }
@@ -54,6 +54,66 @@ pub struct Backup {
 
     /// Delete time
     pub delete_at: NaiveDateTime,
+
+    /// Source path(absolute) of each item in backup, in JSON object
+    /// 
+    /// Empty means backup of single source `Mission::src_path`
+    #[serde(default)]
+    pub sources: String,
+
+    /// Saved in which destination
+    /// 
+    /// Empty means `Mission::dst_path`
+    #[serde(default)]
+    pub destination_id: String,
+
+    /// Uuid of the backup this copy is replicated from
+    /// 
+    /// Empty means original backup
+    #[serde(default)]
+    pub origin_id: String,
+
+    /// Delta chain this backup belongs to
+    /// 
+    /// Empty means snapshot backup
+    #[serde(default)]
+    pub chain_id: String,
+
+    /// Whether backup is mirror of sources, updated in place by each run
+    /// 
+    /// `0` - not mirror
+    /// 
+    /// `1` - mirror
+    #[serde(default)]
+    pub is_mirror: i16,
+
+    /// How files are copied into backup
+    /// 
+    /// `reflink` - cloned on copy-on-write filesystem
+    /// 
+    /// `copy` - copied byte by byte
+    /// 
+    /// `mixed` - both of above, empty means no local copy
+    #[serde(default)]
+    pub copy_method: String,
+
+    /// Items skipped by error policy of procedure, in JSON array of path and reason
+    /// 
+    /// Empty means nothing skipped
+    #[serde(default)]
+    pub skipped: String,
+
+    /// Count of volumes archive is split into, `save_path` is the first volume ends with `.001`
+    /// 
+    /// `0` - not split
+    #[serde(default)]
+    pub volumes: i32,
+
+    /// Version of procedure password archive is encrypted with, see [super::procedure::load_archive_password]
+    /// 
+    /// `0` - not encrypted
+    #[serde(default)]
+    pub is_encrypted: i16,
 }
 
 impl Default for Backup {
@@ -71,6 +131,15 @@ impl Default for Backup {
             update_at: Utc::now().naive_utc(),
             is_deleted: 0,
             delete_at: Utc::now().naive_utc(),
+            sources: "".to_string(),
+            destination_id: "".to_string(),
+            origin_id: "".to_string(),
+            chain_id: "".to_string(),
+            is_mirror: 0,
+            copy_method: "".to_string(),
+            skipped: "".to_string(),
+            volumes: 0,
+            is_encrypted: 0,
         }
     }
 }
@@ -132,14 +201,23 @@ pub fn create_backup_record(
     data: &mut Backup,
     mission: &Mission
 ) -> Result<Backup, diesel::result::Error> {
-    use super::schema::backup::dsl::*;
-
     let cur_time: NaiveDateTime = Utc::now().naive_utc();
-    data.id = backup.count().get_result(conn).unwrap_or(0) as i32 + 1;
     data.backup_id = Uuid::new_v4().to_string();
     data.mission_id = mission.mission_id.clone();
     data.create_at = cur_time;
 
+    insert_backup_record(conn, data)
+}
+
+/// Insert backup record as it is, only primary key is assigned.
+fn insert_backup_record(
+    conn: &mut SqliteConnection,
+    data: &mut Backup
+) -> Result<Backup, diesel::result::Error> {
+    use super::schema::backup::dsl::*;
+
+    data.id = backup.count().get_result(conn).unwrap_or(0) as i32 + 1;
+
     diesel::insert_into(backup)
         .values(data.clone())
         .returning(Backup::as_returning())
@@ -417,12 +495,13 @@ pub fn clean_record(
     conn: &mut SqliteConnection, 
 ) -> Result<usize, diesel::result::Error> {
     use super::schema::backup::dsl::*;
+    use crate::storage::is_remote;
     use std::path::Path;
 
-    // delete invalid backups
+    // delete invalid backups, remote ones are checked when connected
     let cur_backups = query_backup_record(conn, None, None)?;
     for item in cur_backups.iter() {
-        if !Path::new(&item.save_path).exists() {
+        if !is_remote(&item.save_path) && !Path::new(&item.save_path).exists() {
             delete_backup_record(conn, Some(&item.backup_id), None)?;
         }
     }
@@ -448,16 +527,18 @@ pub fn clean_record(
 /// # Arguments
 /// 
 /// * `mid` - Uuid for mission.
+/// * `throttle` - Throttle in loaded app config.
 /// * `conn` - Connection to database.
 /// 
 /// # Examples
 /// 
 /// ```
 /// use db::{establish_sqlite_connection, backup::create_backup};
+/// use config::throttle::ThrottleConfig;
 /// 
 /// if let Ok(mut conn) = establish_sqlite_connection() {
 ///     let mid = "1c69eead-b7cf-457e-95e2-9c9f459120ff";
-///     match create_backup(mid, &mut conn) {
+///     match create_backup(mid, &ThrottleConfig::default(), &mut conn) {
 ///         Ok(backup) => {
 ///             println!("create backup and save at {}", backup.save_path);
 ///         },
@@ -467,26 +548,25 @@ pub fn clean_record(
 ///     }   
 /// }
 /// ```
-pub fn create_backup(mid: &str, conn: &mut SqliteConnection) -> Result<Backup, std::io::Error> {
+pub fn create_backup(mid: &str, throttle: &crate::config::throttle::ThrottleConfig, conn: &mut SqliteConnection) -> Result<Backup, std::io::Error> {
     use super::{
         mission::{ get_mission_related_record, update_mission_status }, 
-        ignore::get_procedure_ignores
-    };
-    use crate::utils::{
-        compress::create_archive,
-        explorer::{
-            copy_all, remove_all, copy_dir_with_build_in_ignore, copy_dir_with_custom_ignores,
-            get_path_size, restrict_dir_subitems_count, restrict_dir_subitems_size
-        }
+        ignore::get_procedure_rules,
+        procedure::get_procedure_throttle,
+        destination::query_destination_record
     };
+    use crate::storage::is_remote;
     use std::path::Path;
     use std::io::{ Error, ErrorKind };
+    use log::error;
     
     if let Ok(record) = get_mission_related_record(mid, conn) {
-        let mut backup = Backup::default();
         let mission = &record.mission;
         let procedure = &record.procedure;
-        let ignores = get_procedure_ignores(&mission.procedure_id, conn);
+        let throttle = get_procedure_throttle(procedure, throttle);
+        let mut rules = get_procedure_rules(procedure, conn);
+        rules.read_limit = throttle.read_limit;
+        rules.write_limit = throttle.write_limit;
 
         if mission.status == 0 {
             return Err(Error::from(ErrorKind::InvalidData));
@@ -495,167 +575,2961 @@ pub fn create_backup(mid: &str, conn: &mut SqliteConnection) -> Result<Backup, s
         // update mission status
         let _ = update_mission_status(conn, 2, &mission.mission_id);
 
-        // get the actual backup save dir and path
-        let cur_time = Utc::now().naive_utc();
-        let mut target_name = Path::new(&mission.src_path).file_name().unwrap_or("".as_ref()).to_str().expect("");
-        if target_name.is_empty() {
-            target_name = mission.mission_id.as_str();
-        }
-        // let mut target_stem = Path::new(&mission.src_path).file_stem().unwrap_or("".as_ref()).to_str().expect("");
-        // if target_stem.is_empty() {
-        //     target_stem = mission.mission_id.as_str();
-        // }
+        // copy sources into save location, or sync changed blocks since last backup
+        let timestamp = Utc::now().naive_utc().and_utc().timestamp().to_string();
+        let (mut backup, files) = match procedure.backup_mode {
+            1 => create_delta_sync(mission, &rules, &timestamp, conn)?,
+            2 => create_mirror(mission, procedure, &rules, &timestamp, conn)?,
+            _ => create_snapshot(mission, procedure, &rules, &throttle, &timestamp, conn)?,
+        };
 
-        let backup_dir = &Path::new(&mission.dst_path)
-            .join(format!("{}", cur_time.and_utc().timestamp().to_string()));
+        // create backup record with manifest
+        backup = record_backup(conn, &mut backup, files, mission, procedure)?;
 
-        let backup_path = backup_dir.join(target_name);
+        // restrict save path
+        restrict_saves(&backup, &mission.dst_path, procedure.restrict, procedure.restrict_days, procedure.restrict_size, conn)?;
 
-        let mut save_path = backup_path.display().to_string();
+        // replicate backup to other destinations, failed ones are recorded in destination status
+        let mut unavailable: Vec<String> = Vec::new();
+        if let Ok(mut destinations) = query_destination_record(conn, Some(&mission.mission_id)) {
+            for destination in destinations.iter_mut() {
+                if let Err(error) = replicate_backup(&backup, destination, &throttle, conn) {
+                    error!("failed to replicate backup to {}, errMsg: {:?}", destination.path, error);
+                    unavailable.push(destination.destination_id.clone());
+                }
+            }
+        }
 
-        // copy from src to dst with or without ignores
-        if Path::new(&mission.src_path).is_dir() { 
-            if procedure.has_ignores { // copy dir with ignore
-                match procedure.ignore_method {
-                    1 => { // with custom ignore
-                        copy_dir_with_custom_ignores(&mission.src_path, &save_path, &ignores)?;
-                    },
-                    2 => { // with .gitignore
-                        copy_dir_with_build_in_ignore(&mission.src_path, &save_path)?;
-                    },
-                    _ => {
-                        copy_all(&mission.src_path, &save_path)?;
+        // clear unavailable backup records, except for those in disconnected destinations
+        if let Ok(cur_backups) = query_backup_record(conn, None, Some(mid)) {
+            for item in &cur_backups {
+                if unavailable.contains(&item.destination_id) || is_remote(&item.save_path) {
+                    continue;
+                }
+                if !Path::new(&item.save_path).exists() {
+                    if let Err(error) = delete_backup_record(conn, Some(&item.backup_id), None) {
+                        error!("failed to delete record of missing backup {}, errMsg: {:?}", item.save_path, error);
                     }
                 }
-            } else {
-                copy_all(&mission.src_path, &save_path)?;
-            }  
-        } else {
-            copy_all(&mission.src_path, &save_path)?;
+            }
         }
 
+        // update mission status
+        let _ = update_mission_status(conn, 1, &mission.mission_id);
 
-        // whether create archive
-        if procedure.is_compress {
-            let support_formats = Vec::from(["zip", "tar.gz", "tar.bz2", "tar.xz", "7z"]);
-            let mut archive_format: &str = "";
-            for (idx, format) in support_formats.iter().enumerate()  {
-                if procedure.compress_format - 1 == (idx as i16) {
-                    archive_format = format;
-                    break;
-                }
-            }
+        return Ok(backup);
+    }
 
-            if !archive_format.is_empty() {
-                let archive_path = backup_dir.join(format!("{}.{}", target_name, archive_format));
-                if let Ok(()) = create_archive(&save_path, archive_path.display().to_string().as_str()) {
-                    remove_all(&save_path)?;
-                    save_path = archive_path.display().to_string();
-                }
-            }
+    Err(Error::from(ErrorKind::NotFound))
+}
+
+/// Create record of backup and describe it with manifest, backup is still usable without manifest.
+fn record_backup(
+    conn: &mut SqliteConnection,
+    backup: &mut Backup,
+    files: std::collections::BTreeMap<String, ManifestFile>,
+    mission: &Mission,
+    procedure: &super::procedure::Procedure
+) -> Result<Backup, std::io::Error> {
+    use std::io::{ Error, ErrorKind };
+    use log::error;
+
+    let backup = match create_backup_record(conn, backup, mission) {
+        Ok(data) => data,
+        Err(_) => {
+            return Err(Error::from(ErrorKind::Other));
         }
+    };
+
+    let manifest = BackupManifest {
+        version: MANIFEST_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        backup: backup.clone(),
+        mission: mission.clone(),
+        procedure: procedure.clone(),
+        files,
+    };
+    if let Err(error) = save_manifest(&manifest) {
+        error!("failed to save manifest of backup {}, errMsg: {:?}", backup.backup_id, error);
+    }
 
-        // create backup record
-        backup.save_path = save_path.to_string();
-        if let Ok(save_size) = get_path_size(&save_path) {
-            backup.backup_size = save_size as i64;
+    Ok(backup)
+}
+
+/// Names each source inside backup, multiple sources are kept side by side with unique names.
+fn map_sources(mission: &Mission) -> std::collections::BTreeMap<String, String> {
+    use std::collections::BTreeMap;
+
+    let sources = mission.sources();
+    let mut mapping: BTreeMap<String, String> = BTreeMap::new();
+    if sources.len() == 1 {
+        let mut entry_name = source_entry_name(&sources[0]);
+        if entry_name.is_empty() {
+            entry_name = mission.mission_id.clone();
         }
-        match create_backup_record(conn, &mut backup, mission) {
-            Ok(data) => {
-                backup = data;
-            },
-            Err(_) => {
-                return Err(Error::from(ErrorKind::Other));
+        mapping.insert(entry_name, sources[0].clone());
+        return mapping;
+    }
+
+    for source in sources.iter() {
+        let mut entry_name = source_entry_name(source);
+        if entry_name.is_empty() {
+            entry_name = "source".to_string();
+        }
+        let mut idx = 1;
+        let base_name = entry_name.clone();
+        while mapping.contains_key(&entry_name) {
+            entry_name = format!("{}_{}", base_name, idx);
+            idx += 1;
+        }
+        mapping.insert(entry_name, source.clone());
+    }
+
+    mapping
+}
+
+/// Copy sources into `<dst_path>/<timestamp>/`, compress it if wanted.
+/// 
+/// Returns unsaved backup record with files for manifest.
+fn create_snapshot(
+    mission: &Mission,
+    procedure: &super::procedure::Procedure,
+    rules: &crate::utils::explorer::IgnoreRules,
+    throttle: &crate::config::throttle::ThrottleConfig,
+    timestamp: &str,
+    conn: &mut SqliteConnection
+) -> Result<(Backup, std::collections::BTreeMap<String, ManifestFile>), std::io::Error> {
+    use super::procedure::{ get_archive_password, is_encryptable };
+    use crate::utils::{
+        compress::{ ArchiveOptions, create_archive_with, split_volumes },
+        explorer::{
+            CopyStats, remove_all, get_path_size
+        }
+    };
+    use crate::storage::is_remote;
+    use std::io::{ Error, ErrorKind };
+    use std::path::Path;
+    use log::error;
+
+    // backup expected to be encrypted is never saved as plain files
+    if procedure.has_password && !is_encryptable(procedure) {
+        error!("archive password of procedure {} requires zip or 7z format", procedure.procedure_id);
+        return Err(Error::from(ErrorKind::InvalidInput));
+    }
+
+    let mut backup = Backup::default();
+
+    // get the actual backup save dir and path
+    let mapping = map_sources(mission);
+    let mut target_name = match mapping.len() {
+        1 => mapping.keys().next().cloned().unwrap_or_default(),
+        _ => sanitize_name(&mission.name),
+    };
+    if target_name.is_empty() {
+        target_name = mission.mission_id.clone();
+    }
+
+    // backups to remote storage are staged locally, then uploaded
+    let is_upload = is_remote(&mission.dst_path);
+    let stage_dir = match is_upload {
+        true => std::env::temp_dir().join("mission_backup").join(&mission.mission_id),
+        false => Path::new(&mission.dst_path).to_path_buf(),
+    };
+    // staged backups left by failed uploads are uploaded first, and kept for the next try if failed again
+    if is_upload {
+        for pending in take_pending_uploads(&stage_dir, &mission.dst_path) {
+            let pending_time = pending.timestamp.clone();
+            let uploaded = upload_pending(&stage_dir, pending)
+                .and_then(|(mut pending_backup, files)| record_backup(conn, &mut pending_backup, files, mission, procedure));
+            if let Err(error) = uploaded {
+                error!("failed to upload staged backup {} of mission {}, errMsg: {:?}", pending_time, mission.mission_id, error);
             }
         }
+    }
+    let backup_dir = &stage_dir.join(timestamp);
+
+    let backup_path = backup_dir.join(&target_name);
+
+    let mut save_path = backup_path.display().to_string();
+
+    // hard link snapshot needs plain copy of the same sources in local save location
+    backup.sources = serde_json::to_string(&mapping).unwrap_or_default();
+    let previous = match procedure.backup_mode == 3 && !is_upload && !procedure.is_compress {
+        true => previous_snapshot(conn, mission, &backup.sources),
+        false => None,
+    };
+
+    // copy from src to dst with or without ignore rules, 
+    // multiple sources are copied side by side under one directory
+    let mut linked_size: Option<u64> = None;
+    let mut copy_stats = CopyStats::default();
+    if let Some(previous) = previous {
+        let (root, base) = match (mapping.len(), previous.parent()) {
+            (1, Some(parent)) => (backup_dir.to_path_buf(), parent.to_path_buf()),
+            _ => (backup_path.clone(), previous),
+        };
+        linked_size = Some(link_sources(&mapping, rules, &root, &base, &mut copy_stats)?);
+    } else if mapping.len() == 1 {
+        for source in mapping.values() {
+            copy_stats.merge(copy_source(source, &save_path, rules)?);
+        }
+    } else {
+        for (entry_name, source) in mapping.iter() {
+            let entry_path = backup_path.join(entry_name).display().to_string();
+            copy_stats.merge(copy_source(source, &entry_path, rules)?);
+        }
+    }
+    backup.copy_method = copy_stats.method();
+    backup.skipped = skipped_json(&copy_stats.skipped);
 
-        // restrict save path
-        if procedure.restrict != 0 {
-            if let Some(backup_path) = backup_dir.parent() {
-                let restrict_path = backup_path.display().to_string();
+    // files are recorded before packed, keyed by entry like those of delta sync
+    let files = match mapping.len() {
+        1 => manifest_files(backup_dir, &backup_path)?,
+        _ => manifest_files(&backup_path, &backup_path)?,
+    };
 
-                match procedure.restrict {
-                    1 => { // restrict days
-                        restrict_dir_subitems_count(&restrict_path, procedure.restrict_days as usize)?;
-                    },
-                    2 => { // restrict size
-                        restrict_dir_subitems_size(&restrict_path, procedure.restrict_size as u64)?;
-                    },
-                    3 => { // restrict days and size
-                        restrict_dir_subitems_count(&restrict_path, procedure.restrict_days as usize)?;
-                        restrict_dir_subitems_size(&restrict_path, procedure.restrict_size as u64)?;
-                    },
-                    _ => {
-                        // do nothing
-                    }
-                }                
+    // whether create archive
+    if procedure.is_compress {
+        let support_formats = Vec::from(["zip", "tar.gz", "tar.bz2", "tar.xz", "7z", "tar.zst", "tar.lz4"]);
+        let mut archive_format: &str = "";
+        for (idx, format) in support_formats.iter().enumerate()  {
+            if procedure.compress_format - 1 == (idx as i16) {
+                archive_format = format;
+                break;
             }
         }
 
-        // clear unavailable backup records
-        if let Ok(cur_backups) = query_backup_record(conn, None, Some(mid)) {
-            for item in &cur_backups {
-                if !Path::new(&item.save_path).exists() {
-                    if let Ok(_) = delete_backup_record(conn, Some(&item.backup_id), None) {
-                        println!("failed to delete invalid backup: {}", item.save_path);
-                    }
+        if !archive_format.is_empty() {
+            let password = get_archive_password(procedure)?;
+            let archive_path = backup_dir.join(format!("{}.{}", target_name, archive_format));
+            let options = ArchiveOptions {
+                write_limit: throttle.write_limit,
+                priority: throttle.compress_priority,
+                level: u32::try_from(procedure.compress_level).ok(),
+                method: procedure.compress_method,
+                dict_size: (procedure.dict_size.max(0) as u32).saturating_mul(1024 * 1024),
+                threads: match procedure.compress_threads {
+                    0 => std::thread::available_parallelism().map_or(1, |count| count.get() as u32),
+                    count => count.max(1) as u32,
+                },
+                password: password.as_ref().map(|(_, password)| password.clone()),
+            };
+            let archived = create_archive_with(&save_path, archive_path.display().to_string().as_str(), &options);
+            if let (Err(error), Some(_)) = (&archived, &options.password) {
+                error!("failed to encrypt backup of mission {}, errMsg: {:?}", mission.mission_id, error);
+                let _ = remove_all(backup_dir.display().to_string().as_str());
+                return Err(Error::new(error.kind(), "failed to encrypt backup"));
+            }
+            if let Ok(()) = archived {
+                remove_all(&save_path)?;
+                save_path = archive_path.display().to_string();
+                backup.is_encrypted = password.map_or(0, |(version, _)| version);
+
+                // archive larger than one volume is split, record keeps the first volume
+                let volume_size = (procedure.volume_size.max(0) as u64).saturating_mul(1024 * 1024);
+                let volumes = split_volumes(&save_path, volume_size)?;
+                if volumes.len() > 1 {
+                    backup.volumes = volumes.len() as i32;
+                    save_path = volumes[0].clone();
                 }
             }
         }
+    }
 
-        // update mission status
-        let _ = update_mission_status(conn, 1, &mission.mission_id);
-
-        return Ok(backup);
+    backup.save_path = save_path.to_string();
+    let volumes = volume_locations(&save_path, backup.volumes);
+    backup.backup_size = volumes.iter().filter_map(|volume| get_path_size(volume).ok()).sum::<u64>() as i64;
+    if let Some(linked_size) = linked_size {
+        backup.backup_size = linked_size as i64;
+    }
+    if is_upload {
+        let pending = PendingUpload {
+            location: mission.dst_path.clone(),
+            timestamp: timestamp.to_string(),
+            backup,
+            files,
+        };
+        write_json_file(&stage_dir.join(format!("{}{}", timestamp, PENDING_SUFFIX)), &pending)?;
+        return upload_pending(&stage_dir, pending);
     }
 
-    Err(Error::from(ErrorKind::NotFound))
+    Ok((backup, files))
 }
 
-/// Physically delete backup in disk.
-/// 
-/// Logically delete backup in record.
+/// Suffix of file describing staged backup not uploaded yet, beside its `<timestamp>/` in stage directory.
+const PENDING_SUFFIX: &str = ".pending.json";
+
+/// Struct PendingUpload
 /// 
-/// # Arguments
+/// Staged backup kept until uploaded, so failed upload resumes with the same files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingUpload {
+    /// Remote save location
+    location: String,
+
+    /// Timestamp directory of backup
+    timestamp: String,
+
+    /// Unsaved backup record, `save_path` is the staged save
+    backup: Backup,
+
+    /// Files for manifest
+    files: std::collections::BTreeMap<String, ManifestFile>,
+}
+
+/// Take staged backups not uploaded yet for `location`, the oldest first.
 /// 
-/// * `bid` - Uuid for backup.
-/// * `conn` - Connection to database.
+/// Staged backups for other locations or with missing files are removed, as they would never be uploaded.
+fn take_pending_uploads(stage_dir: &std::path::Path, location: &str) -> Vec<PendingUpload> {
+    use crate::utils::explorer::remove_all;
+    use std::path::Path;
+    use log::warn;
+
+    let entries = match std::fs::read_dir(stage_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut pendings: Vec<(std::path::PathBuf, Option<PendingUpload>)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.to_string_lossy().ends_with(PENDING_SUFFIX))
+        .map(|path| {
+            let pending = read_json_file::<PendingUpload>(&path).ok();
+            (path, pending)
+        })
+        .collect();
+    pendings.sort_by_key(|(_, pending)| pending.as_ref().map(|pending| pending.timestamp.clone()));
+
+    let mut found: Vec<PendingUpload> = Vec::new();
+    for (path, pending) in pendings {
+        match pending {
+            Some(pending) if pending.location == location && Path::new(&pending.backup.save_path).exists() => {
+                found.push(pending);
+            },
+            pending => {
+                warn!("discard staged backup {}, errMsg: not uploadable to {}", path.display(), location);
+                if let Some(pending) = pending {
+                    let _ = remove_all(stage_dir.join(&pending.timestamp).display().to_string().as_str());
+                }
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    found
+}
+
+/// Upload staged backup to its remote location, staged files are removed once all uploaded.
 /// 
-/// # Examples
+/// Returns unsaved backup record with files for manifest.
+fn upload_pending(
+    stage_dir: &std::path::Path,
+    pending: PendingUpload
+) -> Result<(Backup, std::collections::BTreeMap<String, ManifestFile>), std::io::Error> {
+    use crate::utils::explorer::remove_all;
+    use crate::storage::{ join_location, open_storage };
+    use std::path::Path;
+
+    let PendingUpload { location, timestamp, mut backup, files } = pending;
+    let (storage, root) = open_storage(&location)?;
+    let mut uploaded: Vec<String> = Vec::new();
+    for volume in volume_locations(&backup.save_path, backup.volumes) {
+        let save_name = Path::new(&volume).file_name().map_or(String::new(), |name| name.to_string_lossy().to_string());
+        let relative = format!("{}/{}", timestamp, save_name);
+        storage.put(&volume, &storage.join(&root, &relative))?;
+        uploaded.push(relative);
+    }
+
+    remove_all(stage_dir.join(&timestamp).display().to_string().as_str())?;
+    std::fs::remove_file(stage_dir.join(format!("{}{}", timestamp, PENDING_SUFFIX)))?;
+    backup.save_path = join_location(&location, &uploaded[0]);
+
+    Ok((backup, files))
+}
+
+/// Get save path of previous plain snapshot with the same sources in local save location.
+fn previous_snapshot(conn: &mut SqliteConnection, mission: &Mission, sources: &str) -> Option<std::path::PathBuf> {
+    use std::path::{ Path, PathBuf };
+
+    query_backup_record(conn, None, Some(&mission.mission_id))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|item| item.destination_id.is_empty() && item.chain_id.is_empty() && item.is_mirror == 0 && item.sources == sources)
+        .filter(|item| Path::new(&item.save_path).parent().and_then(|dir| dir.parent()) == Some(Path::new(&mission.dst_path)))
+        .max_by(|a, b| a.create_at.cmp(&b.create_at))
+        .map(|item| PathBuf::from(item.save_path))
+        .filter(|path| path.exists())
+}
+
+/// Copy files of sources into `root`, unchanged files are hard linked to the same ones in `base`.
 /// 
-/// ```
-/// use db::{establish_sqlite_connection, backup::delete_backup};
+/// Returns size of files copied, counts of copy methods and skipped files are added to `stats`.
+fn link_sources(
+    mapping: &std::collections::BTreeMap<String, String>,
+    rules: &crate::utils::explorer::IgnoreRules,
+    root: &std::path::Path,
+    base: &std::path::Path,
+    stats: &mut crate::utils::explorer::CopyStats
+) -> Result<u64, std::io::Error> {
+    use crate::utils::{
+        explorer::{ CopyStats, copy_file_limited, run_with_policy },
+        throttle::RateLimiter
+    };
+    use std::fs::{ create_dir_all, hard_link, metadata };
+
+    let read = RateLimiter::new(rules.read_limit);
+    let write = RateLimiter::new(rules.write_limit);
+    let mut copied_size = 0;
+    for (key, file) in list_source_files(mapping, rules, &mut stats.skipped)? {
+        let save = root.join(&key);
+        let base_file = base.join(&key);
+        let linked = run_with_policy(rules, &file, &mut stats.skipped, || {
+            let meta = metadata(&file)?;
+            if let Some(parent) = save.parent() {
+                create_dir_all(parent)?;
+            }
+
+            // copy keeps time of source, so file is unchanged with the same size and time, like delta sync
+            let is_unchanged = match (metadata(&base_file), modified_secs(&meta)) {
+                (Ok(base_meta), Some(modified)) => {
+                    base_meta.is_file() && base_meta.len() == meta.len() && modified_secs(&base_meta) == Some(modified)
+                },
+                _ => false,
+            };
+            if is_unchanged && hard_link(&base_file, &save).is_ok() {
+                return Ok((0, CopyStats::default()));
+            }
+
+            Ok((meta.len(), copy_file_limited(&file, &save, &read, &write)?))
+        })?;
+
+        if let Some((size, copied)) = linked {
+            stats.merge(copied);
+            copied_size += size;
+        }
+    }
+
+    Ok(copied_size)
+}
+
+/// Get modified time of file in seconds since unix epoch.
+fn modified_secs(meta: &std::fs::Metadata) -> Option<u64> {
+    use std::time::UNIX_EPOCH;
+
+    meta.modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+}
+
+/// Count of syncs in one delta chain, a new full sync starts after that.
+const DELTA_CHAIN_LIMIT: u32 = 10;
+
+/// Manifest of delta sync, saved as `delta.json` in sync directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DeltaManifest {
+    /// Timestamp of previous sync in chain, empty for full sync
+    base: String,
+
+    /// Count of syncs in chain until this one
+    chain: u32,
+
+    /// Files in sync, keyed by `<entry>/<relative path>`
+    files: std::collections::BTreeMap<String, DeltaFile>,
+}
+
+/// File recorded in delta sync.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DeltaFile {
+    /// How file is saved
+    /// 
+    /// `0` - unchanged, same as previous sync
+    /// 
+    /// `1` - full copy in `data/<key>`
+    /// 
+    /// `2` - patch on previous sync in `data/<key>.patch`
+    kind: i16,
+
+    /// File size in bytes
+    size: u64,
+
+    /// Modified time in seconds since unix epoch
+    modified: u64,
+}
+
+/// Lists files of sources with ignore rules, keyed by `<entry>/<relative path>`.
 /// 
-/// if let Ok(mut conn) = establish_sqlite_connection() {
-///     let bid = "661b7d0e-a52c-457e-89e1-2ffe9a230c14";
-///     match delete_backup(backup_id, &mut conn) {
-///         Ok(cnt) => {
-///             println!("remove {} backup with id {}", cnt, backup_id);
-///         },
-///         Err(error) => {
-///             println!("failed to remove backup, errMsg: {:?}", error);
-///         }
-///     }   
-/// }
-/// ```
-pub fn delete_backup(bid: &str, conn: &mut SqliteConnection) -> Result<(), std::io::Error> {
-    use crate::utils::explorer::remove_all;
+/// Special files and items failed to read are added to `skipped`, see [walk_dir_with_rules](crate::utils::explorer::walk_dir_with_rules).
+fn list_source_files(
+    mapping: &std::collections::BTreeMap<String, String>,
+    rules: &crate::utils::explorer::IgnoreRules,
+    skipped: &mut Vec<crate::utils::explorer::SkippedItem>
+) -> Result<std::collections::BTreeMap<String, String>, std::io::Error> {
+    use crate::utils::explorer::{ SkippedItem, walk_dir_with_rules };
+    use std::collections::BTreeMap;
     use std::path::Path;
+
+    let mut files: BTreeMap<String, String> = BTreeMap::new();
+    for (entry_name, source) in mapping.iter() {
+        let src = Path::new(source);
+        if src.is_file() {
+            files.insert(entry_name.clone(), source.clone());
+            continue;
+        }
+
+        let walk_skipped = walk_dir_with_rules(source, rules, |path, file_type, reason| {
+            if reason.is_some() || file_type.is_dir() {
+                return Ok(());
+            }
+            if !file_type.is_file() && !file_type.is_symlink() {
+                skipped.push(SkippedItem {
+                    path: path.display().to_string(),
+                    reason: "special file".to_string(),
+                });
+                return Ok(());
+            }
+
+            let relative = path.strip_prefix(src).unwrap_or(path);
+            let key = format!("{}/{}", entry_name, relative.to_string_lossy().replace('\\', "/"));
+            files.insert(key, path.display().to_string());
+            Ok(())
+        })?;
+        skipped.extend(walk_skipped);
+    }
+
+    Ok(files)
+}
+
+/// Serializes skipped items for backup record, empty if nothing skipped.
+fn skipped_json(skipped: &[crate::utils::explorer::SkippedItem]) -> String {
+    match skipped.is_empty() {
+        true => String::new(),
+        false => serde_json::to_string(skipped).unwrap_or_default(),
+    }
+}
+
+/// Reads json file saved with backup.
+fn read_json_file<T: serde::de::DeserializeOwned>(path: &std::path::Path) -> Result<T, std::io::Error> {
     use std::io::{ Error, ErrorKind };
-    
-    if let Ok(record) = query_backup_record(conn, Some(bid), None) {
-        if record.len() > 0 {
-            let backup = record[0].clone();
 
-            if let Some(backup_dir) = Path::new(&backup.save_path).parent() {
-                remove_all(backup_dir.display().to_string().as_str())?;
+    let content = std::fs::read_to_string(path)?;
+    serde_json::from_str(&content).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+}
 
-                if let Ok(_) = delete_backup_record(conn, Some(bid), None) {
-                    return Ok(());
-                }
-            }            
+/// Writes json file saved with backup.
+fn write_json_file<T: Serialize>(path: &std::path::Path, data: &T) -> Result<(), std::io::Error> {
+    use std::io::{ Error, ErrorKind };
+
+    let content = serde_json::to_string(data).map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+    std::fs::write(path, content)
+}
+
+/// File name of manifest saved with each backup.
+const MANIFEST_NAME: &str = "backup-manifest.json";
+
+/// Version of manifest format, increased on incompatible changes.
+const MANIFEST_VERSION: u32 = 1;
+
+/// Struct BackupManifest
+/// 
+/// Describes backup without database, so backups in save location can be told apart and recorded again.
+/// Snapshot keeps it beside its save in `<timestamp>/`, delta sync and mirror keep it inside their save.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Version of manifest format
+    pub version: u32,
+
+    /// Version of app creating backup
+    pub app_version: String,
+
+    /// Backup record, `save_path` is where this copy is saved
+    pub backup: Backup,
+
+    /// Mission of backup, with its source paths
+    pub mission: Mission,
+
+    /// Procedure settings when backup is created, passwords are never included
+    pub procedure: super::procedure::Procedure,
+
+    /// Files in backup, keyed by `<entry>/<relative path>`
+    pub files: std::collections::BTreeMap<String, ManifestFile>,
+}
+
+/// Struct ManifestFile
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// File size in bytes
+    pub size: u64,
+
+    /// Modified time in seconds since unix epoch
+    pub modified: u64,
+
+    /// SHA256 of file content in upper hex, empty if unknown
+    pub hash: String,
+}
+
+/// Records file for manifest with hash of its content.
+fn manifest_file(path: &str) -> Result<ManifestFile, std::io::Error> {
+    use crate::utils::crypto::encode_sha2_file;
+    use std::time::UNIX_EPOCH;
+
+    let meta = std::fs::metadata(path)?;
+    Ok(ManifestFile {
+        size: meta.len(),
+        modified: meta.modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_secs()),
+        hash: encode_sha2_file(path)?,
+    })
+}
+
+/// Records files under `dir` for manifest, keyed by path relative to `root`.
+fn manifest_files(
+    root: &std::path::Path,
+    dir: &std::path::Path
+) -> Result<std::collections::BTreeMap<String, ManifestFile>, std::io::Error> {
+    use std::collections::BTreeMap;
+
+    let mut files: BTreeMap<String, ManifestFile> = BTreeMap::new();
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
         }
+
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        let key = relative.to_string_lossy().replace('\\', "/");
+        files.insert(key, manifest_file(entry.path().display().to_string().as_str())?);
     }
 
-    Err(Error::from(ErrorKind::NotFound))
+    Ok(files)
+}
+
+/// Get location of manifest of backup.
+fn manifest_location(backup: &Backup) -> Option<String> {
+    use crate::storage::{ join_location, parent_location };
+
+    match backup.chain_id.is_empty() && backup.is_mirror == 0 {
+        true => parent_location(&backup.save_path).map(|dir| join_location(&dir, MANIFEST_NAME)),
+        false => Some(join_location(&backup.save_path, MANIFEST_NAME)),
+    }
+}
+
+/// Reads manifest in location, remote one is downloaded first.
+fn read_manifest(location: &str) -> Result<BackupManifest, std::io::Error> {
+    use crate::utils::explorer::remove_all;
+    use std::path::Path;
+
+    let (local, fetched) = fetch_save(location)?;
+    let manifest = read_json_file(Path::new(&local));
+    if let Some(fetched) = fetched {
+        let _ = remove_all(&fetched);
+    }
+
+    manifest
+}
+
+/// Reads manifest of backup.
+fn read_manifest_of(backup: &Backup) -> Result<BackupManifest, std::io::Error> {
+    use std::io::{ Error, ErrorKind };
+
+    let location = manifest_location(backup).ok_or(Error::from(ErrorKind::InvalidInput))?;
+    read_manifest(&location)
+}
+
+/// Saves manifest with its backup, replaces the existing one.
+fn save_manifest(manifest: &BackupManifest) -> Result<(), std::io::Error> {
+    use crate::storage::open_storage;
+    use std::io::{ Error, ErrorKind };
+    use std::fs::{ create_dir_all, remove_file };
+
+    let location = manifest_location(&manifest.backup).ok_or(Error::from(ErrorKind::InvalidInput))?;
+    let (storage, path) = open_storage(&location)?;
+
+    let work_dir = std::env::temp_dir().join("mission_backup");
+    create_dir_all(&work_dir)?;
+    let local = work_dir.join(format!("{}.json", Uuid::new_v4()));
+    let saved = write_json_file(&local, manifest).and_then(|_| storage.put(local.display().to_string().as_str(), &path));
+    let _ = remove_file(&local);
+
+    saved
+}
+
+/// Sync changed blocks of sources into `<dst_path>/<chain>/<timestamp>/`.
+/// 
+/// Each sync only saves files changed since the previous sync of its chain, as patches against
+/// signatures of previous files, so remote storages without server side processing work as well.
+/// A chain starts with a full sync, then a new chain starts when it reaches [DELTA_CHAIN_LIMIT].
+/// 
+/// Returns unsaved backup record with files for manifest.
+fn create_delta_sync(
+    mission: &Mission,
+    rules: &crate::utils::explorer::IgnoreRules,
+    timestamp: &str,
+    conn: &mut SqliteConnection
+) -> Result<(Backup, std::collections::BTreeMap<String, ManifestFile>), std::io::Error> {
+    use crate::utils::{
+        delta::FileSignature,
+        explorer::{ SkippedItem, remove_all, get_path_size, run_with_policy },
+        throttle::RateLimiter
+    };
+    use crate::storage::{ join_location, open_storage, parent_location };
+    use std::collections::BTreeMap;
+    use std::fs::create_dir_all;
+    use std::path::Path;
+
+    let mapping = map_sources(mission);
+    let (storage, root) = open_storage(&mission.dst_path)?;
+
+    // previous sync in save location, destination copies are not involved
+    let previous = query_backup_record(conn, None, Some(&mission.mission_id))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|item| !item.chain_id.is_empty() && item.destination_id.is_empty())
+        .filter(|item| parent_location(&item.save_path) == Some(join_location(&mission.dst_path, &item.chain_id)))
+        .max_by(|a, b| a.create_at.cmp(&b.create_at));
+
+    // own work dir of delta sync, beside staged uploads of the same mission
+    let work_dir = std::env::temp_dir().join("mission_backup").join(&mission.mission_id).join("delta");
+    let base_dir = work_dir.join("base");
+    let stage_dir = work_dir.join(timestamp);
+    if work_dir.exists() {
+        remove_all(work_dir.display().to_string().as_str())?;
+    }
+    create_dir_all(stage_dir.join("data"))?;
+
+    // signatures of previous sync, start new chain if not available
+    let mut chain_id = timestamp.to_string();
+    let mut manifest = DeltaManifest { base: String::new(), chain: 1, files: BTreeMap::new() };
+    let mut base_signatures: BTreeMap<String, FileSignature> = BTreeMap::new();
+    if let Some(previous) = &previous {
+        let base_name = Path::new(&previous.save_path).file_name().map_or(String::new(), |name| name.to_string_lossy().to_string());
+        let base_path = storage.join(&root, &format!("{}/{}", previous.chain_id, base_name));
+        let fetched = storage.get(&storage.join(&base_path, "delta.json"), base_dir.join("delta.json").display().to_string().as_str())
+            .and_then(|_| storage.get(&storage.join(&base_path, "signatures.json"), base_dir.join("signatures.json").display().to_string().as_str()))
+            .and_then(|_| read_json_file::<DeltaManifest>(&base_dir.join("delta.json")))
+            .and_then(|base| Ok((base, read_json_file::<BTreeMap<String, FileSignature>>(&base_dir.join("signatures.json"))?)));
+        if let Ok((base, signatures)) = fetched {
+            if base.chain < DELTA_CHAIN_LIMIT {
+                chain_id = previous.chain_id.clone();
+                manifest.base = base_name;
+                manifest.chain = base.chain + 1;
+                base_signatures = signatures;
+            }
+        }
+    }
+
+    let read = RateLimiter::new(rules.read_limit);
+    let write = RateLimiter::new(rules.write_limit);
+    let mut signatures: BTreeMap<String, FileSignature> = BTreeMap::new();
+    let mut skipped: Vec<SkippedItem> = Vec::new();
+    for (key, file) in list_source_files(&mapping, rules, &mut skipped)? {
+        let data_path = stage_dir.join("data").join(&key);
+        let base = base_signatures.get(&key);
+        if let Some((item, signature)) = run_with_policy(rules, &file, &mut skipped, || sync_delta_file(&file, &data_path, base, &read, &write))? {
+            signatures.insert(key.clone(), signature);
+            manifest.files.insert(key, item);
+        }
+    }
+
+    write_json_file(&stage_dir.join("delta.json"), &manifest)?;
+    write_json_file(&stage_dir.join("signatures.json"), &signatures)?;
+
+    let mut backup = Backup {
+        backup_size: get_path_size(stage_dir.display().to_string().as_str()).unwrap_or(0) as i64,
+        sources: serde_json::to_string(&mapping).unwrap_or_default(),
+        chain_id: chain_id.clone(),
+        skipped: skipped_json(&skipped),
+        ..Backup::default()
+    };
+
+    let relative = format!("{}/{}", chain_id, timestamp);
+    let upload = storage.put(stage_dir.display().to_string().as_str(), &storage.join(&root, &relative));
+    let _ = remove_all(work_dir.display().to_string().as_str());
+    upload?;
+    backup.save_path = join_location(&mission.dst_path, &relative);
+
+    // unchanged files share signatures with previous sync, hashes included
+    let files = manifest.files.iter()
+        .map(|(key, item)| (key.clone(), ManifestFile {
+            size: item.size,
+            modified: item.modified,
+            hash: signatures.get(key).map_or(String::new(), |signature| signature.hash.clone()),
+        }))
+        .collect();
+
+    Ok((backup, files))
+}
+
+/// Saves file of delta sync into `data_path`, as patch against `base` if smaller than full copy.
+/// 
+/// Reading source and writing save are limited by `read` and `write`, see [RateLimiter](crate::utils::throttle::RateLimiter).
+/// 
+/// Returns record of file with signature of its current content.
+fn sync_delta_file(
+    file: &str,
+    data_path: &std::path::Path,
+    base: Option<&crate::utils::delta::FileSignature>,
+    read: &crate::utils::throttle::RateLimiter,
+    write: &crate::utils::throttle::RateLimiter
+) -> Result<(DeltaFile, crate::utils::delta::FileSignature), std::io::Error> {
+    use crate::utils::{
+        delta::{ file_signature, write_delta },
+        explorer::copy_file_limited
+    };
+    use std::fs::{ canonicalize, create_dir_all, metadata, remove_file };
+
+    let meta = metadata(file)?;
+    let modified = modified_secs(&meta).unwrap_or(0);
+    if let Some(parent) = data_path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    let mut item = DeltaFile { kind: 1, size: meta.len(), modified };
+    match base {
+        Some(signature) if signature.size == item.size && signature.modified == item.modified => {
+            item.kind = 0;
+            return Ok((item, signature.clone()));
+        },
+        Some(signature) => {
+            // source is read once for patch and once for signature
+            let patch_path = format!("{}.patch", data_path.display());
+            let stats = write_delta(signature, file, &patch_path)?;
+            read.consume(item.size);
+            write.consume(metadata(&patch_path).map_or(0, |meta| meta.len()));
+            if stats.literal < item.size {
+                item.kind = 2;
+                let signature = file_signature(file)?;
+                read.consume(item.size);
+                return Ok((item, signature));
+            }
+            remove_file(&patch_path)?;
+        },
+        None => {},
+    }
+
+    // signature is taken from source, so its time matches the next sync of unchanged file,
+    // links are followed as delta works on content
+    copy_file_limited(canonicalize(file)?, data_path, read, write)?;
+    let signature = file_signature(file)?;
+    read.consume(item.size);
+    Ok((item, signature))
+}
+
+/// Restore delta sync by applying patches along its chain.
+fn restore_delta_sync(
+    backup: &Backup,
+    mapping: &std::collections::BTreeMap<String, String>,
+    target: Option<&str>
+) -> Result<Vec<String>, std::io::Error> {
+    use crate::utils::{
+        delta::apply_delta,
+        explorer::{ merge_all, remove_all }
+    };
+    use crate::storage::{ open_storage, parent_location };
+    use std::fs::{ copy, create_dir_all, remove_file, rename };
+    use std::path::{ Path, PathBuf };
+    use std::io::{ Error, ErrorKind };
+
+    let chain_location = parent_location(&backup.save_path).ok_or(Error::from(ErrorKind::InvalidInput))?;
+    let (storage, chain_path) = open_storage(&chain_location)?;
+    let work_dir = std::env::temp_dir().join("mission_backup").join(Uuid::new_v4().to_string());
+
+    let result = (|| {
+        // download syncs from this one back to the full sync of chain
+        let mut syncs: Vec<(PathBuf, DeltaManifest)> = Vec::new();
+        let mut sync_name = Path::new(&backup.save_path).file_name().map_or(String::new(), |name| name.to_string_lossy().to_string());
+        while !sync_name.is_empty() {
+            let sync_dir = work_dir.join(&sync_name);
+            storage.get(&storage.join(&chain_path, &sync_name), sync_dir.display().to_string().as_str())?;
+            let manifest: DeltaManifest = read_json_file(&sync_dir.join("delta.json"))?;
+            sync_name = manifest.base.clone();
+            syncs.push((sync_dir, manifest));
+        }
+
+        let restoring = work_dir.join("restoring");
+        for key in syncs[0].1.files.keys() {
+            // find full copy of file, with patches on it since then
+            let mut full: Option<PathBuf> = None;
+            let mut patches: Vec<PathBuf> = Vec::new();
+            for (sync_dir, manifest) in syncs.iter() {
+                match manifest.files.get(key).map(|item| item.kind) {
+                    Some(0) => continue,
+                    Some(1) => {
+                        full = Some(sync_dir.join("data").join(key));
+                        break;
+                    },
+                    Some(2) => patches.push(PathBuf::from(format!("{}.patch", sync_dir.join("data").join(key).display()))),
+                    _ => break,
+                }
+            }
+            let full = full.ok_or(Error::from(ErrorKind::InvalidData))?;
+
+            let restore_file = restoring.join(key);
+            if let Some(parent) = restore_file.parent() {
+                create_dir_all(parent)?;
+            }
+            let mut current = full.clone();
+            for (idx, patch) in patches.iter().rev().enumerate() {
+                let next = PathBuf::from(format!("{}.{}", restore_file.display(), idx));
+                apply_delta(current.display().to_string().as_str(), patch.display().to_string().as_str(), next.display().to_string().as_str())?;
+                if current != full {
+                    remove_file(&current)?;
+                }
+                current = next;
+            }
+            if current == full {
+                copy(&current, &restore_file)?;
+            } else {
+                rename(&current, &restore_file)?;
+            }
+        }
+
+        let mut restored = Vec::new();
+        for (entry, origin) in mapping.iter() {
+            let item = restoring.join(entry);
+            if !item.exists() {
+                continue;
+            }
+
+            let restore_path = match target {
+                Some(dir) => Path::new(dir).join(entry).display().to_string(),
+                None => origin.clone(),
+            };
+            merge_all(item.display().to_string().as_str(), &restore_path)?;
+            restored.push(restore_path);
+        }
+
+        Ok(restored)
+    })();
+
+    let _ = remove_all(work_dir.display().to_string().as_str());
+
+    result
+}
+
+/// Delete delta sync with the later syncs depending on it in the same chain and location.
+/// 
+/// The whole chain is deleted with its full sync.
+fn delete_delta_sync(backup: &Backup, conn: &mut SqliteConnection) -> Result<(), std::io::Error> {
+    use crate::storage::{ open_storage, parent_location };
+    use std::path::Path;
+    use std::io::{ Error, ErrorKind };
+
+    let sync_name = |item: &Backup| Path::new(&item.save_path).file_name().map_or(String::new(), |name| name.to_string_lossy().to_string());
+    let name = sync_name(backup);
+    let chain_location = parent_location(&backup.save_path).ok_or(Error::from(ErrorKind::InvalidInput))?;
+
+    let dependents: Vec<Backup> = query_backup_record(conn, None, Some(&backup.mission_id))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|item| item.chain_id == backup.chain_id && item.destination_id == backup.destination_id)
+        .filter(|item| parent_location(&item.save_path).as_ref() == Some(&chain_location))
+        .filter(|item| sync_name(item).parse::<i64>().unwrap_or(0) >= name.parse::<i64>().unwrap_or(0))
+        .collect();
+
+    let (storage, chain_path) = open_storage(&chain_location)?;
+    let mut removals: Vec<String> = dependents.iter().map(|item| storage.join(&chain_path, &sync_name(item))).collect();
+    if name == backup.chain_id {
+        removals = vec![chain_path];
+    }
+    for removal in removals.iter() {
+        if let Err(error) = storage.delete(removal) {
+            if error.kind() != ErrorKind::NotFound {
+                return Err(error);
+            }
+        }
+    }
+
+    for item in dependents.iter() {
+        let _ = delete_backup_record(conn, Some(&item.backup_id), None);
+    }
+
+    Ok(())
+}
+
+/// Directory in mirror that removed files are moved to.
+const MIRROR_TRASH: &str = ".trash";
+
+/// Lists files under path of storage recursively, keyed by `<key>/<relative path>`.
+fn list_storage_files(
+    storage: &dyn crate::storage::StorageBackend,
+    path: &str,
+    key: &str,
+    files: &mut std::collections::BTreeMap<String, crate::storage::StorageEntry>
+) -> Result<(), std::io::Error> {
+    use std::path::Path;
+    use std::io::ErrorKind;
+
+    let entry = match storage.stat(path) {
+        Ok(entry) => entry,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(error),
+    };
+    if !entry.is_dir {
+        files.insert(key.to_string(), entry);
+        return Ok(());
+    }
+
+    for child in storage.list(path)? {
+        let name = Path::new(child.path.trim_end_matches('/')).file_name().map_or(String::new(), |name| name.to_string_lossy().to_string());
+        let child_key = format!("{}/{}", key, name);
+        match child.is_dir {
+            true => list_storage_files(storage, &child.path, &child_key, files)?,
+            false => {
+                files.insert(child_key, child);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Mirror sources into `<location>/<entry>/`, only new and changed files are copied.
+/// 
+/// Files removed from sources are kept, deleted or moved to `<location>/.trash/<timestamp>/` by `removed`.
+/// Files skipped by error policy keep their previous copies in mirror.
+/// 
+/// If `files` is given, it holds files of previous manifest and is replaced with files of mirror,
+/// unchanged files keep their previous hashes.
+/// 
+/// Returns size of mirror.
+fn sync_mirror(
+    mapping: &std::collections::BTreeMap<String, String>,
+    rules: &crate::utils::explorer::IgnoreRules,
+    location: &str,
+    removed: i16,
+    timestamp: &str,
+    skipped: &mut Vec<crate::utils::explorer::SkippedItem>,
+    mut files: Option<&mut std::collections::BTreeMap<String, ManifestFile>>
+) -> Result<u64, std::io::Error> {
+    use crate::utils::{
+        explorer::run_with_policy,
+        throttle::RateLimiter
+    };
+    use crate::storage::open_storage;
+    use std::collections::BTreeMap;
+    use std::time::UNIX_EPOCH;
+
+    let read = RateLimiter::new(rules.read_limit);
+    let write = RateLimiter::new(rules.write_limit);
+    let (storage, root) = open_storage(location)?;
+    let mut mirrored = BTreeMap::new();
+    for entry_name in mapping.keys() {
+        list_storage_files(storage.as_ref(), &storage.join(&root, entry_name), entry_name, &mut mirrored)?;
+    }
+
+    let is_recording = files.is_some();
+    let mut recorded = files.as_mut().map(|files| std::mem::take(*files)).unwrap_or_default();
+
+    // copy of file is newer than source, unless source changed after last run
+    let mut mirror_size = 0;
+    for (key, file) in list_source_files(mapping, rules, skipped)? {
+        let previous = mirrored.remove(&key);
+        let previous_record = recorded.remove(&key);
+        let synced = run_with_policy(rules, &file, skipped, || {
+            let meta = std::fs::metadata(&file)?;
+            let modified = meta.modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |duration| duration.as_secs());
+            let is_changed = match &previous {
+                Some(item) => item.size != meta.len() || item.modified < modified,
+                None => true,
+            };
+            if is_changed {
+                put_limited(storage.as_ref(), &file, &storage.join(&root, &key), &read, &write)?;
+            }
+
+            let record = match &previous_record {
+                _ if !is_recording => None,
+                Some(item) if !is_changed && item.size == meta.len() && item.modified == modified => Some(item.clone()),
+                _ => Some(manifest_file(&file)?),
+            };
+            Ok((meta.len(), record))
+        })?;
+
+        // file skipped keeps its previous copy and record
+        let (size, record) = match synced {
+            Some(synced) => synced,
+            None => (previous.map_or(0, |item| item.size), previous_record),
+        };
+        mirror_size += size;
+        if let (Some(files), Some(record)) = (files.as_mut(), record) {
+            files.insert(key, record);
+        }
+    }
+
+    for (key, item) in mirrored.iter() {
+        let path = storage.join(&root, key);
+        match removed {
+            1 => storage.delete(&path)?,
+            2 => storage.rename(&path, &storage.join(&root, &format!("{}/{}/{}", MIRROR_TRASH, timestamp, key)))?,
+            _ => {
+                mirror_size += item.size;
+                if let (Some(files), Some(record)) = (files.as_mut(), recorded.remove(key)) {
+                    files.insert(key.clone(), record);
+                }
+            },
+        }
+    }
+
+    Ok(mirror_size)
+}
+
+/// Put single file into storage, limited by `read` and `write`.
+/// 
+/// File to local storage is copied with limited rate, upload to remote storage is followed by
+/// waiting for its bytes, so the rate is kept on average.
+fn put_limited(
+    storage: &dyn crate::storage::StorageBackend,
+    file: &str,
+    remote: &str,
+    read: &crate::utils::throttle::RateLimiter,
+    write: &crate::utils::throttle::RateLimiter
+) -> Result<(), std::io::Error> {
+    use crate::utils::explorer::{ copy_file_limited, remove_all };
+    use std::fs::{ create_dir_all, metadata };
+    use std::path::Path;
+
+    if storage.is_local() {
+        let save = Path::new(remote);
+        if save.is_dir() {
+            remove_all(remote)?;
+        }
+        if let Some(parent) = save.parent() {
+            create_dir_all(parent)?;
+        }
+        copy_file_limited(file, save, read, write)?;
+        return Ok(());
+    }
+
+    storage.put(file, remote)?;
+    let size = metadata(file).map_or(0, |meta| meta.len());
+    read.consume(size);
+    write.consume(size);
+
+    Ok(())
+}
+
+/// Delete records of previous mirror in the same location, as each run replaces it.
+fn clear_mirror_records(conn: &mut SqliteConnection, mid: &str, destination_id: &str, save_path: &str) {
+    if let Ok(records) = query_backup_record(conn, None, Some(mid)) {
+        for item in records.iter() {
+            if item.is_mirror == 1 && item.destination_id == destination_id && item.save_path == save_path {
+                let _ = delete_backup_record(conn, Some(&item.backup_id), None);
+            }
+        }
+    }
+}
+
+/// Mirror sources into `<dst_path>/<entry>/`.
+/// 
+/// Returns unsaved backup record with files for manifest, which replaces the record of previous run.
+fn create_mirror(
+    mission: &Mission,
+    procedure: &super::procedure::Procedure,
+    rules: &crate::utils::explorer::IgnoreRules,
+    timestamp: &str,
+    conn: &mut SqliteConnection
+) -> Result<(Backup, std::collections::BTreeMap<String, ManifestFile>), std::io::Error> {
+    use crate::storage::join_location;
+
+    let mapping = map_sources(mission);
+    let mut skipped = Vec::new();
+    let mut files = read_manifest(&join_location(&mission.dst_path, MANIFEST_NAME)).map(|manifest| manifest.files).unwrap_or_default();
+    let mirror_size = sync_mirror(&mapping, rules, &mission.dst_path, procedure.mirror_removed, timestamp, &mut skipped, Some(&mut files))?;
+    clear_mirror_records(conn, &mission.mission_id, "", &mission.dst_path);
+
+    let backup = Backup {
+        save_path: mission.dst_path.clone(),
+        backup_size: mirror_size as i64,
+        sources: serde_json::to_string(&mapping).unwrap_or_default(),
+        is_mirror: 1,
+        skipped: skipped_json(&skipped),
+        ..Backup::default()
+    };
+
+    Ok((backup, files))
+}
+
+/// Mirror sources of backup into destination.
+/// 
+/// Returns size of mirror.
+fn replicate_mirror(
+    backup: &Backup,
+    location: &str,
+    throttle: &crate::config::throttle::ThrottleConfig,
+    conn: &mut SqliteConnection
+) -> Result<u64, std::io::Error> {
+    use super::{ mission::get_mission_related_record, ignore::get_procedure_rules, procedure::get_procedure_throttle };
+    use std::collections::BTreeMap;
+
+    let record = get_mission_related_record(&backup.mission_id, conn)?;
+    let throttle = get_procedure_throttle(&record.procedure, throttle);
+    let mut rules = get_procedure_rules(&record.procedure, conn);
+    rules.read_limit = throttle.read_limit;
+    rules.write_limit = throttle.write_limit;
+    let mapping: BTreeMap<String, String> = serde_json::from_str(&backup.sources).unwrap_or_default();
+    let timestamp = backup.create_at.and_utc().timestamp().to_string();
+
+    // files skipped here are already recorded in the original mirror
+    sync_mirror(&mapping, &rules, location, record.procedure.mirror_removed, &timestamp, &mut Vec::new(), None)
+}
+
+/// Restore mirror by copying each entry back.
+fn restore_mirror(
+    backup: &Backup,
+    mapping: &std::collections::BTreeMap<String, String>,
+    target: Option<&str>
+) -> Result<Vec<String>, std::io::Error> {
+    use crate::utils::explorer::{ merge_all, remove_all };
+    use crate::storage::join_location;
+    use std::path::Path;
+
+    let mut restored = Vec::new();
+    for (entry, origin) in mapping.iter() {
+        let (item, fetched) = fetch_save(&join_location(&backup.save_path, entry))?;
+        if Path::new(&item).exists() {
+            let restore_path = match target {
+                Some(dir) => Path::new(dir).join(entry).display().to_string(),
+                None => origin.clone(),
+            };
+            merge_all(&item, &restore_path)?;
+            restored.push(restore_path);
+        }
+        if let Some(fetched) = fetched {
+            remove_all(&fetched)?;
+        }
+    }
+
+    Ok(restored)
+}
+
+/// Delete mirror entries with its trash and manifest, other items in location are kept.
+fn delete_mirror(backup: &Backup, conn: &mut SqliteConnection) -> Result<(), std::io::Error> {
+    use crate::storage::open_storage;
+    use std::collections::BTreeMap;
+    use std::io::{ Error, ErrorKind };
+
+    let mapping: BTreeMap<String, String> = serde_json::from_str(&backup.sources).unwrap_or_default();
+    let (storage, root) = open_storage(&backup.save_path)?;
+    for name in mapping.keys().map(|key| key.as_str()).chain([MIRROR_TRASH, MANIFEST_NAME]) {
+        if let Err(error) = storage.delete(&storage.join(&root, name)) {
+            if error.kind() != ErrorKind::NotFound {
+                return Err(error);
+            }
+        }
+    }
+
+    delete_backup_record(conn, Some(&backup.backup_id), None).map_err(|_| Error::from(ErrorKind::Other))?;
+
+    Ok(())
+}
+
+/// Restrict backups in save location, mirror only restricts its trash, delta syncs are restricted by chains.
+fn restrict_saves(
+    backup: &Backup,
+    location: &str,
+    restrict: i16,
+    days: i16,
+    size: i64,
+    conn: &mut SqliteConnection
+) -> Result<(), std::io::Error> {
+    use crate::storage::{ join_location, open_storage };
+
+    if !backup.chain_id.is_empty() {
+        return restrict_chains(backup, location, restrict, days, size, conn);
+    }
+
+    if backup.is_mirror == 0 {
+        return restrict_backups(location, restrict, days, size);
+    }
+
+    let trash = join_location(location, MIRROR_TRASH);
+    let (storage, path) = open_storage(&trash)?;
+    if !storage.exists(&path) {
+        return Ok(());
+    }
+
+    restrict_backups(&trash, restrict, days, size)
+}
+
+/// Restrict backups in save location.
+/// Restrict delta chains in save location, the earliest chains are deleted with their records.
+/// 
+/// Chain of `backup` is always kept, as later syncs depend on it.
+fn restrict_chains(
+    backup: &Backup,
+    location: &str,
+    restrict: i16,
+    days: i16,
+    size: i64,
+    conn: &mut SqliteConnection
+) -> Result<(), std::io::Error> {
+    use crate::storage::{ join_location, parent_location };
+    use std::collections::BTreeMap;
+    use std::io::{ Error, ErrorKind };
+
+    if restrict == 0 {
+        return Ok(());
+    }
+
+    // syncs in location keyed by chain, chain id is the timestamp of its full sync
+    let mut chains: BTreeMap<String, Vec<Backup>> = BTreeMap::new();
+    let records = query_backup_record(conn, None, Some(&backup.mission_id)).map_err(|_| Error::from(ErrorKind::Other))?;
+    for item in records {
+        if !item.chain_id.is_empty() && item.destination_id == backup.destination_id
+            && parent_location(&item.save_path) == Some(join_location(location, &item.chain_id)) {
+            chains.entry(item.chain_id.clone()).or_default().push(item);
+        }
+    }
+
+    let count = match restrict {
+        1 | 3 => days.max(0) as usize,
+        _ => usize::MAX,
+    };
+    let limit = match restrict {
+        2 | 3 => size.max(0) as u64,
+        _ => u64::MAX,
+    };
+    let chain_size = |syncs: &Vec<Backup>| syncs.iter().map(|item| item.backup_size.max(0) as u64).sum::<u64>();
+    let mut remain = chains.len();
+    let mut total: u64 = chains.values().map(chain_size).sum();
+    for (chain_id, syncs) in chains.iter() {
+        if (remain <= count && total <= limit) || *chain_id == backup.chain_id {
+            break;
+        }
+
+        // deleting the earliest sync takes the whole chain with it
+        if let Some(first) = syncs.iter().min_by(|a, b| a.save_path.cmp(&b.save_path)) {
+            delete_delta_sync(first, conn)?;
+        }
+        remain -= 1;
+        total -= chain_size(syncs);
+    }
+
+    Ok(())
+}
+
+fn restrict_backups(location: &str, restrict: i16, days: i16, size: i64) -> Result<(), std::io::Error> {
+    use crate::storage::open_storage;
+
+    if restrict == 0 {
+        return Ok(());
+    }
+
+    let (storage, path) = open_storage(location)?;
+    match restrict {
+        1 => { // restrict days
+            storage.restrict_count(&path, days as usize)?;
+        },
+        2 => { // restrict size
+            storage.restrict_size(&path, size as u64)?;
+        },
+        3 => { // restrict days and size
+            storage.restrict_count(&path, days as usize)?;
+            storage.restrict_size(&path, size as u64)?;
+        },
+        _ => {
+            // do nothing
+        }
+    }
+
+    Ok(())
+}
+
+/// Replicate backup to destination.
+/// 
+/// The copy keeps the same layout as the original backup, 
+/// then backups in destination are restricted by its own retention.
+/// 
+/// # Arguments
+/// 
+/// * `backup` - Original backup.
+/// * `destination` - Where to replicate, status will be updated.
+/// * `throttle` - Throttle in loaded app config, used by mirror.
+/// * `conn` - Connection to database.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, backup::replicate_backup};
+/// use config::throttle::ThrottleConfig;
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let backup = Backup::default();
+///     let mut destination = Destination::default();
+///     match replicate_backup(&backup, &mut destination, &ThrottleConfig::default(), &mut conn) {
+///         Ok(copy) => {
+///             println!("replicate backup to {}", copy.save_path);
+///         },
+///         Err(error) => {
+///             println!("failed to replicate backup, errMsg: {:?}", error);
+///         }
+///     }   
+/// }
+/// ```
+pub fn replicate_backup(
+    backup: &Backup,
+    destination: &mut super::destination::Destination,
+    throttle: &crate::config::throttle::ThrottleConfig,
+    conn: &mut SqliteConnection
+) -> Result<Backup, std::io::Error> {
+    use super::{ destination::update_destination_status, mission::query_mission_record };
+    use crate::storage::{ join_location, open_storage };
+    use crate::utils::explorer::remove_all;
+    use std::path::Path;
+    use std::io::{ Error, ErrorKind };
+    use log::warn;
+
+    // destination on removable, network drives or remote servers may be disconnected
+    let (storage, root) = match open_storage(&destination.path) {
+        Ok((storage, root)) if storage.is_available(&root) => (storage, root),
+        _ => {
+            let _ = update_destination_status(conn, destination, Some("destination unavailable"));
+            return Err(Error::from(ErrorKind::NotFound));
+        }
+    };
+
+    let mut copy = backup.clone();
+    if backup.is_mirror == 1 {
+        // mirror is synced from sources, then replaces the previous one in destination
+        match replicate_mirror(backup, &destination.path, throttle, conn) {
+            Ok(mirror_size) => {
+                copy.backup_size = mirror_size as i64;
+            },
+            Err(error) => {
+                let _ = update_destination_status(conn, destination, Some(format!("{:?}", error).as_str()));
+                return Err(error);
+            }
+        }
+        clear_mirror_records(conn, &backup.mission_id, &destination.destination_id, &destination.path);
+        copy.save_path = destination.path.clone();
+    } else {
+        // each volume is copied by itself, record keeps the first one
+        for (idx, volume) in volume_locations(&backup.save_path, backup.volumes).iter().enumerate() {
+            let save = Path::new(volume);
+            let relative = match (save.parent().and_then(|dir| dir.file_name()), save.file_name()) {
+                (Some(dir), Some(name)) => format!("{}/{}", dir.to_string_lossy(), name.to_string_lossy()),
+                _ => {
+                    return Err(Error::from(ErrorKind::InvalidInput));
+                }
+            };
+
+            let (local_save, fetched) = fetch_save(volume)?;
+            let upload = storage.put(&local_save, &storage.join(&root, &relative));
+            if let Some(fetched) = fetched {
+                let _ = remove_all(&fetched);
+            }
+            if let Err(error) = upload {
+                let _ = update_destination_status(conn, destination, Some(format!("{:?}", error).as_str()));
+                return Err(error);
+            }
+            if idx == 0 {
+                copy.save_path = join_location(&destination.path, &relative);
+            }
+        }
+    }
+
+    copy.destination_id = destination.destination_id.clone();
+    copy.origin_id = backup.backup_id.clone();
+    let copy = match query_mission_record(conn, Some(&backup.mission_id)) {
+        Ok(missions) if !missions.is_empty() => {
+            create_backup_record(conn, &mut copy, &missions[0]).map_err(|_| Error::from(ErrorKind::Other))?
+        },
+        _ => {
+            return Err(Error::from(ErrorKind::NotFound));
+        }
+    };
+
+    if let Err(error) = restrict_saves(&copy, &destination.path, destination.restrict, destination.restrict_days, destination.restrict_size, conn) {
+        let _ = update_destination_status(conn, destination, Some(format!("{:?}", error).as_str()));
+        return Err(error);
+    }
+
+    // manifest of copy describes it in destination, backups without manifest are copied as they are
+    if let Err(error) = read_manifest_of(backup).and_then(|manifest| save_manifest(&BackupManifest { backup: copy.clone(), ..manifest })) {
+        warn!("failed to copy manifest of backup {}, errMsg: {:?}", backup.backup_id, error);
+    }
+
+    let _ = update_destination_status(conn, destination, None);
+
+    Ok(copy)
+}
+
+/// Get local path of backup save, remote save is downloaded into temp directory.
+/// 
+/// Returns the local path and the temp directory to remove after use.
+fn fetch_save(save_path: &str) -> Result<(String, Option<String>), std::io::Error> {
+    use crate::storage::{ is_remote, open_storage };
+    use std::path::Path;
+
+    if !is_remote(save_path) {
+        return Ok((save_path.to_string(), None));
+    }
+
+    let (storage, path) = open_storage(save_path)?;
+    let fetch_dir = std::env::temp_dir().join("mission_backup").join(uuid::Uuid::new_v4().to_string());
+    let save_name = Path::new(&path).file_name().map_or("save".to_string(), |name| name.to_string_lossy().to_string());
+    let local_save = fetch_dir.join(save_name).display().to_string();
+    if let Err(error) = storage.get(&path, &local_save) {
+        let _ = crate::utils::explorer::remove_all(fetch_dir.display().to_string().as_str());
+        return Err(error);
+    }
+
+    Ok((local_save, Some(fetch_dir.display().to_string())))
+}
+
+/// Get local path of backup save with all its volumes, see [fetch_save].
+/// 
+/// Remote volumes are downloaded into the same temp directory.
+fn fetch_volumes(backup: &Backup) -> Result<(String, Option<String>), std::io::Error> {
+    use crate::storage::open_storage;
+    use crate::utils::explorer::remove_all;
+    use std::path::Path;
+
+    let volumes = volume_locations(&backup.save_path, backup.volumes);
+    let (local_save, fetched) = fetch_save(&volumes[0])?;
+    if let Some(fetch_dir) = &fetched {
+        for volume in volumes.iter().skip(1) {
+            let fetch_volume = || -> Result<(), std::io::Error> {
+                let (storage, path) = open_storage(volume)?;
+                let save_name = Path::new(&path).file_name().map_or("save".to_string(), |name| name.to_string_lossy().to_string());
+                storage.get(&path, &Path::new(fetch_dir).join(save_name).display().to_string())
+            };
+            if let Err(error) = fetch_volume() {
+                let _ = remove_all(fetch_dir);
+                return Err(error);
+            }
+        }
+    }
+
+    Ok((local_save, fetched))
+}
+
+/// Get locations of all volumes of backup save, or the save itself if not split.
+fn volume_locations(save_path: &str, volumes: i32) -> Vec<String> {
+    match (volumes > 1, save_path.strip_suffix(".001")) {
+        (true, Some(base)) => (1..=volumes).map(|idx| format!("{}.{:03}", base, idx)).collect(),
+        _ => vec![save_path.to_string()],
+    }
+}
+
+/// Get name of source item inside backup.
+fn source_entry_name(source: &str) -> String {
+    use std::path::Path;
+
+    match Path::new(source).file_name() {
+        Some(name) => sanitize_name(&name.to_string_lossy()),
+        None => sanitize_name(source),
+    }
+}
+
+/// Replace characters not allowed in file names with `_`.
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if "\\/:*?\"<>|".contains(c) { '_' } else { c })
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Copy source to backup with or without ignore rules.
+fn copy_source(from: &str, to: &str, rules: &crate::utils::explorer::IgnoreRules) -> Result<crate::utils::explorer::CopyStats, std::io::Error> {
+    use crate::utils::explorer::{CopyStats, copy_all, copy_dir_with_rules, run_with_policy};
+    use std::path::Path;
+
+    // walk with rules even if no filter, so error policy applies to each file
+    if Path::new(from).is_dir() {
+        return copy_dir_with_rules(from, to, rules);
+    }
+
+    let mut stats = CopyStats::default();
+    if let Some(copied) = run_with_policy(rules, from, &mut stats.skipped, || copy_all(from, to))? {
+        stats.merge(copied);
+    }
+    Ok(stats)
+}
+
+/// Restore backup to where each source comes from.
+/// 
+/// Archive will be extracted first, existing files will be overwritten.
+/// 
+/// # Arguments
+/// 
+/// * `bid` - Uuid for backup.
+/// * `target` - Restore into this directory instead of the origin paths.
+/// * `conn` - Connection to database.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, backup::restore_backup};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let bid = "661b7d0e-a52c-457e-89e1-2ffe9a230c14";
+///     match restore_backup(bid, None, &mut conn) {
+///         Ok(restored) => {
+///             println!("restore backup {} to {:?}", bid, restored);
+///         },
+///         Err(error) => {
+///             println!("failed to restore backup, errMsg: {:?}", error);
+///         }
+///     }   
+/// }
+/// ```
+pub fn restore_backup(bid: &str, target: Option<&str>, conn: &mut SqliteConnection) -> Result<Vec<String>, std::io::Error> {
+    use super::{ mission::get_mission_related_record, procedure::load_archive_password };
+    use crate::utils::{
+        compress::{ extract_archive_with, is_archive },
+        explorer::{ merge_all, remove_all }
+    };
+    use std::collections::BTreeMap;
+    use std::path::Path;
+    use std::io::{ Error, ErrorKind };
+
+    let backup = match query_backup_record(conn, Some(bid), None) {
+        Ok(records) if !records.is_empty() => records[0].clone(),
+        _ => {
+            return Err(Error::from(ErrorKind::NotFound));
+        }
+    };
+    let record = get_mission_related_record(&backup.mission_id, conn)?;
+
+    // backups before multiple sources only have `Mission::src_path`
+    let mut mapping: BTreeMap<String, String> = serde_json::from_str(&backup.sources).unwrap_or_default();
+    if mapping.is_empty() {
+        let src_path = &record.mission.src_path;
+        mapping.insert(source_entry_name(src_path), src_path.clone());
+    }
+
+    if !backup.chain_id.is_empty() {
+        return restore_delta_sync(&backup, &mapping, target);
+    }
+    if backup.is_mirror == 1 {
+        return restore_mirror(&backup, &mapping, target);
+    }
+
+    let (save_path, fetched) = fetch_volumes(&backup)?;
+    let save = Path::new(&save_path);
+    if !save.exists() {
+        return Err(Error::from(ErrorKind::NotFound));
+    }
+    let is_single = mapping.len() == 1;
+
+    // plain copy of single file source shares the same name with its entry
+    let save_name = save.file_name().map_or(String::new(), |name| name.to_string_lossy().to_string());
+    let is_packed = save.is_file() && is_archive(&save_path) && !(is_single && mapping.contains_key(&save_name));
+
+    let root = match (is_packed, save.parent()) {
+        (true, Some(parent)) => {
+            let extract_dir = parent.join("restoring");
+            if extract_dir.exists() {
+                remove_all(extract_dir.display().to_string().as_str())?;
+            }
+            let password = match backup.is_encrypted {
+                0 => None,
+                version => Some(load_archive_password(&record.procedure.procedure_id, version)?),
+            };
+            extract_archive_with(&save_path, extract_dir.display().to_string().as_str(), password.as_deref())?;
+            extract_dir
+        },
+        _ => save.to_path_buf(),
+    };
+
+    let mut restored = Vec::new();
+    for (entry, origin) in mapping.iter() {
+        let item = match (is_single, is_packed) {
+            (true, true) if root.join(entry).is_file() => root.join(entry),
+            (true, _) => root.clone(),
+            (false, _) => root.join(entry),
+        };
+
+        let restore_path = match target {
+            Some(dir) => Path::new(dir).join(entry).display().to_string(),
+            None => origin.clone(),
+        };
+        merge_all(item.display().to_string().as_str(), &restore_path)?;
+        restored.push(restore_path);
+    }
+
+    if is_packed {
+        remove_all(root.display().to_string().as_str())?;
+    }
+    if let Some(fetched) = fetched {
+        remove_all(&fetched)?;
+    }
+
+    Ok(restored)
+}
+
+/// Physically delete backup in disk.
+/// 
+/// Logically delete backup in record.
+/// 
+/// # Arguments
+/// 
+/// * `bid` - Uuid for backup.
+/// * `conn` - Connection to database.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, backup::delete_backup};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let bid = "661b7d0e-a52c-457e-89e1-2ffe9a230c14";
+///     match delete_backup(backup_id, &mut conn) {
+///         Ok(cnt) => {
+///             println!("remove {} backup with id {}", cnt, backup_id);
+///         },
+///         Err(error) => {
+///             println!("failed to remove backup, errMsg: {:?}", error);
+///         }
+///     }   
+/// }
+/// ```
+pub fn delete_backup(bid: &str, conn: &mut SqliteConnection) -> Result<(), std::io::Error> {
+    use crate::storage::{ open_storage, parent_location };
+    use std::io::{ Error, ErrorKind };
+    
+    if let Ok(record) = query_backup_record(conn, Some(bid), None) {
+        if !record.is_empty() {
+            let backup = record[0].clone();
+
+            // later syncs in delta chain can not be restored without this one
+            if !backup.chain_id.is_empty() {
+                return delete_delta_sync(&backup, conn);
+            }
+            if backup.is_mirror == 1 {
+                return delete_mirror(&backup, conn);
+            }
+
+            if let Some(backup_dir) = parent_location(&backup.save_path) {
+                let (storage, path) = open_storage(&backup_dir)?;
+                storage.delete(&path)?;
+
+                if delete_backup_record(conn, Some(bid), None).is_ok() {
+                    return Ok(());
+                }
+            }            
+        }
+    }
+
+    Err(Error::from(ErrorKind::NotFound))
+}
+
+/// Struct RescanReport
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RescanReport {
+    /// Records created for backups found in location
+    pub recovered: Vec<Backup>,
+
+    /// Count of backups found that are already recorded
+    pub recorded: u32,
+
+    /// Items in location not recognized as backups of mission
+    pub orphans: Vec<String>,
+}
+
+/// Backup found in location, with its manifest if saved.
+struct FoundBackup {
+    backup: Backup,
+    manifest: Option<BackupManifest>,
+}
+
+/// Get name of storage entry.
+fn entry_name(entry: &crate::storage::StorageEntry) -> String {
+    use std::path::Path;
+
+    Path::new(entry.path.trim_end_matches('/')).file_name().map_or(String::new(), |name| name.to_string_lossy().to_string())
+}
+
+/// Convert name of timestamp directory to time, `None` if it is not one.
+fn timestamp_time(name: &str) -> Option<NaiveDateTime> {
+    name.parse::<i64>()
+        .ok()
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .map(|time| time.naive_utc())
+}
+
+/// Read manifest in location if exists.
+/// 
+/// Returns `None` if there is no manifest, or it can not be read.
+fn find_manifest(storage: &dyn crate::storage::StorageBackend, path: &str, location: &str) -> Option<BackupManifest> {
+    use crate::storage::join_location;
+
+    if !storage.exists(&storage.join(path, MANIFEST_NAME)) {
+        return None;
+    }
+    read_manifest(&join_location(location, MANIFEST_NAME)).ok()
+}
+
+/// Recognize backups in timestamp directory, either a snapshot or a chain of delta syncs.
+/// 
+/// Returns `None` if items in directory are not a backup.
+fn scan_timestamp_dir(
+    storage: &dyn crate::storage::StorageBackend,
+    dir: &crate::storage::StorageEntry,
+    location: &str
+) -> Result<Option<Vec<FoundBackup>>, std::io::Error> {
+    use crate::storage::join_location;
+
+    let name = entry_name(dir);
+    let items = storage.list(&dir.path)?;
+
+    // chain of delta syncs, each sync has its own delta.json
+    let mut syncs = Vec::new();
+    for item in items.iter().filter(|item| item.is_dir && timestamp_time(&entry_name(item)).is_some()) {
+        if !storage.exists(&storage.join(&item.path, "delta.json")) {
+            continue;
+        }
+        let sync_name = entry_name(item);
+        let sync_location = join_location(location, &sync_name);
+        let manifest = find_manifest(storage, &item.path, &sync_location);
+        let backup = Backup {
+            save_path: sync_location,
+            backup_size: storage.size(&item.path)? as i64,
+            chain_id: name.clone(),
+            create_at: timestamp_time(&sync_name).unwrap_or_default(),
+            ..Backup::default()
+        };
+        syncs.push(FoundBackup { backup, manifest });
+    }
+    if !syncs.is_empty() {
+        return Ok(Some(syncs));
+    }
+
+    // snapshot, a single save or volumes of archive beside its manifest
+    let manifest = find_manifest(storage, &dir.path, location);
+    let saves: Vec<&crate::storage::StorageEntry> = items.iter().filter(|item| entry_name(item) != MANIFEST_NAME).collect();
+    let volumes: Vec<&crate::storage::StorageEntry> = saves.iter()
+        .filter(|item| !item.is_dir && entry_name(item).rsplit_once('.').is_some_and(|(_, ext)| ext.len() == 3 && ext.parse::<u32>().is_ok()))
+        .cloned()
+        .collect();
+    let mut backup = match (saves.len(), volumes.len() == saves.len()) {
+        (1, _) => Backup {
+            save_path: join_location(location, &entry_name(saves[0])),
+            ..Backup::default()
+        },
+        (count, true) if count > 1 => {
+            let first = volumes.iter().map(|item| entry_name(item)).find(|name| name.ends_with(".001"));
+            match first {
+                Some(first) => Backup {
+                    save_path: join_location(location, &first),
+                    volumes: count as i32,
+                    ..Backup::default()
+                },
+                None => return Ok(None),
+            }
+        },
+        _ => return Ok(None),
+    };
+    backup.backup_size = saves.iter().map(|item| storage.size(&item.path)).sum::<Result<u64, std::io::Error>>()? as i64;
+    backup.create_at = timestamp_time(&name).unwrap_or_default();
+
+    Ok(Some(vec![FoundBackup { backup, manifest }]))
+}
+
+/// Backups found in location without records, for mission of `record`.
+/// 
+/// Returns the unsaved records, count of backups already recorded and items not recognized as backups.
+fn scan_location(
+    record: &super::Record,
+    location: &str,
+    destination_id: &str,
+    conn: &mut SqliteConnection
+) -> Result<(Vec<Backup>, u32, Vec<String>), std::io::Error> {
+    use crate::storage::{ join_location, open_storage };
+    use std::io::{ Error, ErrorKind };
+
+    let mission = &record.mission;
+    let (storage, root) = open_storage(location)?;
+    if !storage.is_available(&root) {
+        return Err(Error::from(ErrorKind::NotFound));
+    }
+
+    let mapping = map_sources(mission);
+    let mut orphans: Vec<String> = Vec::new();
+    let mut found: Vec<FoundBackup> = Vec::new();
+
+    // mirror saves entries of sources in location directly
+    let mut mirror_entries = Vec::new();
+    for item in storage.list(&root)? {
+        let name = entry_name(&item);
+        let item_location = join_location(location, &name);
+
+        if name == MANIFEST_NAME || name == MIRROR_TRASH || mapping.contains_key(&name) {
+            mirror_entries.push(item);
+            continue;
+        }
+        if !item.is_dir || timestamp_time(&name).is_none() {
+            orphans.push(item_location);
+            continue;
+        }
+        match scan_timestamp_dir(storage.as_ref(), &item, &item_location)? {
+            Some(backups) => found.extend(backups),
+            None => orphans.push(item_location),
+        }
+    }
+    if !mirror_entries.is_empty() {
+        let manifest = find_manifest(storage.as_ref(), &root, location);
+        let is_mirror = match &manifest {
+            Some(manifest) => manifest.backup.is_mirror == 1,
+            None => record.procedure.backup_mode == 2,
+        };
+        if is_mirror {
+            let mut backup_size = 0;
+            for item in mirror_entries.iter().filter(|item| mapping.contains_key(&entry_name(item))) {
+                backup_size += storage.size(&item.path)?;
+            }
+            let backup = Backup {
+                save_path: location.to_string(),
+                backup_size: backup_size as i64,
+                is_mirror: 1,
+                create_at: Utc::now().naive_utc(),
+                ..Backup::default()
+            };
+            found.push(FoundBackup { backup, manifest });
+        } else {
+            orphans.extend(mirror_entries.iter().map(|item| join_location(location, &entry_name(item))));
+        }
+    }
+
+    // backups with manifests of other missions are left as orphans
+    let records: Vec<Backup> = query_backup_record(conn, None, Some(&mission.mission_id))
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|item| item.destination_id == destination_id)
+        .collect();
+    let mut unrecorded: Vec<Backup> = Vec::new();
+    let mut recorded = 0;
+    for FoundBackup { backup, manifest } in found {
+        let mut item = match manifest {
+            Some(manifest) if manifest.mission.mission_id != mission.mission_id => {
+                orphans.push(backup.save_path);
+                continue;
+            },
+            Some(manifest) => Backup {
+                id: backup.id,
+                save_path: backup.save_path,
+                is_deleted: 0,
+                ..manifest.backup
+            },
+            None => Backup {
+                sources: serde_json::to_string(&mapping).unwrap_or_default(),
+                ..backup
+            },
+        };
+        if records.iter().any(|record| record.save_path == item.save_path || record.backup_id == item.backup_id) {
+            recorded += 1;
+            continue;
+        }
+
+        // uuid of deleted record is still taken
+        let is_taken = super::schema::backup::table
+            .filter(super::schema::backup::backup_id.eq(&item.backup_id))
+            .count()
+            .get_result::<i64>(conn)
+            .unwrap_or(0) > 0;
+        if is_taken {
+            item.backup_id = Uuid::new_v4().to_string();
+        }
+
+        item.mission_id = mission.mission_id.clone();
+        item.destination_id = destination_id.to_string();
+        item.update_at = Utc::now().naive_utc();
+        unrecorded.push(item);
+    }
+
+    Ok((unrecorded, recorded, orphans))
+}
+
+/// Rebuild backup records of mission by scanning its save location or destination.
+/// 
+/// Timestamp directories of snapshots and delta chains, mirror entries and their manifests are recognized.
+/// Backups with manifest are recorded as described, others are recorded with sources of mission.
+/// Backups already recorded are skipped, items not recognized are reported as orphans.
+/// 
+/// # Arguments
+/// 
+/// * `mid` - Uuid for mission.
+/// * `did` - Uuid for destination of mission, `None` for `Mission::dst_path`.
+/// * `conn` - Connection to database.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, backup::rescan_backups};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let mid = "1c69eead-b7cf-457e-95e2-9c9f459120ff";
+///     match rescan_backups(mid, None, &mut conn) {
+///         Ok(report) => {
+///             println!("recover {} backups, orphans: {:?}", report.recovered.len(), report.orphans);
+///         },
+///         Err(error) => {
+///             println!("failed to rescan backups, errMsg: {:?}", error);
+///         }
+///     }   
+/// }
+/// ```
+pub fn rescan_backups(mid: &str, did: Option<&str>, conn: &mut SqliteConnection) -> Result<RescanReport, std::io::Error> {
+    use super::{ mission::get_mission_related_record, destination::query_destination_record };
+    use std::io::{ Error, ErrorKind };
+
+    let record = get_mission_related_record(mid, conn)?;
+    let (location, destination_id) = match did {
+        Some(did) => match query_destination_record(conn, Some(mid)) {
+            Ok(records) => match records.into_iter().find(|item| item.destination_id == did) {
+                Some(destination) => (destination.path, destination.destination_id),
+                None => return Err(Error::from(ErrorKind::NotFound)),
+            },
+            Err(_) => return Err(Error::from(ErrorKind::Other)),
+        },
+        None => (record.mission.dst_path.clone(), String::new()),
+    };
+
+    let (unrecorded, recorded, orphans) = scan_location(&record, &location, &destination_id, conn)?;
+    let mut report = RescanReport { recovered: Vec::new(), recorded, orphans };
+    for mut item in unrecorded {
+        let recovered = insert_backup_record(conn, &mut item).map_err(|_| Error::from(ErrorKind::Other))?;
+        report.recovered.push(recovered);
+    }
+
+    Ok(report)
+}
+
+/// Struct ReconcileOptions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReconcileOptions {
+    /// Delete records whose saves are not found
+    #[serde(default)]
+    pub remove_missing: bool,
+
+    /// Record backups found without records
+    #[serde(default)]
+    pub record_untracked: bool,
+
+    /// Update records to sizes of their saves
+    #[serde(default)]
+    pub fix_sizes: bool,
+}
+
+/// Struct SizeMismatch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeMismatch {
+    /// Backup record
+    pub backup: Backup,
+
+    /// Size of save found in location
+    pub actual_size: i64,
+}
+
+/// Struct ReconcileReport
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReconcileReport {
+    /// Records whose saves are not found
+    pub missing: Vec<Backup>,
+
+    /// Backups found in locations without records
+    pub untracked: Vec<Backup>,
+
+    /// Records whose sizes differ from their saves
+    pub mismatched: Vec<SizeMismatch>,
+
+    /// Items in locations not recognized as backups
+    pub orphans: Vec<String>,
+
+    /// Locations not available, records in them are not checked
+    pub unavailable: Vec<String>,
+}
+
+/// Get size of backup save, `None` if save is not found.
+/// 
+/// Manifest is not counted, as size is recorded before it is saved.
+fn save_size(backup: &Backup) -> Result<Option<u64>, std::io::Error> {
+    use crate::storage::open_storage;
+    use std::collections::BTreeMap;
+
+    let mut size = 0;
+    if backup.is_mirror == 1 {
+        let mapping: BTreeMap<String, String> = serde_json::from_str(&backup.sources).unwrap_or_default();
+        let (storage, root) = open_storage(&backup.save_path)?;
+        let mut is_found = false;
+        for entry in mapping.keys() {
+            let path = storage.join(&root, entry);
+            if storage.exists(&path) {
+                is_found = true;
+                size += storage.size(&path)?;
+            }
+        }
+        return Ok(is_found.then_some(size));
+    }
+
+    for volume in volume_locations(&backup.save_path, backup.volumes) {
+        let (storage, path) = open_storage(&volume)?;
+        if !storage.exists(&path) {
+            return Ok(None);
+        }
+        size += storage.size(&path)?;
+
+        let manifest = storage.join(&path, MANIFEST_NAME);
+        if !backup.chain_id.is_empty() && storage.exists(&manifest) {
+            size -= storage.size(&manifest)?;
+        }
+    }
+
+    Ok(Some(size))
+}
+
+/// Reconcile backup records with saves in locations of missions.
+/// 
+/// Finds records whose saves are not found, backups without records and records with wrong sizes,
+/// then fixes each of them if wanted. Locations not available are skipped, so records of
+/// disconnected destinations are kept.
+/// 
+/// # Arguments
+/// 
+/// * `mid` - Uuid for mission, `None` for all missions.
+/// * `options` - Which of the differences to fix.
+/// * `conn` - Connection to database.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, backup::{reconcile_backups, ReconcileOptions}};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let options = ReconcileOptions { remove_missing: true, ..Default::default() };
+///     match reconcile_backups(None, &options, &mut conn) {
+///         Ok(report) => {
+///             println!("remove {} records of missing backups", report.missing.len());
+///         },
+///         Err(error) => {
+///             println!("failed to reconcile backups, errMsg: {:?}", error);
+///         }
+///     }   
+/// }
+/// ```
+pub fn reconcile_backups(mid: Option<&str>, options: &ReconcileOptions, conn: &mut SqliteConnection) -> Result<ReconcileReport, std::io::Error> {
+    use super::mission::query_mission_record;
+    use std::io::{ Error, ErrorKind };
+
+    let missions = query_mission_record(conn, mid).map_err(|_| Error::from(ErrorKind::Other))?;
+    if mid.is_some() && missions.is_empty() {
+        return Err(Error::from(ErrorKind::NotFound));
+    }
+
+    // records are fixed all or none, error of reconcile is kept as transaction only takes diesel errors
+    let mut failure: Option<Error> = None;
+    let reconciled = conn.transaction(|conn| {
+        reconcile_missions(&missions, options, conn).map_err(|error| {
+            failure = Some(error);
+            diesel::result::Error::RollbackTransaction
+        })
+    });
+    match reconciled {
+        Ok(report) => Ok(report),
+        Err(_) => Err(failure.unwrap_or(Error::from(ErrorKind::Other))),
+    }
+}
+
+/// Reconcile backup records of missions with their save locations, see [reconcile_backups].
+fn reconcile_missions(missions: &[Mission], options: &ReconcileOptions, conn: &mut SqliteConnection) -> Result<ReconcileReport, std::io::Error> {
+    use super::{
+        mission::get_mission_related_record,
+        destination::query_destination_record
+    };
+    use crate::storage::open_storage;
+    use std::io::{ Error, ErrorKind };
+
+    let mut report = ReconcileReport::default();
+    for mission in missions.iter() {
+        let record = get_mission_related_record(&mission.mission_id, conn)?;
+        let backups = query_backup_record(conn, None, Some(&mission.mission_id)).map_err(|_| Error::from(ErrorKind::Other))?;
+        let mut locations = vec![(mission.dst_path.clone(), String::new())];
+        let destinations = query_destination_record(conn, Some(&mission.mission_id)).map_err(|_| Error::from(ErrorKind::Other))?;
+        for destination in destinations {
+            locations.push((destination.path, destination.destination_id));
+        }
+
+        for (location, destination_id) in locations.iter() {
+            let is_available = open_storage(location).is_ok_and(|(storage, root)| storage.is_available(&root));
+            if !is_available {
+                report.unavailable.push(location.clone());
+                continue;
+            }
+
+            let (untracked, _, orphans) = scan_location(&record, location, destination_id, conn)?;
+            report.orphans.extend(orphans);
+            for mut item in untracked {
+                if options.record_untracked {
+                    item = insert_backup_record(conn, &mut item).map_err(|_| Error::from(ErrorKind::Other))?;
+                }
+                report.untracked.push(item);
+            }
+
+            for backup in backups.iter().filter(|item| &item.destination_id == destination_id) {
+                let actual_size = match save_size(backup)? {
+                    Some(size) => size as i64,
+                    None => {
+                        if options.remove_missing {
+                            delete_backup_record(conn, Some(&backup.backup_id), None).map_err(|_| Error::from(ErrorKind::Other))?;
+                        }
+                        report.missing.push(backup.clone());
+                        continue;
+                    }
+                };
+
+                // hard linked snapshot only counts files copied by itself
+                let is_linked = record.procedure.backup_mode == 3 && backup.chain_id.is_empty() && backup.is_mirror == 0;
+                if actual_size == backup.backup_size || is_linked {
+                    continue;
+                }
+                if options.fix_sizes {
+                    let mut fixed = Backup { backup_size: actual_size, update_at: Utc::now().naive_utc(), ..backup.clone() };
+                    update_backup_record(conn, &mut fixed).map_err(|_| Error::from(ErrorKind::Other))?;
+                }
+                report.mismatched.push(SizeMismatch { backup: backup.clone(), actual_size });
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::test_util::test_connection;
+    use crate::config::throttle::ThrottleConfig;
+
+    /// Writes file of `size` bytes not compressible, modified at `modified` unix seconds.
+    fn test_write_file(path: &std::path::Path, size: usize, seed: u64, modified: u64) {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let mut state = seed.max(1);
+        let content: Vec<u8> = (0..size).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        }).collect();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+        let file = std::fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(UNIX_EPOCH + Duration::from_secs(modified)).unwrap();
+    }
+
+    /// Creates records of mission saving `<test_path>/src` into `<test_path>/dst`, with its procedure.
+    fn test_mission(conn: &mut SqliteConnection, test_path: &std::path::Path) -> Mission {
+        use super::super::{ mission::create_mission_record, procedure::{ Procedure, create_procedure_record } };
+
+        let procedure = create_procedure_record(conn, &mut Procedure::default()).unwrap();
+        let mut mission = Mission {
+            procedure_id: procedure.procedure_id,
+            src_path: test_path.join("src").display().to_string(),
+            dst_path: test_path.join("dst").display().to_string(),
+            status: 1,
+            ..Mission::default()
+        };
+        create_mission_record(conn, &mut mission).unwrap()
+    }
+
+    /// Writes manifest of backup in `dir` for mission.
+    fn test_write_manifest(dir: &std::path::Path, backup: Backup, mission: &Mission) {
+        let manifest = BackupManifest {
+            version: MANIFEST_VERSION,
+            app_version: String::new(),
+            backup,
+            mission: mission.clone(),
+            procedure: super::super::procedure::Procedure::default(),
+            files: std::collections::BTreeMap::new(),
+        };
+        std::fs::create_dir_all(dir).unwrap();
+        write_json_file(&dir.join(MANIFEST_NAME), &manifest).unwrap();
+    }
+
+    #[test]
+    fn test_mission_sources() {
+        let mission = Mission { src_path: "/data/robin".to_string(), ..Mission::default() };
+        assert_eq!(mission.sources(), vec!["/data/robin".to_string()]);
+
+        // empty items are dropped, invalid list falls back to `src_path`
+        let mission = Mission { src_paths: r#"["/data/robin", "", "/data/batman"]"#.to_string(), ..mission };
+        assert_eq!(mission.sources(), vec!["/data/robin".to_string(), "/data/batman".to_string()]);
+        let mission = Mission { src_paths: "/data/batman".to_string(), ..mission };
+        assert_eq!(mission.sources(), vec!["/data/robin".to_string()]);
+    }
+
+    #[test]
+    fn test_migrated_src_paths() {
+        use super::super::{ MIGRATIONS, mission::query_mission_record };
+        use diesel_migrations::MigrationHarness;
+
+        // mission saved before multiple sources
+        let mut conn = SqliteConnection::establish(":memory:").unwrap();
+        loop {
+            let pending = conn.pending_migrations(MIGRATIONS).unwrap();
+            if pending[0].name().to_string().starts_with("2024-07-04-090000") {
+                break;
+            }
+            conn.run_migration(&pending[0]).unwrap();
+        }
+        diesel::sql_query(r#"INSERT INTO "mission" VALUES (1, 'robin', 'procedure', 'robin', 0, '', '/data/robin', '/backups/robin', 2,
+            '2024-07-01 00:00:00', '2024-07-01 00:00:00', '', '', '', '2024-07-01 00:00:00', '2024-07-01 00:00:00', 0, '2024-07-01 00:00:00')"#)
+            .execute(&mut conn)
+            .unwrap();
+        conn.run_pending_migrations(MIGRATIONS).unwrap();
+
+        let mission = &query_mission_record(&mut conn, Some("robin")).unwrap()[0];
+        assert_eq!(mission.src_paths, String::new());
+        assert_eq!(mission.sources(), vec!["/data/robin".to_string()]);
+    }
+
+    #[test]
+    fn test_multi_source_snapshot() {
+        use super::super::{ mission::create_mission_record, procedure::{ Procedure, create_procedure_record } };
+        use std::collections::BTreeMap;
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+
+        let test_path = current_dir().expect("").join("test_backup_sources");
+        let sources = vec![test_path.join("one").join("docs"), test_path.join("two").join("docs")];
+        create_dir_all(&sources[0]).unwrap();
+        create_dir_all(&sources[1]).unwrap();
+        write(sources[0].join("a.txt"), "Hello").unwrap();
+        write(sources[1].join("b.txt"), "world!").unwrap();
+
+        let mut conn = test_connection();
+        let procedure = create_procedure_record(&mut conn, &mut Procedure::default()).unwrap();
+        let mut mission = Mission {
+            procedure_id: procedure.procedure_id,
+            name: "robin".to_string(),
+            src_path: sources[0].display().to_string(),
+            src_paths: serde_json::to_string(&sources).unwrap(),
+            dst_path: test_path.join("dst").display().to_string(),
+            status: 1,
+            ..Mission::default()
+        };
+        let mission = create_mission_record(&mut conn, &mut mission).unwrap();
+        let backup = create_backup(&mission.mission_id, &ThrottleConfig::default(), &mut conn).unwrap();
+
+        // sources of same name are saved side by side under mission name
+        let save = std::path::Path::new(&backup.save_path);
+        assert_eq!(save.file_name().unwrap(), "robin");
+        assert_eq!(read_to_string(save.join("docs").join("a.txt")).unwrap(), "Hello");
+        assert_eq!(read_to_string(save.join("docs_1").join("b.txt")).unwrap(), "world!");
+        let mapping: BTreeMap<String, String> = serde_json::from_str(&backup.sources).unwrap();
+        assert_eq!(mapping["docs"], sources[0].display().to_string());
+        assert_eq!(mapping["docs_1"], sources[1].display().to_string());
+
+        // each entry is restored into target by its name
+        let target = test_path.join("restored");
+        let restored = restore_backup(&backup.backup_id, Some(target.display().to_string().as_str()), &mut conn).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(read_to_string(target.join("docs").join("a.txt")).unwrap(), "Hello");
+        assert_eq!(read_to_string(target.join("docs_1").join("b.txt")).unwrap(), "world!");
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_replicate_backup() {
+        use super::super::destination::{ Destination, create_destination_record, query_destination_record };
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+        use std::path::Path;
+
+        let test_path = current_dir().expect("").join("test_backup_replicate");
+        create_dir_all(test_path.join("src")).unwrap();
+        create_dir_all(test_path.join("mirror")).unwrap();
+        write(test_path.join("src").join("a.txt"), "Hello world!").unwrap();
+
+        let mut conn = test_connection();
+        let mission = test_mission(&mut conn, &test_path);
+        let mut destination = Destination { path: test_path.join("mirror").display().to_string(), ..Destination::default() };
+        let destination = create_destination_record(&mut conn, &mut destination, &mission).unwrap();
+        let backup = create_backup(&mission.mission_id, &ThrottleConfig::default(), &mut conn).unwrap();
+
+        // copy keeps layout of backup and refers to it
+        let copies: Vec<Backup> = query_backup_record(&mut conn, None, Some(&mission.mission_id)).unwrap()
+            .into_iter()
+            .filter(|item| item.destination_id == destination.destination_id)
+            .collect();
+        assert_eq!(copies.len(), 1);
+        assert_eq!(copies[0].origin_id, backup.backup_id);
+        let relative = Path::new(&backup.save_path).strip_prefix(&mission.dst_path).unwrap();
+        assert_eq!(Path::new(&copies[0].save_path), test_path.join("mirror").join(relative));
+        assert_eq!(read_to_string(Path::new(&copies[0].save_path).join("a.txt")).unwrap(), "Hello world!");
+
+        let destination = &query_destination_record(&mut conn, Some(&mission.mission_id)).unwrap()[0];
+        assert_eq!(destination.status, 1);
+        assert!(destination.last_error.is_empty());
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_replicate_retention() {
+        use super::super::destination::{ Destination, create_destination_record };
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, read_dir, remove_dir_all, write};
+
+        let test_path = current_dir().expect("").join("test_backup_retention");
+        create_dir_all(test_path.join("mirror")).unwrap();
+        let mut conn = test_connection();
+        let mission = test_mission(&mut conn, &test_path);
+
+        // destination keeps one backup, save location keeps all of them
+        let mut destination = Destination {
+            path: test_path.join("mirror").display().to_string(),
+            restrict: 1,
+            restrict_days: 1,
+            ..Destination::default()
+        };
+        let mut destination = create_destination_record(&mut conn, &mut destination, &mission).unwrap();
+        for timestamp in ["1720000000", "1720000060"] {
+            let save = test_path.join("dst").join(timestamp).join("src");
+            create_dir_all(&save).unwrap();
+            write(save.join("a.txt"), timestamp).unwrap();
+
+            let mut backup = Backup { save_path: save.display().to_string(), ..Backup::default() };
+            let backup = create_backup_record(&mut conn, &mut backup, &mission).unwrap();
+            replicate_backup(&backup, &mut destination, &ThrottleConfig::default(), &mut conn).unwrap();
+        }
+
+        assert_eq!(read_dir(test_path.join("dst")).unwrap().count(), 2);
+        let kept: Vec<String> = read_dir(test_path.join("mirror")).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(kept, vec!["1720000060".to_string()]);
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_replicate_unavailable() {
+        use super::super::destination::{ Destination, create_destination_record, query_destination_record };
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, remove_dir_all, write};
+
+        let test_path = current_dir().expect("").join("test_backup_unavailable");
+        create_dir_all(test_path.join("src")).unwrap();
+        write(test_path.join("src").join("a.txt"), "Hello world!").unwrap();
+
+        // destination drive is disconnected, with a copy replicated before
+        let mut conn = test_connection();
+        let mission = test_mission(&mut conn, &test_path);
+        let usb = test_path.join("usb");
+        let mut destination = Destination { path: usb.display().to_string(), ..Destination::default() };
+        let mut destination = create_destination_record(&mut conn, &mut destination, &mission).unwrap();
+        let mut copy = Backup {
+            save_path: usb.join("1720000000").join("src").display().to_string(),
+            destination_id: destination.destination_id.clone(),
+            ..Backup::default()
+        };
+        let copy = create_backup_record(&mut conn, &mut copy, &mission).unwrap();
+
+        let backup = create_backup(&mission.mission_id, &ThrottleConfig::default(), &mut conn).unwrap();
+        assert!(replicate_backup(&backup, &mut destination, &ThrottleConfig::default(), &mut conn).is_err());
+
+        // backup succeeds, copies in disconnected destination are kept
+        let destination = &query_destination_record(&mut conn, Some(&mission.mission_id)).unwrap()[0];
+        assert_eq!(destination.status, 2);
+        assert_eq!(destination.last_error, "destination unavailable".to_string());
+        let records = query_backup_record(&mut conn, None, Some(&mission.mission_id)).unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records.iter().any(|item| item.backup_id == copy.backup_id));
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_backup_manifest() {
+        use crate::utils::crypto::encode_sha2_file;
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, remove_dir_all, write};
+        use std::path::Path;
+
+        let test_path = current_dir().expect("").join("test_backup_manifest");
+        create_dir_all(test_path.join("src").join("sub")).unwrap();
+        write(test_path.join("src").join("a.txt"), "Hello").unwrap();
+        write(test_path.join("src").join("sub").join("b.txt"), "Hello world!").unwrap();
+
+        let mut conn = test_connection();
+        let mission = test_mission(&mut conn, &test_path);
+        let backup = create_backup(&mission.mission_id, &ThrottleConfig::default(), &mut conn).unwrap();
+
+        // snapshot keeps manifest beside its save, files are keyed by entry of source
+        assert!(Path::new(&backup.save_path).parent().unwrap().join(MANIFEST_NAME).is_file());
+        let manifest = read_manifest_of(&backup).unwrap();
+        assert_eq!(manifest.version, MANIFEST_VERSION);
+        assert_eq!(manifest.backup.backup_id, backup.backup_id);
+        assert_eq!(manifest.mission.mission_id, mission.mission_id);
+        assert_eq!(manifest.files.keys().cloned().collect::<Vec<String>>(), vec!["src/a.txt".to_string(), "src/sub/b.txt".to_string()]);
+
+        let file = &manifest.files["src/sub/b.txt"];
+        let origin = test_path.join("src").join("sub").join("b.txt").display().to_string();
+        assert_eq!(file.size, 12);
+        assert_eq!(file.hash, encode_sha2_file(&origin).unwrap());
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_find_manifest() {
+        use crate::storage::open_storage;
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, remove_dir_all, write};
+
+        let test_path = current_dir().expect("").join("test_backup_find_manifest");
+        let location = test_path.display().to_string();
+        create_dir_all(&test_path).unwrap();
+        let (storage, root) = open_storage(&location).unwrap();
+        assert!(find_manifest(storage.as_ref(), &root, &location).is_none());
+
+        let mission = Mission { mission_id: "mission".to_string(), ..Mission::default() };
+        test_write_manifest(&test_path, Backup { backup_id: "backup".to_string(), ..Backup::default() }, &mission);
+        let manifest = find_manifest(storage.as_ref(), &root, &location).unwrap();
+        assert_eq!(manifest.backup.backup_id, "backup".to_string());
+        assert_eq!(manifest.mission.mission_id, "mission".to_string());
+
+        // manifest not readable is the same as none
+        write(test_path.join(MANIFEST_NAME), "{").unwrap();
+        assert!(find_manifest(storage.as_ref(), &root, &location).is_none());
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_scan_timestamp_dir() {
+        use crate::storage::{ join_location, open_storage };
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, remove_dir_all, write};
+
+        let test_path = current_dir().expect("").join("test_backup_scan_timestamp");
+        let location = test_path.display().to_string();
+        for sync in ["1720000000", "1720000100"] {
+            create_dir_all(test_path.join("1720000000").join(sync)).unwrap();
+            write(test_path.join("1720000000").join(sync).join("delta.json"), "{}").unwrap();
+        }
+        create_dir_all(test_path.join("1720000200")).unwrap();
+        write(test_path.join("1720000200").join("src.zip.001"), "Hello ").unwrap();
+        write(test_path.join("1720000200").join("src.zip.002"), "world!").unwrap();
+        create_dir_all(test_path.join("1720000300")).unwrap();
+        write(test_path.join("1720000300").join("a.txt"), "").unwrap();
+        write(test_path.join("1720000300").join("b.txt"), "").unwrap();
+
+        let (storage, root) = open_storage(&location).unwrap();
+        let dirs = storage.list(&root).unwrap();
+        let scan = |name: &str| {
+            let dir = dirs.iter().find(|item| entry_name(item) == name).unwrap();
+            scan_timestamp_dir(storage.as_ref(), dir, &join_location(&location, name)).unwrap()
+        };
+
+        // each sync of chain is a backup
+        let syncs = scan("1720000000").unwrap();
+        assert_eq!(syncs.len(), 2);
+        assert!(syncs.iter().all(|item| item.backup.chain_id == *"1720000000"));
+        assert_eq!(syncs[1].backup.save_path, join_location(&join_location(&location, "1720000000"), "1720000100"));
+
+        // volumes are recorded by the first one
+        let volumes = scan("1720000200").unwrap();
+        assert_eq!(volumes.len(), 1);
+        assert_eq!(volumes[0].backup.volumes, 2);
+        assert_eq!(volumes[0].backup.backup_size, 12);
+        assert!(volumes[0].backup.save_path.ends_with("src.zip.001"));
+        assert_eq!(volumes[0].backup.create_at, timestamp_time("1720000200").unwrap());
+
+        // unrelated files are not a backup
+        assert!(scan("1720000300").is_none());
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_rescan_backups() {
+        use crate::storage::join_location;
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, remove_dir_all, write};
+
+        let test_path = current_dir().expect("").join("test_backup_rescan");
+        let mut conn = test_connection();
+        let mission = test_mission(&mut conn, &test_path);
+        let dst = test_path.join("dst");
+        let location = mission.dst_path.clone();
+
+        // snapshot with manifest, snapshot without, one of other mission and unrelated items
+        let with_manifest = Backup { backup_id: "recorded-by-manifest".to_string(), backup_size: 5, ..Backup::default() };
+        test_write_manifest(&dst.join("1720000000"), with_manifest, &mission);
+        write(dst.join("1720000000").join("src.zip"), "Hello").unwrap();
+        create_dir_all(dst.join("1720000100")).unwrap();
+        write(dst.join("1720000100").join("src.zip"), "Hello world!").unwrap();
+        let other = Mission { mission_id: "other".to_string(), ..mission.clone() };
+        test_write_manifest(&dst.join("1720000200"), Backup::default(), &other);
+        write(dst.join("1720000200").join("src.zip"), "Hello").unwrap();
+        write(dst.join("notes.txt"), "").unwrap();
+
+        let report = rescan_backups(&mission.mission_id, None, &mut conn).unwrap();
+        assert_eq!(report.recovered.len(), 2);
+        assert_eq!(report.recorded, 0);
+        let mut orphans = report.orphans.clone();
+        orphans.sort();
+        assert_eq!(orphans, vec![join_location(&join_location(&location, "1720000200"), "src.zip"), join_location(&location, "notes.txt")]);
+
+        let recovered = report.recovered.iter().find(|item| item.save_path.contains("1720000000")).unwrap();
+        assert_eq!(recovered.backup_id, "recorded-by-manifest".to_string());
+        assert_eq!(recovered.mission_id, mission.mission_id);
+        let plain = report.recovered.iter().find(|item| item.save_path.contains("1720000100")).unwrap();
+        assert_eq!(plain.backup_size, 12);
+        assert_eq!(plain.sources, serde_json::to_string(&map_sources(&mission)).unwrap());
+
+        // recorded backups are skipped
+        let report = rescan_backups(&mission.mission_id, None, &mut conn).unwrap();
+        assert!(report.recovered.is_empty());
+        assert_eq!(report.recorded, 2);
+
+        // uuid of deleted record is not reused
+        delete_backup_record(&mut conn, Some("recorded-by-manifest"), None).unwrap();
+        let report = rescan_backups(&mission.mission_id, None, &mut conn).unwrap();
+        assert_eq!(report.recovered.len(), 1);
+        assert_ne!(report.recovered[0].backup_id, "recorded-by-manifest".to_string());
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_reconcile_backups() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, remove_dir_all, write};
+
+        let test_path = current_dir().expect("").join("test_backup_reconcile");
+        let mut conn = test_connection();
+        let mission = test_mission(&mut conn, &test_path);
+        let dst = test_path.join("dst");
+
+        // recorded and intact, recorded with wrong size, recorded but gone, and found without record
+        let mut records = Vec::new();
+        for (timestamp, size) in [("1720000000", 12), ("1720000100", 5), ("1720000200", 12)] {
+            create_dir_all(dst.join(timestamp)).unwrap();
+            write(dst.join(timestamp).join("src.zip"), "Hello world!").unwrap();
+            let mut backup = Backup {
+                save_path: dst.join(timestamp).join("src.zip").display().to_string(),
+                backup_size: size,
+                ..Backup::default()
+            };
+            records.push(create_backup_record(&mut conn, &mut backup, &mission).unwrap());
+        }
+        remove_dir_all(dst.join("1720000200")).unwrap();
+        create_dir_all(dst.join("1720000300")).unwrap();
+        write(dst.join("1720000300").join("src.zip"), "Hello").unwrap();
+
+        // differences are only reported by default
+        let report = reconcile_backups(Some(&mission.mission_id), &ReconcileOptions::default(), &mut conn).unwrap();
+        assert_eq!(report.missing.iter().map(|item| item.backup_id.clone()).collect::<Vec<String>>(), vec![records[2].backup_id.clone()]);
+        assert_eq!(report.untracked.len(), 1);
+        assert_eq!(report.mismatched.len(), 1);
+        assert_eq!(report.mismatched[0].backup.backup_id, records[1].backup_id);
+        assert_eq!(report.mismatched[0].actual_size, 12);
+        assert!(report.unavailable.is_empty());
+        assert_eq!(query_backup_record(&mut conn, None, Some(&mission.mission_id)).unwrap().len(), 3);
+
+        let options = ReconcileOptions { remove_missing: true, record_untracked: true, fix_sizes: true };
+        let report = reconcile_backups(Some(&mission.mission_id), &options, &mut conn).unwrap();
+        assert_eq!(report.missing.len(), 1);
+        assert_eq!(report.untracked.len(), 1);
+        assert_eq!(report.mismatched.len(), 1);
+
+        let mut saved: Vec<(String, i64)> = query_backup_record(&mut conn, None, Some(&mission.mission_id))
+            .unwrap()
+            .into_iter()
+            .map(|item| (item.save_path, item.backup_size))
+            .collect();
+        saved.sort();
+        assert_eq!(saved, vec![
+            (dst.join("1720000000").join("src.zip").display().to_string(), 12),
+            (dst.join("1720000100").join("src.zip").display().to_string(), 12),
+            (dst.join("1720000300").join("src.zip").display().to_string(), 5),
+        ]);
+
+        // nothing left after fixed
+        let report = reconcile_backups(Some(&mission.mission_id), &ReconcileOptions::default(), &mut conn).unwrap();
+        assert!(report.missing.is_empty() && report.untracked.is_empty() && report.mismatched.is_empty());
+
+        // records of location not available are kept
+        remove_dir_all(&dst).unwrap();
+        let report = reconcile_backups(Some(&mission.mission_id), &options, &mut conn).unwrap();
+        assert_eq!(report.unavailable, vec![mission.dst_path.clone()]);
+        assert_eq!(query_backup_record(&mut conn, None, Some(&mission.mission_id)).unwrap().len(), 3);
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_link_sources() {
+        use crate::utils::explorer::{ CopyStats, IgnoreRules };
+        use std::collections::BTreeMap;
+        use std::env::current_dir;
+        use std::fs::{read, remove_dir_all};
+
+        let test_path = current_dir().expect("").join("test_backup_link_sources");
+        let source = test_path.join("src");
+        test_write_file(&source.join("a.bin"), 1024, 7, 1720000000);
+        test_write_file(&source.join("b.bin"), 1024, 11, 1720000000);
+        let mapping = BTreeMap::from([("src".to_string(), source.display().to_string())]);
+        let rules = IgnoreRules::default();
+        let first = test_path.join("dst").join("1720000000");
+        let second = test_path.join("dst").join("1720000060");
+
+        let size = link_sources(&mapping, &rules, &first, &test_path.join("dst").join("none"), &mut CopyStats::default()).unwrap();
+        assert_eq!(size, 2048);
+
+        // b.bin changed with the same size and an earlier time is copied again
+        test_write_file(&source.join("b.bin"), 1024, 13, 1719999000);
+        let size = link_sources(&mapping, &rules, &second, &first, &mut CopyStats::default()).unwrap();
+        assert_eq!(size, 1024);
+        assert_eq!(read(second.join("src").join("a.bin")).unwrap(), read(source.join("a.bin")).unwrap());
+        assert_eq!(read(second.join("src").join("b.bin")).unwrap(), read(source.join("b.bin")).unwrap());
+        assert_ne!(read(first.join("src").join("b.bin")).unwrap(), read(source.join("b.bin")).unwrap());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            assert_eq!(std::fs::metadata(second.join("src").join("a.bin")).unwrap().nlink(), 2);
+            assert_eq!(std::fs::metadata(second.join("src").join("b.bin")).unwrap().nlink(), 1);
+        }
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_sync_delta_file_unchanged() {
+        use std::env::current_dir;
+        use std::fs::remove_dir_all;
+
+        let test_path = current_dir().expect("").join("test_backup_delta_file");
+        let file = test_path.join("src").join("a.bin");
+        test_write_file(&file, 64 * 1024, 7, 1720000000);
+        let file = file.display().to_string();
+        let unlimited = crate::utils::throttle::RateLimiter::default();
+
+        let (item, signature) = sync_delta_file(&file, &test_path.join("sync_0").join("a.bin"), None, &unlimited, &unlimited).unwrap();
+        assert_eq!(item.kind, 1);
+        assert_eq!(signature.modified, 1720000000);
+        assert_eq!(std::fs::metadata(test_path.join("sync_0").join("a.bin")).unwrap().modified().unwrap(), std::fs::metadata(&file).unwrap().modified().unwrap());
+
+        // unchanged file is not read again
+        let (item, _) = sync_delta_file(&file, &test_path.join("sync_1").join("a.bin"), Some(&signature), &unlimited, &unlimited).unwrap();
+        assert_eq!(item.kind, 0);
+        assert!(!test_path.join("sync_1").join("a.bin").exists());
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_sync_mirror_throttled() {
+        use crate::utils::explorer::IgnoreRules;
+        use std::collections::BTreeMap;
+        use std::env::current_dir;
+        use std::fs::{read, remove_dir_all};
+        use std::time::Instant;
+
+        let test_path = current_dir().expect("").join("test_backup_mirror_throttled");
+        let source = test_path.join("src");
+        test_write_file(&source.join("a.bin"), 256 * 1024, 7, 1720000000);
+        test_write_file(&source.join("b.bin"), 256 * 1024, 11, 1720000000);
+        let mut mapping = BTreeMap::new();
+        mapping.insert("src".to_string(), source.display().to_string());
+        let location = test_path.join("dst").display().to_string();
+
+        // one second of bytes is allowed in a burst, the rest waits
+        let rules = IgnoreRules { read_limit: 256 * 1024, ..IgnoreRules::default() };
+        let start = Instant::now();
+        let size = sync_mirror(&mapping, &rules, &location, 0, "1720000000", &mut Vec::new(), None).unwrap();
+        assert!(start.elapsed().as_secs_f64() >= 0.9);
+        assert_eq!(size, 512 * 1024);
+        assert_eq!(read(test_path.join("dst").join("src").join("b.bin")).unwrap(), read(source.join("b.bin")).unwrap());
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_delta_sync_restore() {
+        use crate::utils::explorer::IgnoreRules;
+        use std::env::current_dir;
+        use std::fs::{read, remove_dir_all};
+
+        let test_path = current_dir().expect("").join("test_backup_delta_sync");
+        let source = test_path.join("src");
+        test_write_file(&source.join("a.bin"), 64 * 1024, 7, 1720000000);
+        test_write_file(&source.join("sub").join("b.bin"), 64 * 1024, 11, 1720000000);
+
+        let mut conn = test_connection();
+        let mission = Mission {
+            src_path: source.display().to_string(),
+            dst_path: test_path.join("dst").display().to_string(),
+            ..Mission::default()
+        };
+        let rules = IgnoreRules::default();
+
+        let (mut full, _) = create_delta_sync(&mission, &rules, "1720000000", &mut conn).unwrap();
+        let full = create_backup_record(&mut conn, &mut full, &mission).unwrap();
+        let origin = read(source.join("sub").join("b.bin")).unwrap();
+
+        // change one byte of b.bin only
+        let mut changed = origin.clone();
+        changed[1000] ^= 0xFF;
+        std::fs::write(source.join("sub").join("b.bin"), &changed).unwrap();
+        let file = std::fs::File::options().write(true).open(source.join("sub").join("b.bin")).unwrap();
+        file.set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1720000100)).unwrap();
+
+        let (mut delta, _) = create_delta_sync(&mission, &rules, "1720000100", &mut conn).unwrap();
+        let delta = create_backup_record(&mut conn, &mut delta, &mission).unwrap();
+        assert_eq!(delta.chain_id, full.chain_id);
+        let manifest: DeltaManifest = read_json_file(&std::path::Path::new(&delta.save_path).join("delta.json")).unwrap();
+        assert_eq!(manifest.base, "1720000000".to_string());
+        assert_eq!(manifest.files.get("src/a.bin").map(|item| item.kind), Some(0));
+        assert_eq!(manifest.files.get("src/sub/b.bin").map(|item| item.kind), Some(2));
+
+        let mapping = map_sources(&mission);
+        let restore_dir = test_path.join("restore");
+        restore_delta_sync(&full, &mapping, Some(restore_dir.display().to_string().as_str())).unwrap();
+        assert_eq!(read(restore_dir.join("src").join("sub").join("b.bin")).unwrap(), origin);
+        remove_dir_all(&restore_dir).unwrap();
+
+        restore_delta_sync(&delta, &mapping, Some(restore_dir.display().to_string().as_str())).unwrap();
+        assert_eq!(read(restore_dir.join("src").join("sub").join("b.bin")).unwrap(), changed);
+        assert_eq!(read(restore_dir.join("src").join("a.bin")).unwrap(), read(source.join("a.bin")).unwrap());
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_delta_retention() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, read_dir, remove_dir_all, write};
+
+        let test_path = current_dir().expect("").join("test_backup_delta_retention");
+        let mut conn = test_connection();
+        let mission = test_mission(&mut conn, &test_path);
+
+        let mut latest = Backup::default();
+        for (chain_id, timestamp) in [("1720000000", "1720000000"), ("1720000000", "1720000060"), ("1720000100", "1720000100")] {
+            let save = test_path.join("dst").join(chain_id).join(timestamp);
+            create_dir_all(&save).unwrap();
+            write(save.join("delta.json"), timestamp).unwrap();
+
+            let mut backup = Backup {
+                save_path: save.display().to_string(),
+                chain_id: chain_id.to_string(),
+                backup_size: 10,
+                ..Backup::default()
+            };
+            latest = create_backup_record(&mut conn, &mut backup, &mission).unwrap();
+        }
+
+        // the earlier chain is deleted with records of all its syncs
+        let location = test_path.join("dst").display().to_string();
+        restrict_saves(&latest, &location, 1, 1, 0, &mut conn).unwrap();
+        let kept: Vec<String> = read_dir(test_path.join("dst")).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(kept, vec!["1720000100".to_string()]);
+        let records = query_backup_record(&mut conn, None, Some(&mission.mission_id)).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].backup_id, latest.backup_id);
+
+        // chain of the latest sync is never deleted
+        restrict_saves(&latest, &location, 3, 0, 0, &mut conn).unwrap();
+        assert!(test_path.join("dst").join("1720000100").exists());
+        assert_eq!(query_backup_record(&mut conn, None, Some(&mission.mission_id)).unwrap().len(), 1);
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_password_requires_encryptable_format() {
+        use super::super::procedure::{ Procedure, check_archive_password, create_procedure_record };
+        use crate::utils::explorer::IgnoreRules;
+        use std::env::current_dir;
+        use std::fs::remove_dir_all;
+        use std::io::ErrorKind;
+
+        let test_path = current_dir().expect("").join("test_backup_password_format");
+        test_write_file(&test_path.join("src").join("a.bin"), 1024, 7, 1720000000);
+
+        let mut conn = test_connection();
+        let mission = Mission {
+            src_path: test_path.join("src").display().to_string(),
+            dst_path: test_path.join("dst").display().to_string(),
+            ..Mission::default()
+        };
+        let mut procedure = Procedure { is_compress: true, compress_format: 2, has_password: true, ..Procedure::default() };
+        assert_eq!(check_archive_password(&procedure).unwrap_err().kind(), ErrorKind::InvalidInput);
+
+        // tar.gz is never encrypted, so nothing is saved
+        let error = create_snapshot(&mission, &procedure, &IgnoreRules::default(), &ThrottleConfig::default(), "1720000000", &mut conn).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+        assert!(!test_path.join("dst").exists());
+
+        procedure.compress_format = 5;
+        assert!(check_archive_password(&procedure).is_ok());
+        assert!(create_procedure_record(&mut conn, &mut procedure).is_ok());
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_pending_upload() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, write, read_to_string, remove_dir_all};
+
+        let test_path = current_dir().expect("").join("test_backup_pending");
+        let stage_dir = test_path.join("stage");
+        let location = test_path.join("remote").display().to_string();
+        create_dir_all(stage_dir.join("1720000000")).unwrap();
+        create_dir_all(stage_dir.join("1720000001")).unwrap();
+        write(stage_dir.join("1720000000").join("save.zip"), "Hello world!").unwrap();
+        write(stage_dir.join("1720000001").join("save.zip"), "Hello world!").unwrap();
+        for (timestamp, target) in [("1720000000", location.clone()), ("1720000001", "/moved".to_string())] {
+            let pending = PendingUpload {
+                location: target,
+                timestamp: timestamp.to_string(),
+                backup: Backup {
+                    save_path: stage_dir.join(timestamp).join("save.zip").display().to_string(),
+                    ..Backup::default()
+                },
+                files: std::collections::BTreeMap::new(),
+            };
+            write_json_file(&stage_dir.join(format!("{}{}", timestamp, PENDING_SUFFIX)), &pending).unwrap();
+        }
+
+        // staged backup for other location is discarded
+        let pendings = take_pending_uploads(&stage_dir, &location);
+        assert_eq!(pendings.len(), 1);
+        let pending = pendings[0].clone();
+        assert_eq!(pending.timestamp, "1720000000".to_string());
+        assert!(!stage_dir.join("1720000001").exists());
+        assert!(!stage_dir.join(format!("1720000001{}", PENDING_SUFFIX)).exists());
+
+        // failed upload keeps staged files for the next try
+        let blocked = test_path.join("blocked");
+        write(&blocked, "").unwrap();
+        let failed = PendingUpload { location: blocked.join("remote").display().to_string(), ..pending.clone() };
+        assert!(upload_pending(&stage_dir, failed).is_err());
+        assert_eq!(take_pending_uploads(&stage_dir, &location).len(), 1);
+
+        let (backup, _) = upload_pending(&stage_dir, pending).unwrap();
+        assert_eq!(backup.save_path, test_path.join("remote").join("1720000000").join("save.zip").display().to_string());
+        assert_eq!(read_to_string(&backup.save_path).unwrap(), "Hello world!".to_string());
+        assert!(!stage_dir.join("1720000000").exists());
+        assert!(take_pending_uploads(&stage_dir, &location).is_empty());
+
+        remove_dir_all(test_path).unwrap();
+    }
 }
@@ -0,0 +1,744 @@
+//! # Bundle
+//! 
+//! `bundle` module contains all functions about export and import app config and missions as a bundle.
+
+use std::collections::HashMap;
+use diesel::sqlite::SqliteConnection;
+use chrono::{NaiveDateTime, Utc};
+use serde::{Serialize, Deserialize};
+use super::backup::Backup;
+use super::destination::Destination;
+use super::ignore::Ignore;
+use super::mission::Mission;
+use super::procedure::Procedure;
+use crate::config::AppConfig;
+
+/// Version of bundle format, increased on incompatible changes.
+const BUNDLE_VERSION: u32 = 1;
+
+/// Struct Bundle
+/// 
+/// Saved as toml if file ends with `.toml`, otherwise as json.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle {
+    /// Version of bundle format
+    pub version: u32,
+
+    /// Version of app creating bundle
+    pub app_version: String,
+
+    /// Bundle export time
+    pub export_at: NaiveDateTime,
+
+    /// App config, screensaver password is never exported
+    #[serde(default)]
+    pub config: Option<AppConfig>,
+
+    /// Procedures used by missions
+    #[serde(default)]
+    pub procedures: Vec<Procedure>,
+
+    /// Ignores of procedures
+    #[serde(default)]
+    pub ignores: Vec<Ignore>,
+
+    /// Missions
+    #[serde(default)]
+    pub missions: Vec<Mission>,
+
+    /// Replicate destinations of missions
+    #[serde(default)]
+    pub destinations: Vec<Destination>,
+
+    /// Backup records of missions
+    #[serde(default)]
+    pub backups: Vec<Backup>,
+}
+
+/// Struct ExportOptions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportOptions {
+    /// Uuid of missions to export, empty means all
+    #[serde(default)]
+    pub missions: Vec<String>,
+
+    /// Whether export app config
+    #[serde(default)]
+    pub with_config: bool,
+
+    /// Whether export backup records
+    #[serde(default)]
+    pub with_backups: bool,
+}
+
+/// Struct ImportOptions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportOptions {
+    /// How to handle mission with same uuid or name already exists
+    /// 
+    /// `0` - skip
+    /// 
+    /// `1` - import as new mission, renamed as `name (n)`
+    /// 
+    /// `2` - overwrite settings of existing mission
+    #[serde(default)]
+    pub conflict: i16,
+
+    /// Whether apply app config in bundle
+    #[serde(default)]
+    pub with_config: bool,
+
+    /// Whether import backup records in bundle
+    #[serde(default)]
+    pub with_backups: bool,
+}
+
+/// Struct ImportReport
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Missions created, paused as paths may differ on this machine
+    pub created: Vec<Mission>,
+
+    /// Existing missions overwritten, paused as well
+    pub updated: Vec<Mission>,
+
+    /// Names of missions skipped for conflict
+    pub skipped: Vec<String>,
+
+    /// Count of backup records imported
+    pub backups: u32,
+
+    /// Whether app config applied
+    pub config_applied: bool,
+}
+
+/// Whether bundle file saved as toml.
+fn is_toml(path: &str) -> bool {
+    std::path::Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+}
+
+/// Writes bundle to file.
+fn write_bundle(path: &str, bundle: &Bundle) -> Result<(), std::io::Error> {
+    use std::io::{ Error, ErrorKind };
+
+    let content = if is_toml(path) {
+        toml::to_string_pretty(bundle).map_err(|error| Error::new(ErrorKind::InvalidData, error))?
+    } else {
+        serde_json::to_string_pretty(bundle).map_err(|error| Error::new(ErrorKind::InvalidData, error))?
+    };
+
+    std::fs::write(path, content)
+}
+
+/// Read bundle from file.
+/// 
+/// # Arguments
+/// 
+/// * `path` - Path of bundle file, toml if ends with `.toml`, otherwise json.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::bundle::read_bundle;
+/// 
+/// match read_bundle("D:\\missions.json") {
+///     Ok(bundle) => {
+///         println!("bundle contains {} missions", bundle.missions.len());
+///     },
+///     Err(error) => {
+///         println!("failed to read bundle, errMsg: {:?}", error);
+///     }
+/// }
+/// ```
+#[allow(dead_code)]
+pub fn read_bundle(path: &str) -> Result<Bundle, std::io::Error> {
+    use std::io::{ Error, ErrorKind };
+
+    let content = std::fs::read_to_string(path)?;
+    let bundle: Bundle = if is_toml(path) {
+        toml::from_str(&content).map_err(|error| Error::new(ErrorKind::InvalidData, error))?
+    } else {
+        serde_json::from_str(&content).map_err(|error| Error::new(ErrorKind::InvalidData, error))?
+    };
+
+    if bundle.version > BUNDLE_VERSION {
+        return Err(Error::new(ErrorKind::InvalidData, format!("unsupported bundle version {}", bundle.version)));
+    }
+
+    Ok(bundle)
+}
+
+/// Export app config and missions to bundle file.
+/// 
+/// # Arguments
+/// 
+/// * `path` - Path of bundle file, toml if ends with `.toml`, otherwise json.
+/// * `options` - What to export.
+/// * `conn` - Connection to database.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, bundle::{export_bundle, ExportOptions}};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let options = ExportOptions { with_config: true, ..Default::default() };
+///     match export_bundle("D:\\missions.toml", &options, &mut conn) {
+///         Ok(bundle) => {
+///             println!("export {} missions", bundle.missions.len());
+///         },
+///         Err(error) => {
+///             println!("failed to export bundle, errMsg: {:?}", error);
+///         }
+///     }
+/// }
+/// ```
+#[allow(dead_code)]
+pub fn export_bundle(path: &str, options: &ExportOptions, conn: &mut SqliteConnection) -> Result<Bundle, std::io::Error> {
+    use log::warn;
+    use std::io::{ Error, ErrorKind };
+    use super::{
+        backup::query_backup_record,
+        destination::query_destination_record,
+        ignore::query_ignore_record,
+        mission::query_mission_record,
+        procedure::query_procedure_record,
+    };
+
+    let missions: Vec<Mission> = query_mission_record(conn, None)
+        .map_err(|_| Error::from(ErrorKind::Other))?
+        .into_iter()
+        .filter(|mission| options.missions.is_empty() || options.missions.contains(&mission.mission_id))
+        .collect();
+
+    let mut bundle = Bundle {
+        version: BUNDLE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        export_at: Utc::now().naive_utc(),
+        config: None,
+        procedures: Vec::new(),
+        ignores: Vec::new(),
+        missions: Vec::new(),
+        destinations: Vec::new(),
+        backups: Vec::new(),
+    };
+
+    for mission in missions.into_iter() {
+        if !bundle.procedures.iter().any(|item| item.procedure_id == mission.procedure_id) {
+            let procedures = query_procedure_record(conn, Some(&mission.procedure_id))
+                .map_err(|_| Error::from(ErrorKind::Other))?;
+            if procedures.is_empty() {
+                warn!("skip mission {} in bundle, errMsg: procedure {} not found", mission.name, mission.procedure_id);
+                continue;
+            }
+
+            bundle.procedures.extend(procedures);
+            bundle.ignores.extend(
+                query_ignore_record(conn, Some(&mission.procedure_id))
+                    .map_err(|_| Error::from(ErrorKind::Other))?
+            );
+        }
+
+        bundle.destinations.extend(
+            query_destination_record(conn, Some(&mission.mission_id))
+                .map_err(|_| Error::from(ErrorKind::Other))?
+        );
+
+        if options.with_backups {
+            bundle.backups.extend(
+                query_backup_record(conn, None, Some(&mission.mission_id))
+                    .map_err(|_| Error::from(ErrorKind::Other))?
+            );
+        }
+
+        bundle.missions.push(mission);
+    }
+
+    if options.with_config {
+        let mut config = crate::config::load_app_config().unwrap_or_default();
+        config.screensaver.password = "".to_string();
+        bundle.config = Some(config);
+    }
+
+    write_bundle(path, &bundle)?;
+
+    Ok(bundle)
+}
+
+/// Get name not used by any mission, as `name (n)`.
+fn unique_mission_name(name: &str, missions: &[Mission]) -> String {
+    let mut index = 1;
+    loop {
+        let candidate = format!("{} ({})", name, index);
+        if !missions.iter().any(|mission| mission.name == candidate) {
+            return candidate;
+        }
+        index += 1;
+    }
+}
+
+/// Import app config and missions from bundle file.
+/// 
+/// Missions conflict with existing ones if they share uuid or name, handled as `options.conflict`.
+/// Procedures, destinations and backup records imported as new get new uuids, references to them are
+/// remapped. Overwritten mission whose procedure is shared with other missions gets a new procedure.
+/// Created and overwritten missions are paused, as their paths may not exist on this machine.
+/// 
+/// Records are imported in one transaction, nothing is imported if any of them fails.
+/// 
+/// Archive passwords are kept in system keyring, so they need to be saved again for new procedures.
+/// 
+/// # Arguments
+/// 
+/// * `path` - Path of bundle file, toml if ends with `.toml`, otherwise json.
+/// * `options` - How to import.
+/// * `conn` - Connection to database.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, bundle::{import_bundle, ImportOptions}};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let options = ImportOptions { conflict: 1, ..Default::default() };
+///     match import_bundle("D:\\missions.toml", &options, &mut conn) {
+///         Ok(report) => {
+///             println!("create {} missions, skip {:?}", report.created.len(), report.skipped);
+///         },
+///         Err(error) => {
+///             println!("failed to import bundle, errMsg: {:?}", error);
+///         }
+///     }
+/// }
+/// ```
+#[allow(dead_code)]
+pub fn import_bundle(path: &str, options: &ImportOptions, conn: &mut SqliteConnection) -> Result<ImportReport, std::io::Error> {
+    use diesel::Connection;
+    use std::io::{ Error, ErrorKind };
+
+    let bundle = read_bundle(path)?;
+
+    // records are imported all or none, error of import is kept as transaction only takes diesel errors
+    let mut failure: Option<Error> = None;
+    let imported = conn.transaction(|conn| {
+        import_records(&bundle, options, conn).map_err(|error| {
+            failure = Some(error);
+            diesel::result::Error::RollbackTransaction
+        })
+    });
+    let mut report = match imported {
+        Ok(report) => report,
+        Err(_) => return Err(failure.unwrap_or(Error::from(ErrorKind::Other))),
+    };
+
+    if options.with_config {
+        if let Some(config) = &bundle.config {
+            let mut config = config.clone();
+            config.screensaver.password = crate::config::load_app_config().unwrap_or_default().screensaver.password;
+            crate::config::save_app_config(&config)?;
+            report.config_applied = true;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Import records of missions in bundle, see [import_bundle].
+fn import_records(bundle: &Bundle, options: &ImportOptions, conn: &mut SqliteConnection) -> Result<ImportReport, std::io::Error> {
+    use log::warn;
+    use std::io::{ Error, ErrorKind };
+    use super::{
+        backup::{ create_backup_record, query_backup_record, update_backup_record },
+        destination::{ create_destination_record, query_destination_record },
+        ignore::{ create_ignore_record, delete_ignore_record },
+        mission::{ create_mission_record, query_mission_record, update_mission_record },
+        procedure::{ check_archive_password, create_procedure_record, query_procedure_record, update_procedure_record },
+    };
+
+    let mut report = ImportReport::default();
+
+    let mut existing = query_mission_record(conn, None)
+        .map_err(|_| Error::from(ErrorKind::Other))?;
+
+    // uuid in bundle -> uuid in database
+    let mut procedure_map: HashMap<String, String> = HashMap::new();
+    let mut mission_map: HashMap<String, Mission> = HashMap::new();
+    let mut destination_map: HashMap<String, String> = HashMap::new();
+
+    for item in bundle.missions.iter() {
+        let procedure = match bundle.procedures.iter().find(|procedure| procedure.procedure_id == item.procedure_id) {
+            Some(procedure) => procedure,
+            None => {
+                warn!("skip mission {} in bundle, errMsg: procedure {} not found", item.name, item.procedure_id);
+                report.skipped.push(item.name.clone());
+                continue;
+            }
+        };
+        let ignores: Vec<Ignore> = bundle.ignores.iter()
+            .filter(|ignore| ignore.procedure_id == procedure.procedure_id)
+            .cloned()
+            .collect();
+
+        let conflict = existing.iter()
+            .find(|mission| mission.mission_id == item.mission_id || mission.name == item.name)
+            .cloned();
+
+        let mission = match (conflict, options.conflict) {
+            (Some(_), 0) => {
+                report.skipped.push(item.name.clone());
+                continue;
+            },
+            (Some(current), 2) => {
+                // procedure shared with other missions is left to them, overwritten mission gets its own
+                let is_shared = existing.iter()
+                    .any(|mission| mission.procedure_id == current.procedure_id && mission.mission_id != current.mission_id);
+                let stored = match is_shared {
+                    true => None,
+                    false => query_procedure_record(conn, Some(&current.procedure_id))
+                        .map_err(|_| Error::from(ErrorKind::Other))?
+                        .into_iter()
+                        .next(),
+                };
+
+                let mut updated_procedure = procedure.clone();
+                if let Some(stored) = stored {
+                    updated_procedure.id = stored.id;
+                    updated_procedure.procedure_id = stored.procedure_id.clone();
+                    updated_procedure.create_at = stored.create_at;
+                    check_archive_password(&updated_procedure)?;
+                    updated_procedure = update_procedure_record(conn, &mut updated_procedure)
+                        .map_err(|_| Error::from(ErrorKind::Other))?;
+                } else {
+                    check_archive_password(&updated_procedure)?;
+                    updated_procedure = create_procedure_record(conn, &mut updated_procedure)
+                        .map_err(|_| Error::from(ErrorKind::Other))?;
+                }
+
+                delete_ignore_record(conn, None, Some(&updated_procedure.procedure_id))
+                    .map_err(|_| Error::from(ErrorKind::Other))?;
+                for ignore in ignores.iter() {
+                    create_ignore_record(conn, &mut ignore.clone(), &updated_procedure)
+                        .map_err(|_| Error::from(ErrorKind::Other))?;
+                }
+
+                let mut mission = item.clone();
+                mission.id = current.id;
+                mission.mission_id = current.mission_id.clone();
+                mission.procedure_id = updated_procedure.procedure_id.clone();
+                mission.status = 0;
+                mission.next_runtime = current.next_runtime;
+                mission.last_trigger = current.last_trigger;
+                mission.create_at = current.create_at;
+                let mission = update_mission_record(conn, &mut mission)
+                    .map_err(|_| Error::from(ErrorKind::Other))?;
+
+                existing.retain(|stored| stored.mission_id != mission.mission_id);
+                report.updated.push(mission.clone());
+                mission
+            },
+            (conflict, _) => {
+                let pid = match procedure_map.get(&procedure.procedure_id) {
+                    Some(pid) => pid.clone(),
+                    None => {
+                        check_archive_password(procedure)?;
+                        let created = create_procedure_record(conn, &mut procedure.clone())
+                            .map_err(|_| Error::from(ErrorKind::Other))?;
+                        for ignore in ignores.iter() {
+                            create_ignore_record(conn, &mut ignore.clone(), &created)
+                                .map_err(|_| Error::from(ErrorKind::Other))?;
+                        }
+                        procedure_map.insert(procedure.procedure_id.clone(), created.procedure_id.clone());
+                        created.procedure_id
+                    }
+                };
+
+                let mut mission = item.clone();
+                mission.procedure_id = pid;
+                mission.status = 0;
+                if conflict.is_some() {
+                    mission.name = unique_mission_name(&item.name, &existing);
+                }
+                let mission = create_mission_record(conn, &mut mission)
+                    .map_err(|_| Error::from(ErrorKind::Other))?;
+
+                report.created.push(mission.clone());
+                mission
+            }
+        };
+
+        let current_destinations = query_destination_record(conn, Some(&mission.mission_id))
+            .map_err(|_| Error::from(ErrorKind::Other))?;
+        for destination in bundle.destinations.iter().filter(|destination| destination.mission_id == item.mission_id) {
+            let did = match current_destinations.iter().find(|current| current.path == destination.path) {
+                Some(current) => current.destination_id.clone(),
+                None => {
+                    let mut created = destination.clone();
+                    created.status = 0;
+                    created.last_error = "".to_string();
+                    create_destination_record(conn, &mut created, &mission)
+                        .map_err(|_| Error::from(ErrorKind::Other))?
+                        .destination_id
+                }
+            };
+            destination_map.insert(destination.destination_id.clone(), did);
+        }
+
+        existing.push(mission.clone());
+        mission_map.insert(item.mission_id.clone(), mission);
+    }
+
+    if options.with_backups {
+        // originals first, so copies can be remapped to them
+        let mut backups: Vec<&Backup> = bundle.backups.iter().collect();
+        backups.sort_by_key(|backup| !backup.origin_id.is_empty());
+
+        let mut backup_map: HashMap<String, String> = HashMap::new();
+        for backup in backups.into_iter() {
+            let mission = match mission_map.get(&backup.mission_id) {
+                Some(mission) => mission,
+                None => continue,
+            };
+
+            let recorded = query_backup_record(conn, None, Some(&mission.mission_id))
+                .map_err(|_| Error::from(ErrorKind::Other))?;
+            if let Some(current) = recorded.iter().find(|current| current.save_path == backup.save_path) {
+                backup_map.insert(backup.backup_id.clone(), current.backup_id.clone());
+                continue;
+            }
+
+            let mut data = backup.clone();
+            if !backup.destination_id.is_empty() {
+                match destination_map.get(&backup.destination_id) {
+                    Some(did) => data.destination_id = did.clone(),
+                    None => continue,
+                }
+            }
+            if !backup.origin_id.is_empty() {
+                data.origin_id = backup_map.get(&backup.origin_id).cloned().unwrap_or_default();
+            }
+
+            let mut created = create_backup_record(conn, &mut data, mission)
+                .map_err(|_| Error::from(ErrorKind::Other))?;
+            created.create_at = backup.create_at;
+            let created = update_backup_record(conn, &mut created)
+                .map_err(|_| Error::from(ErrorKind::Other))?;
+
+            backup_map.insert(backup.backup_id.clone(), created.backup_id);
+            report.backups += 1;
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use super::super::test_util::test_connection;
+
+    /// Bundle of one mission, with its procedure, destination, a backup and a copy of the backup.
+    fn test_bundle(name: &str) -> Bundle {
+        let procedure = Procedure { name: format!("{} procedure", name), compress_level: 9, ..Procedure::default() };
+        let mission = Mission {
+            procedure_id: procedure.procedure_id.clone(),
+            name: name.to_string(),
+            status: 1,
+            src_path: format!("/data/{}", name),
+            dst_path: format!("/backups/{}", name),
+            ..Mission::default()
+        };
+        let destination = Destination {
+            mission_id: mission.mission_id.clone(),
+            path: format!("/mirror/{}", name),
+            status: 1,
+            ..Destination::default()
+        };
+        let backup = Backup {
+            mission_id: mission.mission_id.clone(),
+            save_path: format!("/backups/{}/1720000000/src.zip", name),
+            ..Backup::default()
+        };
+        let copy = Backup {
+            mission_id: mission.mission_id.clone(),
+            destination_id: destination.destination_id.clone(),
+            origin_id: backup.backup_id.clone(),
+            save_path: format!("/mirror/{}/1720000000/src.zip", name),
+            ..Backup::default()
+        };
+
+        Bundle {
+            version: BUNDLE_VERSION,
+            app_version: "0.0.0".to_string(),
+            export_at: Utc::now().naive_utc(),
+            config: None,
+            procedures: vec![procedure],
+            ignores: Vec::new(),
+            missions: vec![mission],
+            destinations: vec![destination],
+            backups: vec![backup, copy],
+        }
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, remove_dir_all};
+
+        let test_path = current_dir().expect("").join("test_bundle_round_trip");
+        create_dir_all(&test_path).unwrap();
+        let bundle = test_bundle("documents");
+
+        for name in ["missions.json", "missions.toml"] {
+            let path = test_path.join(name).display().to_string();
+            write_bundle(&path, &bundle).unwrap();
+            let read = read_bundle(&path).unwrap();
+            assert_eq!(read.missions[0].mission_id, bundle.missions[0].mission_id, "{}", name);
+            assert_eq!(read.missions[0].src_path, bundle.missions[0].src_path, "{}", name);
+            assert_eq!(read.procedures[0].compress_level, 9, "{}", name);
+            assert_eq!(read.destinations[0].path, bundle.destinations[0].path, "{}", name);
+            assert_eq!(read.backups[1].origin_id, bundle.backups[0].backup_id, "{}", name);
+        }
+        assert!(std::fs::read_to_string(test_path.join("missions.toml")).unwrap().contains("[[missions]]"));
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_bundle_version() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, remove_dir_all};
+        use std::io::ErrorKind;
+
+        let test_path = current_dir().expect("").join("test_bundle_version");
+        create_dir_all(&test_path).unwrap();
+        let path = test_path.join("missions.json").display().to_string();
+
+        let bundle = Bundle { version: BUNDLE_VERSION + 1, ..test_bundle("documents") };
+        write_bundle(&path, &bundle).unwrap();
+        assert_eq!(read_bundle(&path).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_import_remap() {
+        use super::super::{ backup::query_backup_record, destination::query_destination_record, procedure::query_procedure_record };
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, remove_dir_all};
+
+        let test_path = current_dir().expect("").join("test_bundle_import_remap");
+        create_dir_all(&test_path).unwrap();
+        let path = test_path.join("missions.json").display().to_string();
+        let bundle = test_bundle("documents");
+        write_bundle(&path, &bundle).unwrap();
+
+        let mut conn = test_connection();
+        let options = ImportOptions { with_backups: true, ..Default::default() };
+        let report = import_bundle(&path, &options, &mut conn).unwrap();
+        assert_eq!(report.created.len(), 1);
+        assert_eq!(report.backups, 2);
+
+        // records get uuids of their own, references follow them
+        let mission = &report.created[0];
+        assert_ne!(mission.mission_id, bundle.missions[0].mission_id);
+        assert_eq!(mission.status, 0);
+        let procedures = query_procedure_record(&mut conn, Some(&mission.procedure_id)).unwrap();
+        assert_eq!(procedures.len(), 1);
+        assert_ne!(procedures[0].procedure_id, bundle.procedures[0].procedure_id);
+
+        let destinations = query_destination_record(&mut conn, Some(&mission.mission_id)).unwrap();
+        assert_eq!(destinations.len(), 1);
+        assert_ne!(destinations[0].destination_id, bundle.destinations[0].destination_id);
+        assert_eq!(destinations[0].status, 0);
+
+        let backups = query_backup_record(&mut conn, None, Some(&mission.mission_id)).unwrap();
+        let origin = backups.iter().find(|backup| backup.origin_id.is_empty()).unwrap();
+        let copy = backups.iter().find(|backup| !backup.origin_id.is_empty()).unwrap();
+        assert_eq!(copy.origin_id, origin.backup_id);
+        assert_eq!(copy.destination_id, destinations[0].destination_id);
+        assert_eq!(origin.create_at, bundle.backups[0].create_at);
+
+        // backups recorded already are not imported twice
+        let options = ImportOptions { conflict: 2, with_backups: true, ..Default::default() };
+        assert_eq!(import_bundle(&path, &options, &mut conn).unwrap().backups, 0);
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_import_conflicts() {
+        use super::super::{
+            mission::{ create_mission_record, query_mission_record },
+            procedure::{ create_procedure_record, query_procedure_record },
+        };
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, remove_dir_all};
+
+        let test_path = current_dir().expect("").join("test_bundle_import_conflicts");
+        create_dir_all(&test_path).unwrap();
+        let path = test_path.join("missions.json").display().to_string();
+        write_bundle(&path, &test_bundle("documents")).unwrap();
+
+        // existing mission of same name shares its procedure with another mission
+        let mut conn = test_connection();
+        let shared = create_procedure_record(&mut conn, &mut Procedure { name: "shared".to_string(), ..Procedure::default() }).unwrap();
+        for name in ["documents", "pictures"] {
+            let mut mission = Mission { procedure_id: shared.procedure_id.clone(), name: name.to_string(), status: 1, ..Mission::default() };
+            create_mission_record(&mut conn, &mut mission).unwrap();
+        }
+
+        let report = import_bundle(&path, &ImportOptions { conflict: 0, ..Default::default() }, &mut conn).unwrap();
+        assert_eq!(report.skipped, vec!["documents".to_string()]);
+        assert_eq!(query_mission_record(&mut conn, None).unwrap().len(), 2);
+
+        let report = import_bundle(&path, &ImportOptions { conflict: 1, ..Default::default() }, &mut conn).unwrap();
+        assert_eq!(report.created[0].name, "documents (1)".to_string());
+        assert_eq!(query_mission_record(&mut conn, None).unwrap().len(), 3);
+
+        // overwritten mission is paused and leaves shared procedure alone
+        let report = import_bundle(&path, &ImportOptions { conflict: 2, ..Default::default() }, &mut conn).unwrap();
+        let updated = &report.updated[0];
+        assert_eq!(updated.name, "documents".to_string());
+        assert_eq!(updated.src_path, "/data/documents".to_string());
+        assert_eq!(updated.status, 0);
+        assert_ne!(updated.procedure_id, shared.procedure_id);
+        assert_eq!(query_procedure_record(&mut conn, Some(&shared.procedure_id)).unwrap()[0].name, "shared".to_string());
+        assert_eq!(query_procedure_record(&mut conn, Some(&updated.procedure_id)).unwrap()[0].name, "documents procedure".to_string());
+
+        // procedure of its own is overwritten in place
+        let report = import_bundle(&path, &ImportOptions { conflict: 2, ..Default::default() }, &mut conn).unwrap();
+        assert_eq!(report.updated[0].procedure_id, updated.procedure_id);
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_import_rollback() {
+        use super::super::{ mission::query_mission_record, procedure::query_procedure_record };
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, remove_dir_all};
+
+        let test_path = current_dir().expect("").join("test_bundle_import_rollback");
+        create_dir_all(&test_path).unwrap();
+        let path = test_path.join("missions.json").display().to_string();
+
+        // procedure of the second mission is refused, as tar.gz can not be encrypted
+        let mut bundle = test_bundle("documents");
+        let mut invalid = test_bundle("pictures");
+        invalid.procedures[0].is_compress = true;
+        invalid.procedures[0].compress_format = 2;
+        invalid.procedures[0].has_password = true;
+        bundle.procedures.extend(invalid.procedures);
+        bundle.missions.extend(invalid.missions);
+        write_bundle(&path, &bundle).unwrap();
+
+        let mut conn = test_connection();
+        assert!(import_bundle(&path, &ImportOptions::default(), &mut conn).is_err());
+        assert!(query_mission_record(&mut conn, None).unwrap().is_empty());
+        assert!(query_procedure_record(&mut conn, None).unwrap().is_empty());
+
+        remove_dir_all(test_path).unwrap();
+    }
+}
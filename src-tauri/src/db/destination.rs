@@ -0,0 +1,406 @@
+//! # Destination
+//! 
+//! `destination` module contains all functions about handle 'destination' table.
+
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use chrono::{NaiveDateTime, Utc};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use super::mission::Mission;
+use crate::utils::common::rand_number;
+
+/// Struct Destination
+#[derive(Debug, Queryable, Selectable, Insertable, AsChangeset, Serialize, Deserialize, Clone)]
+#[diesel(table_name = super::schema::destination)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Destination {
+    /// Primary key for table
+    pub id: i32,
+
+    /// Uuid for destination
+    pub destination_id: String,
+
+    /// Belong to which mission
+    pub mission_id: String,
+
+    /// Replicate save path(absolute)
+    pub path: String,
+
+    /// Destination status
+    /// 
+    /// `0` - unknown
+    /// 
+    /// `1` - available
+    /// 
+    /// `2` - failed
+    pub status: i16,
+
+    /// Whether restrict backups in destination
+    /// 
+    /// `0` - no restrict
+    /// 
+    /// `1` - days restrict
+    /// 
+    /// `2` - size restrict
+    /// 
+    /// `3` - days and size restrict
+    pub restrict: i16,
+
+    /// Restrict days
+    pub restrict_days: i16,
+
+    /// Restrict size, in byte
+    pub restrict_size: i64,
+
+    /// Error message of last replication
+    pub last_error: String,
+
+    /// Last successful replication time
+    pub last_sync: NaiveDateTime,
+
+    /// Reserved for future use
+    pub reserved_0: String,
+
+    /// Reserved for future use
+    pub reserved_1: String,
+
+    /// Reserved for future use
+    pub reserved_2: String,
+
+    /// Destination create time
+    pub create_at: NaiveDateTime,
+
+    /// Destination update time
+    pub update_at: NaiveDateTime,
+
+    /// Whether been deleted
+    /// 
+    /// `0` - not deleted
+    /// 
+    /// `1` - been deleted
+    pub is_deleted: i16,
+
+    /// Delete time
+    pub delete_at: NaiveDateTime,
+}
+
+impl Default for Destination {
+    fn default() -> Self {
+        Destination {
+            id: rand_number(),
+            destination_id: Uuid::new_v4().to_string(),
+            mission_id: Uuid::new_v4().to_string(),
+            path: "".to_string(),
+            status: 0,
+            restrict: 0,
+            restrict_days: 3,
+            restrict_size: 1024,
+            last_error: "".to_string(),
+            last_sync: Utc::now().naive_utc(),
+            reserved_0: "".to_string(),
+            reserved_1: "".to_string(),
+            reserved_2: "".to_string(),
+            create_at: Utc::now().naive_utc(),
+            update_at: Utc::now().naive_utc(),
+            is_deleted: 0,
+            delete_at: Utc::now().naive_utc(),
+        }
+    }
+}
+
+/// Create destination record and insert into database.
+/// 
+/// # Arguments
+/// 
+/// * `conn` - Connection to database.
+/// * `data` - Data for destination.
+/// * `mission` - Which mission replicates to this destination
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, destination::create_destination_record};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let mut destination = Destination::default();
+///     let mission = Mission::default();
+///     match create_destination_record(&mut conn, &mut destination, &mission) {
+///         Ok(record) => {
+///             println!("create record: {:?}", record);
+///         },
+///         Err(error) => {
+///             println!("failed to create record, errMsg: {:?}", error);
+///         }
+///     }
+/// }
+/// ```
+pub fn create_destination_record(
+    conn: &mut SqliteConnection,
+    data: &mut Destination,
+    mission: &Mission
+) -> Result<Destination, diesel::result::Error> {
+    use super::schema::destination::dsl::*;
+
+    let cur_time: NaiveDateTime = Utc::now().naive_utc();
+    data.id = destination.count().get_result(conn).unwrap_or(0) as i32 + 1;
+    data.destination_id = Uuid::new_v4().to_string();
+    data.mission_id = mission.mission_id.clone();
+    data.create_at = cur_time;
+    data.update_at = cur_time;
+
+    diesel::insert_into(destination)
+        .values(data.clone())
+        .returning(Destination::as_returning())
+        .get_result(conn)
+}
+
+/// Update destination record in database.
+/// 
+/// # Arguments
+/// 
+/// * `conn` - Connection to database.
+/// * `data` - Updated Data for destination.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, destination::update_destination_record};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let mut destination = Destination::default();
+///     destination.path = "D:\\backups".to_string();
+///     match update_destination_record(&mut conn, &mut destination) {
+///         Ok(record) => {
+///             println!("update record: {:?}", record);
+///         },
+///         Err(error) => {
+///             println!("failed to update record, errMsg: {:?}", error);
+///         }
+///     }
+/// }
+/// ```
+pub fn update_destination_record(
+    conn: &mut SqliteConnection,
+    data: &mut Destination,
+) -> Result<Destination, diesel::result::Error> {
+    use super::schema::{destination, destination::destination_id};
+
+    let cur_time: NaiveDateTime = Utc::now().naive_utc();
+    data.update_at = cur_time;
+
+    diesel::update(destination::table)
+        .filter(destination_id.eq(&data.destination_id))
+        .set(data.clone())
+        .returning(Destination::as_returning())
+        .get_result(conn)
+}
+
+/// Get destination records from database.
+/// 
+/// # Arguments
+/// 
+/// * `conn` - Connection to database.
+/// * `uuid` - Uuid for mission, if `None`, get all.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, destination::query_destination_record};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     match query_destination_record(&mut conn, None) {
+///         Ok(records) => {
+///             println!("get all records: {:?}", records);
+///         },
+///         Err(error) => {
+///             println!("failed to get records, errMsg: {:?}", error);
+///         }
+///     }
+/// }
+/// ```
+pub fn query_destination_record(
+    conn: &mut SqliteConnection,
+    uuid: Option<&str>,
+) -> Result<Vec<Destination>, diesel::result::Error> {
+    use super::schema::destination::dsl::*;
+
+    match uuid {
+        Some(uid) => {
+            destination.filter(mission_id.eq(uid))
+            .filter(is_deleted.eq(0))
+            .select(Destination::as_select())
+            .load(conn)
+        },
+        None => {
+            destination.select(Destination::as_select())
+                    .filter(is_deleted.eq(0))
+                    .load(conn)
+        }
+    }
+}
+
+/// Delete destination record in database logically.
+/// 
+/// # Arguments
+/// 
+/// * `conn` - Connection to database.
+/// * `did` - Uuid for destination, delete single destination record.
+/// * `mid` - Uuid for mission, delete all related destination records.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, destination::delete_destination_record};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let mid = "1c69eead-b7cf-457e-95e2-9c9f459120ff";
+///     match delete_destination_record(&mut conn, None, Some(mid)) {
+///         Ok(cnt) => {
+///             println!("delete {} records", cnt);
+///         },
+///         Err(error) => {
+///             println!("failed to delete records, errMsg: {:?}", error);
+///         }
+///     }
+/// }
+/// ```
+pub fn delete_destination_record(
+    conn: &mut SqliteConnection,
+    did: Option<&str>,
+    mid: Option<&str>,
+) -> Result<usize, diesel::result::Error> {
+    use super::schema::destination::dsl::*;
+
+    if let Some(uuid) = did {
+        return diesel::update(destination)
+                    .filter(destination_id.eq(uuid))
+                    .set((
+                        is_deleted.eq(1),
+                        delete_at.eq(Utc::now().naive_utc())
+                    ))
+                    .execute(conn);
+    } else if let Some(uuid) = mid {
+        return diesel::update(destination)
+                    .filter(mission_id.eq(uuid))
+                    .set((
+                        is_deleted.eq(1),
+                        delete_at.eq(Utc::now().naive_utc())
+                    ))
+                    .execute(conn);
+    }
+
+    Err(diesel::result::Error::NotFound)
+}
+
+/// Clear 'destination' table records.
+/// 
+/// # Arguments
+/// 
+/// * `conn` - Connection to database.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, destination::clear_destination_record};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     match clear_destination_record(&mut conn) {
+///         Ok(cnt) => {
+///             println!("clear table with total {} records", cnt);
+///         },
+///         Err(error) => {
+///             println!("failed to clear records, errMsg: {:?}", error);
+///         }
+///     }
+/// }
+/// ```
+pub fn clear_destination_record(
+    conn: &mut SqliteConnection,
+) -> Result<usize, diesel::result::Error> {
+    use super::schema::destination::dsl::*;
+
+    diesel::delete(destination)
+        .execute(conn)
+}
+
+/// Clean 'destination' table records.
+/// 
+/// Physically delete records where `is_deleted` is `1`, and reorder the remaining records.
+/// 
+/// # Arguments
+/// 
+/// * `conn` - Connection to database.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, destination::clean_record};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     match clean_record(&mut conn) {
+///         Ok(cnt) => {
+///             println!("cleaned {} records", cnt);
+///         },
+///         Err(error) => {
+///             println!("failed to clean records, errMsg: {:?}", error);
+///         }
+///     }
+/// }
+/// ```
+pub fn clean_record(
+    conn: &mut SqliteConnection,
+) -> Result<usize, diesel::result::Error> {
+    use super::schema::destination::dsl::*;
+
+    let cleaned: usize = diesel::delete(destination.filter(is_deleted.eq(1))).execute(conn)?;
+
+    let mut remaining: Vec<Destination> = destination.select(Destination::as_select()).load(conn)?;
+    for (idx, item) in remaining.iter_mut().enumerate() {
+        let new_id = (idx + 1) as i32;
+        diesel::update(destination)
+            .filter(destination_id.eq(&item.destination_id))
+            .set(id.eq(new_id))
+            .execute(conn)?;
+    }
+
+    Ok(cleaned)
+}
+
+/// Update destination status after replication.
+/// 
+/// # Arguments
+/// 
+/// * `conn` - Connection to database.
+/// * `data` - Target destination.
+/// * `error` - Error message if replication failed.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, destination::update_destination_status};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let mut destination = Destination::default();
+///     let _ = update_destination_status(&mut conn, &mut destination, Some("destination unavailable"));
+/// }
+/// ```
+pub fn update_destination_status(
+    conn: &mut SqliteConnection,
+    data: &mut Destination,
+    error: Option<&str>,
+) -> Result<Destination, diesel::result::Error> {
+    match error {
+        Some(msg) => {
+            data.status = 2;
+            data.last_error = msg.to_string();
+        },
+        None => {
+            data.status = 1;
+            data.last_error = "".to_string();
+            data.last_sync = Utc::now().naive_utc();
+        }
+    }
+
+    update_destination_record(conn, data)
+}
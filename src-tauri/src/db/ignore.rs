@@ -24,9 +24,17 @@ pub struct Ignore {
     /// Belong to which procedure
     pub procedure_id: String,
 
-    /// Ignore keyword
+    /// Ignore pattern in gitignore syntax, relative to mission source path
     pub keyword: String,
 
+    /// Rule type
+    /// 
+    /// `0` - exclude matched paths
+    /// 
+    /// `1` - include matched paths only
+    #[serde(default)]
+    pub rule_type: i16,
+
     /// Reserved for future use
     pub reserved_0: String,
 
@@ -60,6 +68,7 @@ impl Default for Ignore {
             ignore_id: Uuid::new_v4().to_string(),
             procedure_id: Uuid::new_v4().to_string(),
             keyword: "".to_string(),
+            rule_type: 0,
             reserved_0: "".to_string(),
             reserved_1: "".to_string(),
             reserved_2: "".to_string(),
@@ -355,6 +364,7 @@ pub fn clean_record(
     Ok(cleaned)   
 }
 
+#[allow(dead_code)]
 /// Get procedure related ignores.
 /// 
 /// # Arguments
@@ -380,10 +390,39 @@ pub fn clean_record(
 /// }
 /// ```
 pub fn get_procedure_ignores(pid: &str, conn: &mut SqliteConnection) -> Vec<String> {
+    get_procedure_patterns(pid, 0, conn)
+}
+
+#[allow(dead_code)]
+/// Get procedure related include patterns.
+/// 
+/// # Arguments
+/// 
+/// * `pid` - Target procedure.
+/// * `conn` - Connection to database.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, ignore::get_procedure_includes};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let pid = "e56da9c2-851e-4cb5-a896-f371f2e3997f";
+///     let includes = get_procedure_includes(pid, &mut conn);
+///     println!("get includes {:?} for procedure {}", includes, pid);
+/// }
+/// ```
+pub fn get_procedure_includes(pid: &str, conn: &mut SqliteConnection) -> Vec<String> {
+    get_procedure_patterns(pid, 1, conn)
+}
+
+#[allow(dead_code)]
+/// Get procedure related patterns with the given rule type.
+fn get_procedure_patterns(pid: &str, kind: i16, conn: &mut SqliteConnection) -> Vec<String> {
     let mut data = Vec::new();
     if let Ok(ignores) = query_ignore_record(conn, Some(pid)) {
         for ignore in ignores.iter() {
-            if ignore.is_deleted == 1 {
+            if ignore.is_deleted == 1 || ignore.rule_type != kind {
                 continue;
             }
             data.push(ignore.keyword.clone());
@@ -392,3 +431,101 @@ pub fn get_procedure_ignores(pid: &str, conn: &mut SqliteConnection) -> Vec<Stri
 
     data
 }
+
+/// Get all ignore rules of procedure.
+/// 
+/// Layers .gitignore files, custom exclude and include patterns, 
+/// and the size and age filters of procedure.
+/// 
+/// # Arguments
+/// 
+/// * `procedure` - Target procedure.
+/// * `conn` - Connection to database.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::{establish_sqlite_connection, ignore::get_procedure_rules};
+/// 
+/// if let Ok(mut conn) = establish_sqlite_connection() {
+///     let procedure = Procedure::default();
+///     let rules = get_procedure_rules(&procedure, &mut conn);
+///     println!("get rules {:?} for procedure {}", rules, procedure.procedure_id);
+/// }
+/// ```
+pub fn get_procedure_rules(procedure: &Procedure, conn: &mut SqliteConnection) -> crate::utils::explorer::IgnoreRules {
+    let ignores = query_ignore_record(conn, Some(&procedure.procedure_id)).unwrap_or_default();
+
+    build_procedure_rules(procedure, &ignores)
+}
+
+/// Build ignore rules of procedure with the given ignores.
+/// 
+/// Used for unsaved ignores, see [get_procedure_rules] for saved ones.
+/// 
+/// # Arguments
+/// 
+/// * `procedure` - Target procedure.
+/// * `ignores` - Ignores of procedure.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::ignore::{Ignore, build_procedure_rules};
+/// 
+/// let procedure = Procedure { has_ignores: true, ignore_method: 1, ..Procedure::default() };
+/// let ignores = vec![Ignore { keyword: "*.log".to_string(), ..Ignore::default() }];
+/// let rules = build_procedure_rules(&procedure, &ignores);
+/// assert_eq!(rules.excludes, vec!["*.log".to_string()]);
+/// ```
+pub fn build_procedure_rules(procedure: &Procedure, ignores: &[Ignore]) -> crate::utils::explorer::IgnoreRules {
+    use crate::utils::explorer::IgnoreRules;
+
+    let mut rules = IgnoreRules::default();
+    if procedure.has_ignores {
+        if procedure.ignore_method & 1 != 0 {
+            for ignore in ignores.iter().filter(|item| item.is_deleted == 0) {
+                match ignore.rule_type {
+                    1 => rules.includes.push(ignore.keyword.clone()),
+                    _ => rules.excludes.push(ignore.keyword.clone()),
+                }
+            }
+        }
+        rules.build_in = procedure.ignore_method & 2 != 0;
+    }
+    rules.max_size = procedure.max_file_size.max(0) as u64;
+    rules.max_days = procedure.max_file_days.max(0) as u64;
+    rules.on_error = procedure.error_policy;
+    rules.retries = procedure.error_retries.max(0) as u32;
+    rules.workers = procedure.copy_workers.max(0) as usize;
+
+    rules
+}
+
+/// Convert legacy ignore keyword to gitignore-style pattern.
+/// 
+/// Legacy keywords were matched as substrings of the full path.
+/// 
+/// # Arguments
+/// 
+/// * `keyword` - Legacy keyword.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::ignore::keyword_to_pattern;
+/// 
+/// assert_eq!(keyword_to_pattern("target"), "target".to_string());
+/// assert_eq!(keyword_to_pattern("src\\debug"), "**/src/debug".to_string());
+/// ```
+pub fn keyword_to_pattern(keyword: &str) -> String {
+    let pattern = keyword.replace('\\', "/").trim().to_string();
+
+    if pattern.trim_end_matches('/').contains('/')
+        && !pattern.starts_with('/')
+        && !pattern.starts_with("**/") {
+        return format!("**/{}", pattern);
+    }
+
+    pattern
+}
@@ -41,6 +41,12 @@ pub struct Mission {
     /// Target source path(absolute)
     pub src_path: String,
 
+    /// All source paths(absolute) in JSON array, backed up side by side
+    /// 
+    /// Empty means only `src_path`
+    #[serde(default)]
+    pub src_paths: String,
+
     /// Target save path(absolute)
     pub dst_path: String,
 
@@ -95,6 +101,7 @@ impl Default for Mission {
             status: 0,
             description: "".to_string(),
             src_path: "".to_string(),
+            src_paths: "".to_string(),
             dst_path: "".to_string(),
             path_type: 0,
             next_runtime: Utc::now().naive_utc(),
@@ -110,6 +117,25 @@ impl Default for Mission {
     }
 }
 
+impl Mission {
+    /// Get all source paths of mission.
+    /// 
+    /// Falls back to `src_path` if `src_paths` is empty or invalid.
+    pub fn sources(&self) -> Vec<String> {
+        let sources: Vec<String> = serde_json::from_str::<Vec<String>>(&self.src_paths)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|item| !item.is_empty())
+            .collect();
+
+        if sources.is_empty() {
+            return vec![self.src_path.clone()];
+        }
+
+        sources
+    }
+}
+
 /// Create mission record and insert into database.
 /// 
 /// # Arguments
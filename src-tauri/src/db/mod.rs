@@ -1,11 +1,16 @@
+pub mod destination;
 pub mod ignore;
 pub mod mission;
 pub mod backup;
+pub mod bundle;
 pub mod procedure;
 pub mod schema;
 pub mod utils;
+#[cfg(test)]
+mod test_util;
 
 use self::backup::Backup;
+use self::destination::Destination;
 use self::ignore::Ignore;
 use self::mission::Mission;
 use self::procedure::Procedure;
@@ -24,6 +29,10 @@ pub struct Record {
     /// Backup record
     pub backup: Backup,
 
+    /// Destination record
+    #[serde(default)]
+    pub destination: Destination,
+
     /// Ignore record
     pub ignore: Ignore,
 
@@ -38,6 +47,7 @@ impl Default for Record {
     fn default() -> Self {
         Record {
             backup: Backup::default(),
+            destination: Destination::default(),
             ignore: Ignore::default(),
             mission: Mission::default(),
             procedure: Procedure::default(),
@@ -184,6 +194,9 @@ pub fn create_db_record(table: &str, data: &mut Record, conn: &mut SqliteConnect
         "backup" => {
             backup::create_backup_record(conn, &mut data.backup, &data.mission)?;
         },
+        "destination" => {
+            destination::create_destination_record(conn, &mut data.destination, &data.mission)?;
+        },
         _ => {
             return Err(Error::from(Error::NotFound));
         }
@@ -233,6 +246,9 @@ pub fn update_db_record(table: &str, data: &mut Record, conn: &mut SqliteConnect
         "backup" => {
             // backup::update_backup_record(conn, &mut data.backup)?;
         },
+        "destination" => {
+            destination::update_destination_record(conn, &mut data.destination)?;
+        },
         _ => {
             return Err(Error::from(Error::NotFound));
         }
@@ -306,6 +322,15 @@ pub fn query_db_record(table: &str, uid: Option<&str>, conn: &mut SqliteConnecti
                 res.push(full);
             }
         },
+        "destination" => {
+            let records = destination::query_destination_record(conn, uid)?;
+
+            for item in records {
+                let mut full = Record::default();
+                full.destination = item;
+                res.push(full);
+            }
+        },
         _ => {
             return Err(Error::from(Error::NotFound));
         }
@@ -356,6 +381,9 @@ pub fn delete_db_record(table: &str, uuid_0: Option<&str>, uuid_1: Option<&str>,
         "backup" => {
             remove_cnt = backup::delete_backup_record(conn, uuid_0, uuid_1)?;
         },
+        "destination" => {
+            remove_cnt = destination::delete_destination_record(conn, uuid_0, uuid_1)?;
+        },
         _ => {
             return Err(Error::from(Error::NotFound));
         }
@@ -404,6 +432,9 @@ pub fn clear_db_record(table: &str, conn: &mut SqliteConnection) -> Result<usize
         "backup" => {
             remove_cnt = backup::clear_backup_record(conn)?;
         },
+        "destination" => {
+            remove_cnt = destination::clear_destination_record(conn)?;
+        },
         _ => {
             return Err(Error::from(Error::NotFound));
         }
@@ -454,6 +485,9 @@ pub fn clean_db_record(table: &str, conn: &mut SqliteConnection) -> Result<usize
         "backup" => {
             remove_cnt = backup::clean_record(conn)?;
         },
+        "destination" => {
+            remove_cnt = destination::clean_record(conn)?;
+        },
         _ => {
             return Err(Error::from(Error::NotFound));
         }
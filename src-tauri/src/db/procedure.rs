@@ -26,13 +26,15 @@ pub struct Procedure {
     /// Whether has ignores
     pub has_ignores: bool,
 
-    /// Ignore method
+    /// Ignore method, can be combined
     /// 
     /// `0` - no ignores
     /// 
     /// `1` - use custom ignores
     /// 
     /// `2` - use .gitignore 
+    /// 
+    /// `3` - use custom ignores and .gitignore
     pub ignore_method: i16,
 
     /// Whether compress
@@ -51,6 +53,10 @@ pub struct Procedure {
     /// `4` - tar.xz
     /// 
     /// `5` - 7z
+    /// 
+    /// `6` - tar.zst
+    /// 
+    /// `7` - tar.lz4
     pub compress_format: i16,
 
     /// Which trigger backup
@@ -86,6 +92,116 @@ pub struct Procedure {
     /// Restrict size, in byte
     pub restrict_size: i64,
 
+    /// Skip files larger than this size, in byte
+    /// 
+    /// `0` - no limit
+    #[serde(default)]
+    pub max_file_size: i64,
+
+    /// Skip files not modified within these days
+    /// 
+    /// `0` - no limit
+    #[serde(default)]
+    pub max_file_days: i16,
+
+    /// How backup is saved
+    /// 
+    /// `0` - snapshot, copy of sources in each backup
+    /// 
+    /// `1` - delta sync, only changed blocks since last backup
+    /// 
+    /// `2` - mirror, single up-to-date copy of sources
+    /// 
+    /// `3` - hard link snapshot, unchanged files are linked to previous snapshot
+    #[serde(default)]
+    pub backup_mode: i16,
+
+    /// How files removed from sources are handled in mirror
+    /// 
+    /// `0` - keep in mirror
+    /// 
+    /// `1` - delete from mirror
+    /// 
+    /// `2` - move to `.trash/<timestamp>` in mirror
+    #[serde(default)]
+    pub mirror_removed: i16,
+
+    /// How error of single file is handled, like unreadable file or broken link
+    /// 
+    /// `0` - abort backup
+    /// 
+    /// `1` - skip and record in backup
+    /// 
+    /// `2` - retry `error_retries` times, then skip and record in backup
+    #[serde(default)]
+    pub error_policy: i16,
+
+    /// Times to retry failed file when `error_policy` is `2`
+    #[serde(default)]
+    pub error_retries: i16,
+
+    /// Count of threads copying files of directory sources
+    /// 
+    /// `0` - decided by available cpus
+    /// 
+    /// `1` - copy one by one
+    #[serde(default)]
+    pub copy_workers: i16,
+
+    /// Bytes per second read from sources, `0` means no limit, `-1` uses app config
+    #[serde(default = "inherit_throttle")]
+    pub read_limit: i64,
+
+    /// Bytes per second written to save path, `0` means no limit, `-1` uses app config
+    #[serde(default = "inherit_throttle")]
+    pub write_limit: i64,
+
+    /// Priority of compression thread, `-1` uses app config
+    /// 
+    /// `0` - normal
+    /// 
+    /// `1` - below normal
+    /// 
+    /// `2` - lowest
+    #[serde(default = "inherit_priority")]
+    pub compress_priority: i16,
+
+    /// Compression level, `-1` uses default level of format
+    #[serde(default = "default_level")]
+    pub compress_level: i16,
+
+    /// Compression method of zip archive
+    /// 
+    /// `0` - deflate
+    /// 
+    /// `1` - bzip2
+    /// 
+    /// `2` - zstd
+    /// 
+    /// `3` - store without compression
+    #[serde(default)]
+    pub compress_method: i16,
+
+    /// Dictionary size in MB for xz and 7z archive, `0` uses size of compression level
+    #[serde(default)]
+    pub dict_size: i32,
+
+    /// Count of threads compressing xz archive
+    /// 
+    /// `0` - decided by available cpus
+    /// 
+    /// `1` - single thread
+    #[serde(default = "single_thread")]
+    pub compress_threads: i16,
+
+    /// Size in MB of each volume archive is split into, `0` means no split
+    #[serde(default)]
+    pub volume_size: i64,
+
+    /// Whether zip and 7z archives are encrypted with password, which is saved in system keyring
+    #[serde(default)]
+    pub has_password: bool,
+
     /// Reserved for future use
     pub reserved_0: String,
 
@@ -112,6 +228,26 @@ pub struct Procedure {
     pub delete_at: NaiveDateTime,
 }
 
+/// Throttle of procedure follows app config by default.
+fn inherit_throttle() -> i64 {
+    -1
+}
+
+/// Compression priority of procedure follows app config by default.
+fn inherit_priority() -> i16 {
+    -1
+}
+
+/// Compression level of procedure follows format by default.
+fn default_level() -> i16 {
+    -1
+}
+
+/// Archive of procedure is compressed in single thread by default.
+fn single_thread() -> i16 {
+    1
+}
+
 impl Default for Procedure {
     fn default() -> Self {
         Procedure {
@@ -127,6 +263,22 @@ impl Default for Procedure {
             restrict: 0,
             restrict_days: 3,
             restrict_size: 1024,
+            max_file_size: 0,
+            max_file_days: 0,
+            backup_mode: 0,
+            mirror_removed: 0,
+            error_policy: 0,
+            error_retries: 3,
+            copy_workers: 0,
+            read_limit: -1,
+            write_limit: -1,
+            compress_priority: -1,
+            compress_level: -1,
+            compress_method: 0,
+            dict_size: 0,
+            compress_threads: 1,
+            volume_size: 0,
+            has_password: false,
             reserved_0: "".to_string(),
             reserved_1: "".to_string(),
             reserved_2: "".to_string(),
@@ -425,3 +577,202 @@ pub fn clean_record(
 
     Ok(cleaned)   
 }
+
+/// Get throttle of procedure, items not overridden by procedure follow app config.
+/// 
+/// # Arguments
+/// 
+/// * `item` - Procedure to get throttle for.
+/// * `config` - Throttle in loaded app config.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::procedure::{Procedure, get_procedure_throttle};
+/// use config::throttle::ThrottleConfig;
+/// 
+/// let throttle = get_procedure_throttle(&Procedure::default(), &ThrottleConfig::default());
+/// println!("write at most {} bytes per second", throttle.write_limit);
+/// ```
+pub fn get_procedure_throttle(item: &Procedure, config: &crate::config::throttle::ThrottleConfig) -> crate::config::throttle::ThrottleConfig {
+    config.with_overrides(item.read_limit, item.write_limit, item.compress_priority)
+}
+
+/// Whether procedure creates zip or 7z archive, the formats able to be encrypted.
+pub fn is_encryptable(item: &Procedure) -> bool {
+    item.is_compress && matches!(item.compress_format, 1 | 5)
+}
+
+/// Checks procedure with password creates archive able to be encrypted,
+/// so backups are never saved unencrypted when password is expected.
+/// 
+/// Procedure should be checked before it is created or updated.
+/// 
+/// # Arguments
+/// 
+/// * `item` - Procedure to check.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::procedure::{Procedure, check_archive_password};
+/// 
+/// if let Err(error) = check_archive_password(&Procedure::default()) {
+///     println!("invalid procedure, errMsg: {:?}", error);
+/// }
+/// ```
+pub fn check_archive_password(item: &Procedure) -> Result<(), std::io::Error> {
+    use std::io::{ Error, ErrorKind };
+
+    if item.has_password && !is_encryptable(item) {
+        return Err(Error::new(ErrorKind::InvalidInput, "archive password requires zip or 7z format"));
+    }
+
+    Ok(())
+}
+
+/// Gets key of archive password of procedure in system keyring.
+/// 
+/// The first version keeps the key without version, which archives encrypted before versioning use.
+fn archive_password_key(pid: &str, version: i16) -> String {
+    match version {
+        ..=1 => format!("archive:{}", pid),
+        _ => format!("archive:{}:{}", pid, version),
+    }
+}
+
+/// Gets latest version of archive password of procedure in system keyring, `0` if none saved.
+fn archive_password_version(pid: &str) -> i16 {
+    use crate::utils::secret::get_secret;
+
+    let mut version = 0;
+    while version < i16::MAX && get_secret(&archive_password_key(pid, version + 1)).is_ok() {
+        version += 1;
+    }
+
+    version
+}
+
+/// Save archive password of procedure into system keyring.
+/// 
+/// Changed password is saved as a new version, previous versions are kept for
+/// restoring archives encrypted with them. Empty password saves nothing, as procedure
+/// stops using password by turning off `has_password`.
+/// 
+/// # Arguments
+/// 
+/// * `pid` - Uuid for procedure.
+/// * `password` - Password to encrypt zip and 7z archives.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::procedure::save_archive_password;
+/// 
+/// let pid = "1c69eead-b7cf-457e-95e2-9c9f459120ff";
+/// save_archive_password(pid, "password").unwrap();
+/// ```
+pub fn save_archive_password(pid: &str, password: &str) -> Result<(), std::io::Error> {
+    use crate::utils::secret::{ get_secret, set_secret };
+
+    if password.is_empty() {
+        return Ok(());
+    }
+
+    let version = archive_password_version(pid);
+    if version > 0 && get_secret(&archive_password_key(pid, version))? == password {
+        return Ok(());
+    }
+
+    set_secret(&archive_password_key(pid, version + 1), password)
+}
+
+/// Load archive password of procedure from system keyring.
+/// 
+/// Password is loaded even if procedure no longer has one, for restoring archives encrypted before.
+/// 
+/// # Arguments
+/// 
+/// * `pid` - Uuid for procedure.
+/// * `version` - Version of password, recorded by backup as [super::backup::Backup::is_encrypted].
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::procedure::load_archive_password;
+/// 
+/// let pid = "1c69eead-b7cf-457e-95e2-9c9f459120ff";
+/// if let Ok(password) = load_archive_password(pid, 1) {
+///     println!("archive encrypted with password of length {}", password.len());
+/// }
+/// ```
+pub fn load_archive_password(pid: &str, version: i16) -> Result<String, std::io::Error> {
+    use crate::utils::secret::get_secret;
+    use std::io::{ Error, ErrorKind };
+    use log::error;
+
+    match get_secret(&archive_password_key(pid, version)) {
+        Ok(password) => Ok(password),
+        Err(_) => {
+            error!("no archive password of version {} found for procedure {}", version, pid);
+            Err(Error::from(ErrorKind::PermissionDenied))
+        }
+    }
+}
+
+/// Get latest archive password of procedure with its version, see [load_archive_password].
+/// 
+/// Returns `None` if procedure has no password.
+/// 
+/// # Arguments
+/// 
+/// * `item` - Procedure to get password for.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use db::procedure::{Procedure, get_archive_password};
+/// 
+/// if let Ok(Some((version, password))) = get_archive_password(&Procedure::default()) {
+///     println!("archive encrypted with password version {} of length {}", version, password.len());
+/// }
+/// ```
+pub fn get_archive_password(item: &Procedure) -> Result<Option<(i16, String)>, std::io::Error> {
+    if !item.has_password {
+        return Ok(None);
+    }
+
+    let version = archive_password_version(&item.procedure_id).max(1);
+    load_archive_password(&item.procedure_id, version).map(|password| Some((version, password)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Needs a running system keyring, like secret service on linux.
+    #[test]
+    #[ignore]
+    fn test_archive_password_versions() {
+        let pid = "test-archive-password-versions";
+        save_archive_password(pid, "first").unwrap();
+        save_archive_password(pid, "first").unwrap();
+        assert_eq!(archive_password_version(pid), 1);
+
+        let item = Procedure { procedure_id: pid.to_string(), has_password: true, ..Procedure::default() };
+        save_archive_password(pid, "second").unwrap();
+        assert_eq!(get_archive_password(&item).unwrap(), Some((2, "second".to_string())));
+        assert_eq!(load_archive_password(pid, 1).unwrap(), "first".to_string());
+
+        // empty password keeps saved versions, the next one follows them
+        save_archive_password(pid, "").unwrap();
+        assert_eq!(archive_password_version(pid), 2);
+        save_archive_password(pid, "third").unwrap();
+        assert_eq!(get_archive_password(&item).unwrap(), Some((3, "third".to_string())));
+        assert_eq!(load_archive_password(pid, 2).unwrap(), "second".to_string());
+
+        for version in 1..=3 {
+            crate::utils::secret::delete_secret(&archive_password_key(pid, version)).unwrap();
+        }
+    }
+}
@@ -14,6 +14,37 @@ diesel::table! {
         update_at -> Timestamp,
         is_deleted -> SmallInt,
         delete_at -> Timestamp,
+        sources -> Text,
+        destination_id -> Text,
+        origin_id -> Text,
+        chain_id -> Text,
+        is_mirror -> SmallInt,
+        copy_method -> Text,
+        skipped -> Text,
+        volumes -> Integer,
+        is_encrypted -> SmallInt,
+    }
+}
+
+diesel::table! {
+    destination (id) {
+        id -> Integer,
+        destination_id -> Text,
+        mission_id -> Text,
+        path -> Text,
+        status -> SmallInt,
+        restrict -> SmallInt,
+        restrict_days -> SmallInt,
+        restrict_size -> BigInt,
+        last_error -> Text,
+        last_sync -> Timestamp,
+        reserved_0 -> Text,
+        reserved_1 -> Text,
+        reserved_2 -> Text,
+        create_at -> Timestamp,
+        update_at -> Timestamp,
+        is_deleted -> SmallInt,
+        delete_at -> Timestamp,
     }
 }
 
@@ -30,6 +61,7 @@ diesel::table! {
         update_at -> Timestamp,
         is_deleted -> SmallInt,
         delete_at -> Timestamp,
+        rule_type -> SmallInt,
     }
 }
 
@@ -53,6 +85,7 @@ diesel::table! {
         update_at -> Timestamp,
         is_deleted -> SmallInt,
         delete_at -> Timestamp,
+        src_paths -> Text,
     }
 }
 
@@ -77,11 +110,28 @@ diesel::table! {
         update_at -> Timestamp,
         is_deleted -> SmallInt,
         delete_at -> Timestamp,
+        max_file_size -> BigInt,
+        max_file_days -> SmallInt,
+        backup_mode -> SmallInt,
+        mirror_removed -> SmallInt,
+        error_policy -> SmallInt,
+        error_retries -> SmallInt,
+        copy_workers -> SmallInt,
+        read_limit -> BigInt,
+        write_limit -> BigInt,
+        compress_priority -> SmallInt,
+        compress_level -> SmallInt,
+        compress_method -> SmallInt,
+        dict_size -> Integer,
+        compress_threads -> SmallInt,
+        volume_size -> BigInt,
+        has_password -> Bool,
     }
 }
 
 diesel::allow_tables_to_appear_in_same_query!(
     backup,
+    destination,
     ignore,
     mission,
     procedure,
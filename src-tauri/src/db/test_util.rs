@@ -0,0 +1,15 @@
+//! # Test util
+//! 
+//! `test_util` module contains helpers shared by tests of database modules.
+
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+
+/// Connection to database in memory with tables created.
+pub fn test_connection() -> SqliteConnection {
+    use diesel_migrations::MigrationHarness;
+
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    conn.run_pending_migrations(super::MIGRATIONS).unwrap();
+    conn
+}
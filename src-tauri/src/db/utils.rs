@@ -87,6 +87,7 @@ pub fn get_db_path() -> Result<String, std::io::Error> {
 pub fn get_db_deleted_count(conn: &mut SqliteConnection) -> Result<u64, std::io::Error> {
     use crate::db::schema::{
         backup::dsl::*,
+        destination::dsl::*,
         ignore::dsl::*,
         mission::dsl::*,
         procedure::dsl::*
@@ -94,6 +95,7 @@ pub fn get_db_deleted_count(conn: &mut SqliteConnection) -> Result<u64, std::io:
 
     let mut count: u64 = 0;
     count += backup.filter(super::schema::backup::is_deleted.eq(1)).count().get_result(conn).unwrap_or(0) as u64;
+    count += destination.filter(super::schema::destination::is_deleted.eq(1)).count().get_result(conn).unwrap_or(0) as u64;
     count += ignore.filter(super::schema::ignore::is_deleted.eq(1)).count().get_result(conn).unwrap_or(0) as u64;
     count += mission.filter(super::schema::mission::is_deleted.eq(1)).count().get_result(conn).unwrap_or(0) as u64;
     count += procedure.filter(super::schema::procedure::is_deleted.eq(1)).count().get_result(conn).unwrap_or(0) as u64;  
@@ -118,7 +120,7 @@ pub fn clean_database_records(conn: &mut SqliteConnection) -> Result<DBInfo, std
 
     let db_path = get_db_path()?;
 
-    let tables = vec!["backup", "ignore", "mission", "procedure"];
+    let tables = vec!["backup", "destination", "ignore", "mission", "procedure"];
     let mut cleaned_cnt: usize = 0;
     for item in tables {
         match clean_db_record(item, conn) {
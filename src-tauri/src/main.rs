@@ -6,6 +6,7 @@ mod plugins;
 mod config;
 mod core;
 mod db;
+mod storage;
 
 use tauri_plugin_autostart::MacosLauncher;
 use plugins::on_another_instance;
@@ -0,0 +1,104 @@
+//! # Local
+//! 
+//! `local` module contains backend of local file system.
+
+use super::{StorageBackend, StorageEntry};
+
+/// Struct LocalStorage
+#[derive(Debug, Clone, Default)]
+pub struct LocalStorage {}
+
+impl LocalStorage {
+    /// Create local storage.
+    pub fn new() -> Self {
+        LocalStorage {}
+    }
+}
+
+/// Get storage entry from metadata.
+fn to_entry(path: &std::path::Path, meta: &std::fs::Metadata) -> StorageEntry {
+    use std::time::UNIX_EPOCH;
+
+    StorageEntry {
+        path: path.display().to_string(),
+        is_dir: meta.is_dir(),
+        size: if meta.is_dir() { 0 } else { meta.len() },
+        modified: meta.modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_secs()),
+    }
+}
+
+impl StorageBackend for LocalStorage {
+    fn is_local(&self) -> bool {
+        true
+    }
+
+    fn put(&self, local: &str, remote: &str) -> Result<(), std::io::Error> {
+        crate::utils::explorer::merge_all(local, remote)
+    }
+
+    fn get(&self, remote: &str, local: &str) -> Result<(), std::io::Error> {
+        crate::utils::explorer::merge_all(remote, local)
+    }
+
+    fn list(&self, remote: &str) -> Result<Vec<StorageEntry>, std::io::Error> {
+        use std::fs::read_dir;
+
+        let mut entries = Vec::new();
+        for entry in read_dir(remote)? {
+            let entry = entry?;
+            entries.push(to_entry(&entry.path(), &entry.metadata()?));
+        }
+
+        Ok(entries)
+    }
+
+    fn delete(&self, remote: &str) -> Result<(), std::io::Error> {
+        crate::utils::explorer::remove_all(remote)
+    }
+
+    fn stat(&self, remote: &str) -> Result<StorageEntry, std::io::Error> {
+        use std::path::Path;
+        use std::fs::metadata;
+
+        Ok(to_entry(Path::new(remote), &metadata(remote)?))
+    }
+
+    fn rename(&self, from: &str, to: &str) -> Result<(), std::io::Error> {
+        use crate::utils::explorer::{merge_all, remove_all};
+        use std::path::Path;
+        use std::fs::create_dir_all;
+
+        if let Some(parent) = Path::new(to).parent() {
+            create_dir_all(parent)?;
+        }
+
+        // items on different devices can not be renamed
+        if std::fs::rename(from, to).is_err() {
+            merge_all(from, to)?;
+            remove_all(from)?;
+        }
+
+        Ok(())
+    }
+
+    fn join(&self, path: &str, name: &str) -> String {
+        use std::path::Path;
+
+        Path::new(path).join(name).display().to_string()
+    }
+
+    fn size(&self, remote: &str) -> Result<u64, std::io::Error> {
+        crate::utils::explorer::get_path_size(remote)
+    }
+
+    fn restrict_count(&self, remote: &str, count: usize) -> Result<(), std::io::Error> {
+        crate::utils::explorer::restrict_dir_subitems_count(remote, count)
+    }
+
+    fn restrict_size(&self, remote: &str, size: u64) -> Result<(), std::io::Error> {
+        crate::utils::explorer::restrict_dir_subitems_size(remote, size)
+    }
+}
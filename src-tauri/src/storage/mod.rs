@@ -0,0 +1,432 @@
+//! # Storage
+//! 
+//! `storage` module contains backends that backups can be saved to.
+//! 
+//! A backup location is either a local path, or an url of remote backend like `sftp://user@host:22/backups`.
+
+pub mod local;
+pub mod sftp;
+pub mod s3;
+pub mod webdav;
+
+use serde::{Serialize, Deserialize};
+
+/// Struct StorageEntry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StorageEntry {
+    /// Path of entry in backend
+    pub path: String,
+
+    /// Whether entry is directory
+    pub is_dir: bool,
+
+    /// Size of file in bytes, `0` for directory
+    pub size: u64,
+
+    /// Last modification time in unix seconds
+    pub modified: u64,
+}
+
+/// Backend that backups are saved to.
+/// 
+/// Paths of backend are absolute paths in the backend, use [open_storage] to split them from location.
+pub trait StorageBackend {
+    /// Whether backend saves to local file system.
+    fn is_local(&self) -> bool {
+        false
+    }
+
+    /// Uploads local file or directory to backend, overwriting existing files.
+    fn put(&self, local: &str, remote: &str) -> Result<(), std::io::Error>;
+
+    /// Downloads file or directory from backend, overwriting existing files.
+    fn get(&self, remote: &str, local: &str) -> Result<(), std::io::Error>;
+
+    /// Lists the direct children of directory.
+    fn list(&self, remote: &str) -> Result<Vec<StorageEntry>, std::io::Error>;
+
+    /// Deletes the whole file or directory.
+    fn delete(&self, remote: &str) -> Result<(), std::io::Error>;
+
+    /// Gets information of file or directory.
+    fn stat(&self, remote: &str) -> Result<StorageEntry, std::io::Error>;
+
+    /// Moves file or directory inside backend, overwriting existing files.
+    /// 
+    /// Backends without server side moving download and upload the item again.
+    fn rename(&self, from: &str, to: &str) -> Result<(), std::io::Error> {
+        use crate::utils::explorer::remove_all;
+
+        let temp_dir = std::env::temp_dir().join("mission_backup").join(uuid::Uuid::new_v4().to_string());
+        let local = temp_dir.join("item").display().to_string();
+        let moved = self.get(from, &local).and_then(|_| self.put(&local, to));
+        let _ = remove_all(temp_dir.display().to_string().as_str());
+        moved?;
+
+        self.delete(from)
+    }
+
+    /// Joins name to path of backend.
+    fn join(&self, path: &str, name: &str) -> String {
+        format!("{}/{}", path.trim_end_matches('/'), name.trim_start_matches('/'))
+    }
+
+    /// Whether backend is reachable and path can be saved to.
+    fn is_available(&self, path: &str) -> bool {
+        self.stat(path).is_ok_and(|entry| entry.is_dir)
+    }
+
+    /// Whether file or directory exists.
+    fn exists(&self, remote: &str) -> bool {
+        self.stat(remote).is_ok()
+    }
+
+    /// Gets the total size of file or directory.
+    fn size(&self, remote: &str) -> Result<u64, std::io::Error> {
+        let entry = self.stat(remote)?;
+        if !entry.is_dir {
+            return Ok(entry.size);
+        }
+
+        let mut total = 0;
+        for child in self.list(remote)? {
+            total += match child.is_dir {
+                true => self.size(&child.path)?,
+                false => child.size,
+            };
+        }
+
+        Ok(total)
+    }
+
+    /// Restricts the directory to wanted items count, deletes the earliest modified items.
+    /// 
+    /// Items modified in the same second are ordered by name, as backups are named by timestamp.
+    fn restrict_count(&self, remote: &str, count: usize) -> Result<(), std::io::Error> {
+        let mut items = self.list(remote)?;
+        if items.len() > count {
+            items.sort_by(|a, b| (a.modified, &a.path).cmp(&(b.modified, &b.path)));
+            for item in items[..(items.len() - count)].iter() {
+                self.delete(&item.path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Restricts the directory to wanted size, deletes the earliest modified items.
+    fn restrict_size(&self, remote: &str, size: u64) -> Result<(), std::io::Error> {
+        let mut items = self.list(remote)?;
+        items.sort_by(|a, b| (a.modified, &a.path).cmp(&(b.modified, &b.path)));
+
+        let mut sizes = Vec::new();
+        for item in items.iter() {
+            sizes.push(self.size(&item.path)?);
+        }
+
+        let mut total: u64 = sizes.iter().sum();
+        for (item, item_size) in items.iter().zip(sizes.iter()) {
+            if total <= size {
+                break;
+            }
+            self.delete(&item.path)?;
+            total -= item_size;
+        }
+
+        Ok(())
+    }
+}
+
+/// Url schemes of remote backends.
+const REMOTE_SCHEMES: [&str; 4] = ["sftp://", "s3://", "webdav://", "webdavs://"];
+
+#[allow(dead_code)]
+/// Whether location is in remote backend.
+/// 
+/// # Arguments
+/// 
+/// * `location` - A string that holds the local path or remote url
+/// 
+/// # Examples
+/// 
+/// ```
+/// use storage::is_remote;
+/// 
+/// assert_eq!(is_remote("sftp://robin@nas.local/backups"), true);
+/// assert_eq!(is_remote("D:\\backups"), false);
+/// ```
+pub fn is_remote(location: &str) -> bool {
+    REMOTE_SCHEMES.iter().any(|scheme| location.starts_with(scheme))
+}
+
+/// Splits query like `?endpoint=...` from remote url.
+fn split_query(location: &str) -> (&str, &str) {
+    match location.find('?') {
+        Some(idx) => location.split_at(idx),
+        None => (location, ""),
+    }
+}
+
+#[allow(dead_code)]
+/// Gets path of url in remote server.
+/// 
+/// # Arguments
+/// 
+/// * `url` - Url of location.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use storage::url_path;
+/// 
+/// let url = url::Url::parse("sftp://robin@nas.local/my%20backups").unwrap();
+/// assert_eq!(url_path(&url), "/my backups".to_string());
+/// ```
+pub fn url_path(url: &url::Url) -> String {
+    match percent_decode(url.path()) {
+        path if path.is_empty() => "/".to_string(),
+        path => path,
+    }
+}
+
+/// Decodes percent encoded string like `my%20backups`.
+pub fn percent_decode(data: &str) -> String {
+    let bytes = data.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let hex = bytes.get(idx + 1..idx + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[idx], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                idx += 3;
+            },
+            (byte, _) => {
+                decoded.push(byte);
+                idx += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Percent encodes string, keeps unreserved characters, and `/` if not `encode_slash`.
+pub fn uri_encode(data: &str, encode_slash: bool) -> String {
+    let mut encoded = String::new();
+    for byte in data.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+#[allow(dead_code)]
+/// Joins name to location.
+/// 
+/// # Arguments
+/// 
+/// * `location` - A string that holds the local path or remote url
+/// * `name` - Name to join, may contain `/`
+/// 
+/// # Examples
+/// 
+/// ```
+/// use storage::join_location;
+/// 
+/// assert_eq!(join_location("sftp://robin@nas.local/backups", "1720000000"), "sftp://robin@nas.local/backups/1720000000");
+/// ```
+pub fn join_location(location: &str, name: &str) -> String {
+    use std::path::Path;
+
+    if is_remote(location) {
+        let (base, query) = split_query(location);
+        return format!("{}/{}{}", base.trim_end_matches('/'), name.trim_start_matches('/'), query);
+    }
+
+    Path::new(location).join(name).display().to_string()
+}
+
+#[allow(dead_code)]
+/// Gets parent of location.
+/// 
+/// # Arguments
+/// 
+/// * `location` - A string that holds the local path or remote url
+/// 
+/// # Examples
+/// 
+/// ```
+/// use storage::parent_location;
+/// 
+/// assert_eq!(parent_location("sftp://robin@nas.local/backups/1720000000"), Some("sftp://robin@nas.local/backups".to_string()));
+/// ```
+pub fn parent_location(location: &str) -> Option<String> {
+    use std::path::Path;
+
+    if is_remote(location) {
+        let (base, query) = split_query(location);
+        return base.trim_end_matches('/')
+            .rsplit_once('/')
+            .filter(|(parent, _)| !parent.ends_with('/'))
+            .map(|(parent, _)| format!("{}{}", parent, query));
+    }
+
+    Path::new(location).parent().map(|parent| parent.display().to_string())
+}
+
+#[allow(dead_code)]
+/// Opens backend of location.
+/// 
+/// Returns the backend and the path of location in backend.
+/// 
+/// # Arguments
+/// 
+/// * `location` - A string that holds the local path or remote url
+/// 
+/// # Examples
+/// 
+/// ```
+/// use storage::open_storage;
+/// 
+/// let (storage, path) = open_storage("sftp://robin@nas.local/backups").unwrap();
+/// assert_eq!(path, "/backups".to_string());
+/// println!("{:?}", storage.list(&path));
+/// ```
+pub fn open_storage(location: &str) -> Result<(Box<dyn StorageBackend>, String), std::io::Error> {
+    use std::io::{Error, ErrorKind};
+    use log::error;
+
+    if !is_remote(location) {
+        return Ok((Box::new(local::LocalStorage::new()), location.to_string()));
+    }
+
+    let url = match url::Url::parse(location) {
+        Ok(url) => url,
+        Err(error) => {
+            error!("invalid storage location {}, errMsg: {:?}", location, error);
+            return Err(Error::from(ErrorKind::InvalidInput));
+        }
+    };
+
+    match url.scheme() {
+        "sftp" => {
+            let storage = sftp::SftpStorage::connect(&url)?;
+            Ok((Box::new(storage), url_path(&url)))
+        },
+        "s3" => {
+            let storage = s3::S3Storage::connect(&url)?;
+            Ok((Box::new(storage), url_path(&url)))
+        },
+        "webdav" | "webdavs" => {
+            let storage = webdav::WebDavStorage::connect(&url)?;
+            Ok((Box::new(storage), url_path(&url)))
+        },
+        _ => {
+            Err(Error::from(ErrorKind::Unsupported))
+        }
+    }
+}
+
+#[allow(dead_code)]
+/// Saves credential of remote location into system keyring.
+/// 
+/// # Arguments
+/// 
+/// * `location` - A string that holds the remote url
+/// * `access_key` - Access key for S3, unused for WebDAV as user is in url
+/// * `secret` - Secret key for S3, or password for WebDAV
+/// 
+/// # Examples
+/// 
+/// ```
+/// use storage::save_credential;
+/// 
+/// save_credential("webdavs://robin@cloud.example.com/backups", "", "app-password").unwrap();
+/// ```
+pub fn save_credential(location: &str, access_key: &str, secret: &str) -> Result<(), std::io::Error> {
+    use std::io::{Error, ErrorKind};
+
+    match location.split_once("://").map(|(scheme, _)| scheme) {
+        Some("s3") => {
+            let credential = s3::S3Credential { access_key: access_key.to_string(), secret_key: secret.to_string() };
+            s3::save_credential(location, &credential)
+        },
+        Some("webdav") | Some("webdavs") => {
+            webdav::save_credential(location, secret)
+        },
+        _ => {
+            Err(Error::from(ErrorKind::Unsupported))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_join_location() {
+        assert_eq!(join_location("sftp://robin@nas.local/backups/", "/1720000000"), "sftp://robin@nas.local/backups/1720000000".to_string());
+        assert_eq!(is_remote("sftp://robin@nas.local/backups"), true);
+        assert_eq!(is_remote("/home/robin/backups"), false);
+        assert_eq!(parent_location("sftp://robin@nas.local/backups/1720000000/"), Some("sftp://robin@nas.local/backups".to_string()));
+        assert_eq!(parent_location("sftp://robin@nas.local/backups"), Some("sftp://robin@nas.local".to_string()));
+        assert_eq!(parent_location("sftp://robin@nas.local"), None);
+        assert_eq!(join_location("s3://backups/mission?region=us-east-1", "1720000000"), "s3://backups/mission/1720000000?region=us-east-1".to_string());
+        assert_eq!(parent_location("s3://backups/mission/1720000000?region=us-east-1"), Some("s3://backups/mission?region=us-east-1".to_string()));
+    }
+
+    #[test]
+    fn test_url_path() {
+        let url = url::Url::parse("sftp://robin@nas.local:2222/my%20backups/?key=/home/robin/.ssh/id_rsa").unwrap();
+        assert_eq!(url_path(&url), "/my backups/".to_string());
+        assert_eq!(url.port(), Some(2222));
+
+        let url = url::Url::parse("s3://backups?region=us-east-1").unwrap();
+        assert_eq!(url_path(&url), "/".to_string());
+    }
+
+    /// Backend without overriding the provided methods, like remote backends.
+    struct PlainStorage(local::LocalStorage);
+
+    impl StorageBackend for PlainStorage {
+        fn put(&self, local: &str, remote: &str) -> Result<(), std::io::Error> { self.0.put(local, remote) }
+        fn get(&self, remote: &str, local: &str) -> Result<(), std::io::Error> { self.0.get(remote, local) }
+        fn list(&self, remote: &str) -> Result<Vec<StorageEntry>, std::io::Error> { self.0.list(remote) }
+        fn delete(&self, remote: &str) -> Result<(), std::io::Error> { self.0.delete(remote) }
+        fn stat(&self, remote: &str) -> Result<StorageEntry, std::io::Error> { self.0.stat(remote) }
+    }
+
+    #[test]
+    fn test_restrict_storage() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, write, remove_dir_all};
+
+        let test_path = current_dir().expect("").join("test_storage_restrict");
+        for name in ["1720000000", "1720000001", "1720000002", "1720000003"] {
+            create_dir_all(test_path.join(name)).unwrap();
+            write(test_path.join(name).join("save.txt"), "Hello world!").unwrap();
+        }
+
+        let storage = PlainStorage(local::LocalStorage::new());
+        let path = test_path.display().to_string();
+        assert_eq!(storage.size(&path).unwrap(), 48);
+
+        storage.restrict_count(&path, 3).unwrap();
+        assert_eq!(storage.exists(&test_path.join("1720000000").display().to_string()), false);
+
+        storage.restrict_size(&path, 24).unwrap();
+        let remaining: Vec<String> = storage.list(&path).unwrap().iter().map(|item| item.path.clone()).collect();
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(storage.exists(&test_path.join("1720000003").display().to_string()), true);
+
+        let _ = remove_dir_all(test_path).unwrap();
+    }
+}
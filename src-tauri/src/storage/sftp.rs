@@ -0,0 +1,281 @@
+//! # Sftp
+//! 
+//! `sftp` module contains backend of remote server over SFTP.
+//! 
+//! Location is like `sftp://user@host:22/backups`, authenticates with ssh agent first, then with
+//! private key in query `?key=/path/to/key` or default keys `~/.ssh/id_ed25519` and `~/.ssh/id_rsa`.
+//! 
+//! Host key must be recorded in `~/.ssh/known_hosts`, passwords are never stored.
+
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use log::error;
+use super::{StorageBackend, StorageEntry};
+
+/// Struct SftpStorage
+pub struct SftpStorage {
+    /// Session to server, keeps connection alive
+    _session: ssh2::Session,
+
+    /// Sftp channel of session
+    sftp: ssh2::Sftp,
+}
+
+/// Gets home directory of current user.
+fn home_dir() -> Option<PathBuf> {
+    directories::BaseDirs::new().map(|dirs| dirs.home_dir().to_path_buf())
+}
+
+/// Verifies host key of session with `~/.ssh/known_hosts`.
+fn check_known_host(session: &ssh2::Session, host: &str, port: u16) -> Result<(), Error> {
+    use ssh2::{CheckResult, KnownHostFileKind};
+
+    let known_hosts_path = match home_dir() {
+        Some(home) => home.join(".ssh").join("known_hosts"),
+        None => return Err(Error::from(ErrorKind::NotFound)),
+    };
+
+    let mut known_hosts = session.known_hosts()?;
+    known_hosts.read_file(&known_hosts_path, KnownHostFileKind::OpenSSH)?;
+
+    let (key, _) = match session.host_key() {
+        Some(key) => key,
+        None => return Err(Error::from(ErrorKind::InvalidData)),
+    };
+
+    match known_hosts.check_port(host, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => {
+            error!("host key of {}:{} mismatch with known_hosts", host, port);
+            Err(Error::from(ErrorKind::PermissionDenied))
+        },
+        CheckResult::NotFound => {
+            error!("host {}:{} not found in known_hosts", host, port);
+            Err(Error::from(ErrorKind::PermissionDenied))
+        },
+        CheckResult::Failure => {
+            Err(Error::from(ErrorKind::Other))
+        }
+    }
+}
+
+/// Authenticates session with ssh agent or private keys.
+fn authenticate(session: &ssh2::Session, user: &str, key: Option<String>) -> Result<(), Error> {
+    if session.userauth_agent(user).is_ok() && session.authenticated() {
+        return Ok(());
+    }
+
+    let keys: Vec<PathBuf> = match key {
+        Some(key) => vec![PathBuf::from(key)],
+        None => match home_dir() {
+            Some(home) => vec![home.join(".ssh").join("id_ed25519"), home.join(".ssh").join("id_rsa")],
+            None => vec![],
+        }
+    };
+
+    for key in keys.iter().filter(|key| key.exists()) {
+        if session.userauth_pubkey_file(user, None, key, None).is_ok() && session.authenticated() {
+            return Ok(());
+        }
+    }
+
+    error!("failed to authenticate {} with agent or keys", user);
+    Err(Error::from(ErrorKind::PermissionDenied))
+}
+
+/// Converts sftp file stat to storage entry.
+fn to_entry(path: &Path, stat: &ssh2::FileStat) -> StorageEntry {
+    StorageEntry {
+        path: path.to_string_lossy().replace('\\', "/"),
+        is_dir: stat.is_dir(),
+        size: if stat.is_dir() { 0 } else { stat.size.unwrap_or(0) },
+        modified: stat.mtime.unwrap_or(0),
+    }
+}
+
+impl SftpStorage {
+    /// Connects to server of url.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `url` - Url like `sftp://user@host:22/backups`.
+    pub fn connect(url: &url::Url) -> Result<Self, Error> {
+        use std::net::TcpStream;
+
+        let host = match url.host_str() {
+            Some(host) => host.to_string(),
+            None => return Err(Error::from(ErrorKind::InvalidInput)),
+        };
+        let port = url.port().unwrap_or(22);
+        let user = match url.username() {
+            "" => std::env::var("USER").or(std::env::var("USERNAME")).unwrap_or_default(),
+            name => name.to_string(),
+        };
+        let key = url.query_pairs()
+            .find(|(name, _)| name == "key")
+            .map(|(_, value)| value.to_string());
+
+        let stream = TcpStream::connect((host.as_str(), port))?;
+        let mut session = ssh2::Session::new()?;
+        session.set_tcp_stream(stream);
+        session.handshake()?;
+
+        check_known_host(&session, &host, port)?;
+        authenticate(&session, &user, key)?;
+
+        let sftp = session.sftp()?;
+        Ok(SftpStorage { _session: session, sftp })
+    }
+
+    /// Creates directory and all its parents.
+    fn create_dir_all(&self, remote: &Path) -> Result<(), Error> {
+        let mut current = PathBuf::new();
+        for component in remote.components() {
+            current.push(component);
+            if self.sftp.stat(&current).is_err() {
+                self.sftp.mkdir(&current, 0o755)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Uploads single file.
+    fn put_file(&self, local: &Path, remote: &Path) -> Result<(), Error> {
+        use std::fs::File;
+        use std::io::copy;
+
+        if let Some(parent) = remote.parent() {
+            self.create_dir_all(parent)?;
+        }
+
+        let mut from = File::open(local)?;
+        let mut to = self.sftp.create(remote)?;
+        copy(&mut from, &mut to)?;
+
+        Ok(())
+    }
+
+    /// Downloads single file.
+    fn get_file(&self, remote: &Path, local: &Path) -> Result<(), Error> {
+        use std::fs::{File, create_dir_all};
+        use std::io::copy;
+
+        if let Some(parent) = local.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let mut from = self.sftp.open(remote)?;
+        let mut to = File::create(local)?;
+        copy(&mut from, &mut to)?;
+
+        Ok(())
+    }
+}
+
+impl StorageBackend for SftpStorage {
+    fn put(&self, local: &str, remote: &str) -> Result<(), Error> {
+        use walkdir::WalkDir;
+
+        let local_path = Path::new(local);
+        if local_path.is_file() {
+            return self.put_file(local_path, Path::new(remote));
+        }
+
+        for entry in WalkDir::new(local_path) {
+            let entry = entry?;
+            let relative = entry.path().strip_prefix(local_path).unwrap_or(entry.path());
+            let target = Path::new(remote).join(relative);
+            if entry.file_type().is_dir() {
+                self.create_dir_all(&target)?;
+            } else {
+                self.put_file(entry.path(), &target)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, remote: &str, local: &str) -> Result<(), Error> {
+        use std::fs::create_dir_all;
+
+        let entry = self.stat(remote)?;
+        if !entry.is_dir {
+            return self.get_file(Path::new(remote), Path::new(local));
+        }
+
+        create_dir_all(local)?;
+        for child in self.list(remote)? {
+            let name = Path::new(&child.path).file_name().unwrap_or_default().to_os_string();
+            let target = Path::new(local).join(name).display().to_string();
+            self.get(&child.path, &target)?;
+        }
+
+        Ok(())
+    }
+
+    fn list(&self, remote: &str) -> Result<Vec<StorageEntry>, Error> {
+        let entries = self.sftp.readdir(Path::new(remote))?;
+
+        Ok(entries.iter().map(|(path, stat)| to_entry(path, stat)).collect())
+    }
+
+    fn delete(&self, remote: &str) -> Result<(), Error> {
+        let entry = self.stat(remote)?;
+        if !entry.is_dir {
+            return Ok(self.sftp.unlink(Path::new(remote))?);
+        }
+
+        for child in self.list(remote)? {
+            self.delete(&child.path)?;
+        }
+        self.sftp.rmdir(Path::new(remote))?;
+
+        Ok(())
+    }
+
+    fn rename(&self, from: &str, to: &str) -> Result<(), Error> {
+        if let Some(parent) = Path::new(to).parent() {
+            self.create_dir_all(parent)?;
+        }
+
+        Ok(self.sftp.rename(Path::new(from), Path::new(to), None)?)
+    }
+
+    fn stat(&self, remote: &str) -> Result<StorageEntry, Error> {
+        let stat = self.sftp.stat(Path::new(remote))?;
+
+        Ok(to_entry(Path::new(remote), &stat))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    /// Runs against a local ssh server, e.g. `MISSION_BACKUP_TEST_SFTP=sftp://robin@127.0.0.1:22/tmp/mission_backup`.
+    #[test]
+    #[ignore]
+    fn test_sftp_storage() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, write, read_to_string, remove_dir_all};
+
+        let location = std::env::var("MISSION_BACKUP_TEST_SFTP").expect("sftp location not set");
+        let (storage, path) = crate::storage::open_storage(&location).unwrap();
+
+        let test_path = current_dir().expect("").join("test_sftp_storage");
+        create_dir_all(test_path.join("from").join("sub")).unwrap();
+        write(test_path.join("from").join("sub").join("save.txt"), "Hello world!").unwrap();
+
+        let remote = format!("{}/from", path.trim_end_matches('/'));
+        storage.put(&test_path.join("from").display().to_string(), &remote).unwrap();
+        assert_eq!(storage.size(&remote).unwrap(), 12);
+        assert_eq!(storage.list(&remote).unwrap().len(), 1);
+
+        storage.get(&remote, &test_path.join("to").display().to_string()).unwrap();
+        assert_eq!(read_to_string(test_path.join("to").join("sub").join("save.txt")).unwrap(), "Hello world!");
+
+        storage.delete(&remote).unwrap();
+        assert_eq!(storage.exists(&remote), false);
+
+        let _ = remove_dir_all(test_path).unwrap();
+    }
+}
@@ -0,0 +1,527 @@
+//! # WebDav
+//! 
+//! `webdav` module contains backend of WebDAV server, like Nextcloud.
+//! 
+//! Location is like `webdavs://robin@cloud.example.com/remote.php/dav/files/robin/backups`,
+//! `webdav://` is for plain http. Password is saved in system keyring by [save_credential],
+//! and falls back to `MISSION_BACKUP_WEBDAV_PASSWORD` environment variable.
+//! 
+//! Large files to Nextcloud are uploaded in chunks, uploaded chunks are kept on failure
+//! so the next upload of the same file resumes from them. Uploads not resumed for a week are removed.
+
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use log::error;
+use super::{StorageBackend, StorageEntry, percent_decode, uri_encode};
+
+/// Size of each chunk in chunked upload, files not larger than it are uploaded at once.
+const CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Seconds after which chunked upload not resumed is abandoned, and removed by the next chunked upload.
+const UPLOAD_EXPIRY: u64 = 7 * 24 * 60 * 60;
+
+/// Properties requested in `PROPFIND`.
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:resourcetype/>
+    <d:getcontentlength/>
+    <d:getlastmodified/>
+  </d:prop>
+</d:propfind>"#;
+
+/// Struct WebDavStorage
+pub struct WebDavStorage {
+    /// Scheme, host and port of server
+    base: String,
+
+    /// Value of `Authorization` header
+    authorization: String,
+
+    /// Collection for chunked upload, only for Nextcloud
+    uploads: Option<String>,
+
+    /// Http agent
+    agent: ureq::Agent,
+}
+
+#[allow(dead_code)]
+/// Gets key of location credential in system keyring.
+/// 
+/// # Arguments
+/// 
+/// * `location` - Url like `webdavs://robin@cloud.example.com/remote.php/dav/files/robin`.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use storage::webdav::credential_key;
+/// 
+/// assert_eq!(credential_key("webdavs://robin@cloud.example.com/backups").unwrap(), "webdav:robin@cloud.example.com".to_string());
+/// ```
+pub fn credential_key(location: &str) -> Result<String, Error> {
+    let url = url::Url::parse(location).map_err(|_| Error::from(ErrorKind::InvalidInput))?;
+
+    match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => Ok(format!("webdav:{}@{}:{}", percent_decode(url.username()), host, port)),
+        (Some(host), None) => Ok(format!("webdav:{}@{}", percent_decode(url.username()), host)),
+        _ => Err(Error::from(ErrorKind::InvalidInput)),
+    }
+}
+
+#[allow(dead_code)]
+/// Saves password of location into system keyring.
+/// 
+/// # Arguments
+/// 
+/// * `location` - Url like `webdavs://robin@cloud.example.com/remote.php/dav/files/robin`.
+/// * `password` - Password or app password of user.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use storage::webdav::save_credential;
+/// 
+/// save_credential("webdavs://robin@cloud.example.com/backups", "app-password").unwrap();
+/// ```
+pub fn save_credential(location: &str, password: &str) -> Result<(), Error> {
+    use crate::utils::secret::set_secret;
+
+    set_secret(&credential_key(location)?, password)
+}
+
+/// Gets password of location from system keyring or environment variable.
+fn load_credential(location: &str) -> Result<String, Error> {
+    use crate::utils::secret::get_secret;
+
+    if let Ok(password) = get_secret(&credential_key(location)?) {
+        return Ok(password);
+    }
+
+    match std::env::var("MISSION_BACKUP_WEBDAV_PASSWORD") {
+        Ok(password) => Ok(password),
+        Err(_) => {
+            error!("no credential found for {}", location);
+            Err(Error::from(ErrorKind::PermissionDenied))
+        }
+    }
+}
+
+/// Gets collection for chunked upload if path is inside Nextcloud files.
+fn chunked_uploads(path: &str) -> Option<String> {
+    let idx = path.find("/remote.php/dav/files/")?;
+    let user = path[idx + "/remote.php/dav/files/".len()..].split('/').next()?;
+
+    match user.is_empty() {
+        true => None,
+        false => Some(format!("{}/remote.php/dav/uploads/{}", &path[..idx], user)),
+    }
+}
+
+/// Gets text of first descendant element.
+fn descendant_text(node: &roxmltree::Node, name: &str) -> Option<String> {
+    node.descendants()
+        .find(|child| child.is_element() && child.tag_name().name() == name)
+        .and_then(|child| child.text())
+        .map(|text| text.trim().to_string())
+}
+
+/// Parses entries from `PROPFIND` multistatus response.
+fn parse_multistatus(text: &str) -> Result<Vec<StorageEntry>, Error> {
+    let doc = roxmltree::Document::parse(text).map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+    let mut entries = Vec::new();
+    for response in doc.descendants().filter(|node| node.is_element() && node.tag_name().name() == "response") {
+        let href = descendant_text(&response, "href").unwrap_or_default();
+        let href = match url::Url::parse(&href) {
+            Ok(url) => url.path().to_string(),
+            Err(_) => href,
+        };
+        let path = match percent_decode(&href).trim_end_matches('/') {
+            "" => "/".to_string(),
+            path => path.to_string(),
+        };
+        let is_dir = response.descendants().any(|node| node.is_element() && node.tag_name().name() == "collection");
+
+        entries.push(StorageEntry {
+            path,
+            is_dir,
+            size: match is_dir {
+                true => 0,
+                false => descendant_text(&response, "getcontentlength").and_then(|size| size.parse().ok()).unwrap_or(0),
+            },
+            modified: descendant_text(&response, "getlastmodified")
+                .and_then(|time| chrono::DateTime::parse_from_rfc2822(&time).ok())
+                .map_or(0, |time| time.timestamp().max(0) as u64),
+        });
+    }
+
+    Ok(entries)
+}
+
+impl WebDavStorage {
+    /// Prepares connection to server of url.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `url` - Url like `webdavs://robin@cloud.example.com/remote.php/dav/files/robin`.
+    pub fn connect(url: &url::Url) -> Result<Self, Error> {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+        let scheme = match url.scheme() {
+            "webdavs" => "https",
+            _ => "http",
+        };
+        let base = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}://{}:{}", scheme, host, port),
+            (Some(host), None) => format!("{}://{}", scheme, host),
+            _ => return Err(Error::from(ErrorKind::InvalidInput)),
+        };
+        let password = load_credential(url.as_str())?;
+        let authorization = format!("Basic {}", STANDARD.encode(format!("{}:{}", percent_decode(url.username()), password)));
+
+        Ok(WebDavStorage {
+            base,
+            authorization,
+            uploads: chunked_uploads(&super::url_path(url)),
+            agent: ureq::AgentBuilder::new().build(),
+        })
+    }
+
+    /// Gets url of path in server.
+    fn href(&self, path: &str) -> String {
+        format!("{}{}", self.base, uri_encode(path, false))
+    }
+
+    /// Builds request with authorization.
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        self.agent.request(method, &self.href(path)).set("Authorization", &self.authorization)
+    }
+
+    /// Converts response of request into io result.
+    fn check(&self, response: Result<ureq::Response, ureq::Error>, method: &str, path: &str) -> Result<ureq::Response, Error> {
+        match response {
+            Ok(response) => Ok(response),
+            Err(ureq::Error::Status(404, _)) => Err(Error::from(ErrorKind::NotFound)),
+            Err(ureq::Error::Status(405, _)) if method == "MKCOL" => Err(Error::from(ErrorKind::AlreadyExists)),
+            Err(ureq::Error::Status(code, _)) if code == 401 || code == 403 => {
+                error!("webdav request {} {} denied", method, path);
+                Err(Error::from(ErrorKind::PermissionDenied))
+            },
+            Err(ureq::Error::Status(code, response)) => {
+                let msg = response.into_string().unwrap_or_default();
+                error!("webdav request {} {} failed with {}, errMsg: {}", method, path, code, msg);
+                Err(Error::other(format!("webdav status {}", code)))
+            },
+            Err(error) => {
+                error!("webdav request {} {} failed, errMsg: {:?}", method, path, error);
+                Err(Error::other(format!("{}", error)))
+            }
+        }
+    }
+
+    /// Gets properties of path and its children if `depth` is `1`.
+    fn propfind(&self, path: &str, depth: &str) -> Result<Vec<StorageEntry>, Error> {
+        let response = self.request("PROPFIND", path)
+            .set("Depth", depth)
+            .set("Content-Type", "application/xml; charset=utf-8")
+            .send_string(PROPFIND_BODY);
+        let text = self.check(response, "PROPFIND", path)?.into_string()?;
+
+        parse_multistatus(&text)
+    }
+
+    /// Creates collection and all its parents.
+    fn create_dir_all(&self, path: &str) -> Result<(), Error> {
+        let path = path.trim_end_matches('/');
+        if path.is_empty() || self.propfind(path, "0").is_ok() {
+            return Ok(());
+        }
+
+        if let Some((parent, _)) = path.rsplit_once('/') {
+            self.create_dir_all(parent)?;
+        }
+        match self.check(self.request("MKCOL", path).call(), "MKCOL", path) {
+            Err(error) if error.kind() != ErrorKind::AlreadyExists => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    /// Uploads single file, large file to Nextcloud is uploaded in chunks.
+    fn put_file(&self, local: &Path, remote: &str) -> Result<(), Error> {
+        use std::fs::File;
+
+        let file = File::open(local)?;
+        let size = file.metadata()?.len();
+        if let (Some(uploads), true) = (&self.uploads, size > CHUNK_SIZE) {
+            return self.put_chunked(file, size, remote, uploads);
+        }
+
+        let response = self.request("PUT", remote)
+            .set("Content-Length", &size.to_string())
+            .send(file);
+        self.check(response, "PUT", remote)?;
+
+        Ok(())
+    }
+
+    /// Uploads file in chunks with Nextcloud chunking, chunks already uploaded are skipped.
+    fn put_chunked(&self, mut file: std::fs::File, size: u64, remote: &str, uploads: &str) -> Result<(), Error> {
+        use std::collections::HashMap;
+        use std::io::{Read, Seek, SeekFrom};
+        use sha2::{Digest, Sha256};
+
+        // same file to same path shares the upload, so it resumes after failure
+        let modified = file.metadata()?.modified().ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_secs());
+        let upload_id = hex::encode(Sha256::digest(format!("{}:{}:{}", remote, size, modified).as_bytes()));
+        let upload_dir = format!("{}/mission-backup-{}", uploads, &upload_id[..16]);
+        let destination = self.href(remote);
+
+        let uploaded: HashMap<String, u64> = match self.propfind(&upload_dir, "1") {
+            Ok(entries) => entries.into_iter()
+                .filter(|entry| !entry.is_dir)
+                .filter_map(|entry| Path::new(&entry.path).file_name().map(|name| (name.to_string_lossy().to_string(), entry.size)))
+                .collect(),
+            Err(_) => {
+                let response = self.request("MKCOL", &upload_dir).set("Destination", &destination).call();
+                self.check(response, "MKCOL", &upload_dir)?;
+                HashMap::new()
+            }
+        };
+
+        let count = size.div_ceil(CHUNK_SIZE);
+        for idx in 0..count {
+            let name = format!("{:05}", idx + 1);
+            let offset = idx * CHUNK_SIZE;
+            let length = CHUNK_SIZE.min(size - offset);
+            if uploaded.get(&name) == Some(&length) {
+                continue;
+            }
+
+            file.seek(SeekFrom::Start(offset))?;
+            let chunk_path = format!("{}/{}", upload_dir, name);
+            let response = self.request("PUT", &chunk_path)
+                .set("Destination", &destination)
+                .set("OC-Total-Length", &size.to_string())
+                .set("Content-Length", &length.to_string())
+                .send((&mut file).take(length));
+            self.check(response, "PUT", &chunk_path)?;
+        }
+
+        let assembled = format!("{}/.file", upload_dir);
+        let response = self.request("MOVE", &assembled)
+            .set("Destination", &destination)
+            .set("OC-Total-Length", &size.to_string())
+            .set("Overwrite", "T")
+            .call();
+        self.check(response, "MOVE", &assembled)?;
+        self.clean_uploads(uploads, &upload_dir);
+
+        Ok(())
+    }
+
+    /// Removes chunked uploads of this app not resumed for [UPLOAD_EXPIRY], except `current`.
+    fn clean_uploads(&self, uploads: &str, current: &str) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let entries = match self.propfind(uploads, "1") {
+            Ok(entries) => entries,
+            Err(error) => {
+                error!("failed to list uploads in {}, errMsg: {:?}", uploads, error);
+                return;
+            }
+        };
+
+        for entry in entries.into_iter().filter(|entry| entry.is_dir) {
+            let name = match Path::new(entry.path.trim_end_matches('/')).file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => continue,
+            };
+            let upload_dir = format!("{}/{}", uploads, name);
+            if !name.starts_with("mission-backup-") || upload_dir == current {
+                continue;
+            }
+            if entry.modified == 0 || now.saturating_sub(entry.modified) < UPLOAD_EXPIRY {
+                continue;
+            }
+            if let Err(error) = self.check(self.request("DELETE", &upload_dir).call(), "DELETE", &upload_dir) {
+                error!("failed to remove abandoned upload {}, errMsg: {:?}", upload_dir, error);
+            }
+        }
+    }
+
+    /// Downloads single file.
+    fn get_file(&self, remote: &str, local: &Path) -> Result<(), Error> {
+        use std::fs::{File, create_dir_all};
+        use std::io::copy;
+
+        let response = self.check(self.request("GET", remote).call(), "GET", remote)?;
+        if let Some(parent) = local.parent() {
+            create_dir_all(parent)?;
+        }
+        let mut to = File::create(local)?;
+        copy(&mut response.into_reader(), &mut to)?;
+
+        Ok(())
+    }
+}
+
+impl StorageBackend for WebDavStorage {
+    fn put(&self, local: &str, remote: &str) -> Result<(), Error> {
+        use walkdir::WalkDir;
+
+        let local_path = Path::new(local);
+        if let Some((parent, _)) = remote.trim_end_matches('/').rsplit_once('/') {
+            self.create_dir_all(parent)?;
+        }
+        if local_path.is_file() {
+            return self.put_file(local_path, remote);
+        }
+
+        for entry in WalkDir::new(local_path) {
+            let entry = entry?;
+            let relative = entry.path().strip_prefix(local_path).unwrap_or(entry.path());
+            let relative = relative.components()
+                .map(|component| component.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<String>>()
+                .join("/");
+            let target = self.join(remote, &relative);
+            if entry.file_type().is_dir() {
+                self.create_dir_all(&target)?;
+            } else {
+                self.put_file(entry.path(), &target)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, remote: &str, local: &str) -> Result<(), Error> {
+        use std::fs::create_dir_all;
+
+        let entry = self.stat(remote)?;
+        if !entry.is_dir {
+            return self.get_file(remote, Path::new(local));
+        }
+
+        create_dir_all(local)?;
+        for child in self.list(remote)? {
+            let name = Path::new(&child.path).file_name().unwrap_or_default().to_os_string();
+            let target = Path::new(local).join(name).display().to_string();
+            self.get(&child.path, &target)?;
+        }
+
+        Ok(())
+    }
+
+    fn list(&self, remote: &str) -> Result<Vec<StorageEntry>, Error> {
+        let current = remote.trim_end_matches('/');
+
+        Ok(self.propfind(remote, "1")?
+            .into_iter()
+            .filter(|entry| entry.path.trim_end_matches('/') != current)
+            .collect())
+    }
+
+    fn delete(&self, remote: &str) -> Result<(), Error> {
+        self.check(self.request("DELETE", remote).call(), "DELETE", remote)?;
+
+        Ok(())
+    }
+
+    fn rename(&self, from: &str, to: &str) -> Result<(), Error> {
+        if let Some((parent, _)) = to.trim_end_matches('/').rsplit_once('/') {
+            self.create_dir_all(parent)?;
+        }
+
+        let response = self.request("MOVE", from)
+            .set("Destination", &self.href(to))
+            .set("Overwrite", "T")
+            .call();
+        self.check(response, "MOVE", from)?;
+
+        Ok(())
+    }
+
+    fn stat(&self, remote: &str) -> Result<StorageEntry, Error> {
+        match self.propfind(remote, "0")?.into_iter().next() {
+            Some(entry) => Ok(entry),
+            None => Err(Error::from(ErrorKind::NotFound)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_multistatus() {
+        let text = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+  <d:response>
+    <d:href>/remote.php/dav/files/robin/my%20backups/</d:href>
+    <d:propstat>
+      <d:prop><d:resourcetype><d:collection/></d:resourcetype><d:getlastmodified>Sat, 06 Jul 2024 09:00:00 GMT</d:getlastmodified></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+    <d:propstat>
+      <d:prop><d:getcontentlength/></d:prop>
+      <d:status>HTTP/1.1 404 Not Found</d:status>
+    </d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>https://cloud.example.com/remote.php/dav/files/robin/my%20backups/save.zip</d:href>
+    <d:propstat>
+      <d:prop><d:resourcetype/><d:getcontentlength>12</d:getcontentlength><d:getlastmodified>Sat, 06 Jul 2024 09:00:01 GMT</d:getlastmodified></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+        let entries = parse_multistatus(text).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, "/remote.php/dav/files/robin/my backups".to_string());
+        assert_eq!(entries[0].is_dir, true);
+        assert_eq!(entries[1].path, "/remote.php/dav/files/robin/my backups/save.zip".to_string());
+        assert_eq!(entries[1].size, 12);
+        assert_eq!(entries[1].modified, 1720256401);
+
+        assert_eq!(chunked_uploads("/remote.php/dav/files/robin/backups"), Some("/remote.php/dav/uploads/robin".to_string()));
+        assert_eq!(chunked_uploads("/dav/backups"), None);
+    }
+
+    /// Runs against a local WebDAV server, e.g. `MISSION_BACKUP_TEST_WEBDAV=webdav://robin@127.0.0.1:8080/remote.php/dav/files/robin/test`
+    /// with `MISSION_BACKUP_WEBDAV_PASSWORD` set.
+    #[test]
+    #[ignore]
+    fn test_webdav_storage() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, write, read_to_string, remove_dir_all};
+
+        let location = std::env::var("MISSION_BACKUP_TEST_WEBDAV").expect("webdav location not set");
+        let (storage, path) = crate::storage::open_storage(&location).unwrap();
+
+        let test_path = current_dir().expect("").join("test_webdav_storage");
+        create_dir_all(test_path.join("from").join("sub")).unwrap();
+        write(test_path.join("from").join("sub").join("save.txt"), "Hello world!").unwrap();
+        write(test_path.join("from").join("large.bin"), vec![7u8; CHUNK_SIZE as usize + 1024]).unwrap();
+
+        let remote = storage.join(&path, "from");
+        storage.put(&test_path.join("from").display().to_string(), &remote).unwrap();
+        assert_eq!(storage.is_available(&path), true);
+        assert_eq!(storage.size(&remote).unwrap(), CHUNK_SIZE + 1024 + 12);
+        assert_eq!(storage.list(&remote).unwrap().len(), 2);
+
+        storage.get(&remote, &test_path.join("to").display().to_string()).unwrap();
+        assert_eq!(read_to_string(test_path.join("to").join("sub").join("save.txt")).unwrap(), "Hello world!");
+
+        storage.delete(&remote).unwrap();
+        assert_eq!(storage.exists(&remote), false);
+
+        let _ = remove_dir_all(test_path).unwrap();
+    }
+}
@@ -1,5 +1,62 @@
 //! The `compressor` module is able to create archieves for file or directory.
-//! The following compression formats are supported: zip, tar.gz, tar.bz2, tar.xz, 7z.
+//! The following compression formats are supported: zip, tar.gz, tar.bz2, tar.xz, 7z, tar.zst, tar.lz4.
+//! Zip and tar archives keep modification time, permissions and symbolic links, tar archives
+//! also keep ownership and extended attributes, 7z archives store targets of symbolic links. Archives can be split into volumes of fixed size.
+
+/// Options for creating archive.
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveOptions {
+    /// Bytes per second written to archive, `0` means no limit
+    pub write_limit: u64,
+
+    /// Priority of compression thread, see [super::throttle::lower_thread_priority]
+    pub priority: i16,
+
+    /// Compression level, `None` uses default of the format
+    /// 
+    /// Levels out of range are clamped, `1` to `9` for deflate and bzip2 of zip and bzip2 of tar,
+    /// `0` to `9` for gzip, xz and 7z, `1` to `22` for zstd, lz4 has no levels.
+    pub level: Option<u32>,
+
+    /// Compression method of zip archive
+    /// 
+    /// `0` - deflate
+    /// 
+    /// `1` - bzip2
+    /// 
+    /// `2` - zstd
+    /// 
+    /// `3` - store without compression
+    pub method: i16,
+
+    /// Dictionary size in bytes for xz and 7z, `0` uses size of the level
+    pub dict_size: u32,
+
+    /// Threads compressing xz archive, `0` and `1` compress in single thread
+    pub threads: u32,
+
+    /// Password of zip and 7z archive, encrypted with AES-256, other formats are not encrypted
+    pub password: Option<String>,
+}
+
+impl ArchiveOptions {
+    /// Gets zip compression method and level supported by the method.
+    fn zip_method(&self) -> (zip::CompressionMethod, Option<i64>) {
+        use zip::CompressionMethod;
+
+        match self.method {
+            1 => (CompressionMethod::Bzip2, self.level.map(|level| level.clamp(1, 9) as i64)),
+            2 => (CompressionMethod::Zstd, self.level.map(|level| level.clamp(1, 22) as i64)),
+            3 => (CompressionMethod::Stored, None),
+            _ => (CompressionMethod::Deflated, self.level.map(|level| level.clamp(1, 9) as i64)),
+        }
+    }
+
+    /// Gets preset of lzma based formats, `6` by default.
+    fn lzma_preset(&self) -> u32 {
+        self.level.unwrap_or(6).min(9)
+    }
+}
 
 #[allow(dead_code)]
 /// Prepares packing for `from` path and `to path.
@@ -28,7 +85,8 @@
 fn prepare_packing(from: &str, to: &str) -> Result<String, std::io::Error> {
     use std::io::{Error, ErrorKind};
     use std::path::Path;
-    use std::fs::{File, copy, create_dir_all};
+    use std::fs::{File, create_dir_all};
+    use super::explorer::copy_file;
 
     let src_path = Path::new(from);
     let save_path = Path::new(to);
@@ -55,7 +113,7 @@ fn prepare_packing(from: &str, to: &str) -> Result<String, std::io::Error> {
             match file_name {
                 Some(name) => {
                     create_dir_all(packing_dir)?;
-                    copy(from, packing_dir.join(name.to_str().unwrap()))?;             
+                    copy_file(from, packing_dir.join(name.to_str().unwrap()))?;
                     return Ok(packing_dir.display().to_string());
                 },
                 None => {
@@ -101,6 +159,9 @@ fn finish_packing(from: &str, to: &str) -> Result<(), std::io::Error> {
     Ok(())
 }
 
+/// Size from which zip entries are written with zip64 extra fields.
+const ZIP64_THRESHOLD: u64 = 0xFFFFFFFF;
+
 #[allow(dead_code)]
 /// Creates zip archive for directory.
 /// 
@@ -110,6 +171,8 @@ fn finish_packing(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// * `prefix` - A string slice that hold the directory path
 /// * `writer` - Write target that stores the archive data
 /// * `method` - The zip compression method
+/// * `level` - The compression level, `None` for default level of method
+/// * `password` - Password to encrypt files with AES-256, `None` for no encryption
 /// 
 /// # Examples
 /// 
@@ -124,7 +187,7 @@ fn finish_packing(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// let walkdir = walkdir::WalkDir::new(dir);
 /// let iter = walkdir.into_iter();
 /// let saver = OpenOptions::new().write(true).open(save_dir)?;
-/// if let Err(_) = zip_dir(&mut iter.filter_map(|e| e.ok()), &dir, saver, zip::CompressionMethod::Deflated) {
+/// if let Err(_) = zip_dir(&mut iter.filter_map(|e| e.ok()), &dir, saver, zip::CompressionMethod::Deflated, None, None) {
 ///     return Err(Error::from(ErrorKind::Other));
 /// }
 /// ```
@@ -133,34 +196,54 @@ fn zip_dir<T>(
     prefix: &str,
     writer: T,
     method: zip::CompressionMethod,
+    level: Option<i64>,
+    password: Option<&str>,
 ) -> zip::result::ZipResult<()>
 where
     T: std::io::Write + std::io::Seek,
 {
-    use std::io::{Read, Write};
+    use std::io::copy;
     use std::path::Path;
-    use std::fs::File;
+    use std::fs::{File, read_link};
     let mut zip = zip::ZipWriter::new(writer);
-    let options = zip::write::FileOptions::default()
+    let default_options = zip::write::SimpleFileOptions::default()
         .compression_method(method)
+        .compression_level(level)
         .unix_permissions(0o755);
+    let default_options = match password {
+        Some(password) => default_options.with_aes_encryption(zip::AesMode::Aes256, password),
+        None => default_options,
+    };
 
-    let mut buffer = Vec::new();
     for entry in it {
         let path = entry.path();
         let name = path.strip_prefix(Path::new(prefix)).unwrap();
+        let meta = entry.metadata().map_err(std::io::Error::from)?;
+
+        let mut options = default_options;
+        if let Some(modified) = meta.modified().ok().and_then(to_zip_time) {
+            options = options.last_modified_time(modified);
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            options = options.unix_permissions(meta.permissions().mode());
+        }
 
         // Write file or directory explicitly
         // Some unzip tools unzip files with directory paths correctly, some do not!
-        if path.is_file() {
+        if meta.file_type().is_symlink() {
+            let target = read_link(path)?;
+            let name = name.to_string_lossy().replace('\\', "/");
+            zip.add_symlink(name, target.to_string_lossy(), options)?;
+        } else if meta.is_file() {
             // info!("adding file {:?} as {:?} ...", path, name);
+            // Entries of 4 GiB and above need zip64 extra fields declared up front
+            let options = options.large_file(meta.len() >= ZIP64_THRESHOLD);
             #[allow(deprecated)]
             zip.start_file_from_path(name, options)?;
             let mut f = File::open(path)?;
-
-            f.read_to_end(&mut buffer)?;
-            zip.write_all(&*buffer)?;
-            buffer.clear();
+            copy(&mut f, &mut zip)?;
         } else if !name.as_os_str().is_empty() {
             // Only if not root! Avoids path spec / warning
             // and mapname conversion failed error on unzip
@@ -173,6 +256,300 @@ where
     Result::Ok(())
 }    
 
+#[allow(dead_code)]
+/// Converts system time to zip time in local timezone, as zip tools expect.
+/// 
+/// Returns `None` if time is out of zip time range, 1980 to 2107.
+/// 
+/// # Arguments
+/// 
+/// * `time` - System time to convert
+fn to_zip_time(time: std::time::SystemTime) -> Option<zip::DateTime> {
+    use chrono::{DateTime, Datelike, Local, Timelike};
+
+    let local = DateTime::<Local>::from(time);
+    zip::DateTime::from_date_and_time(
+        u16::try_from(local.year()).ok()?,
+        local.month() as u8,
+        local.day() as u8,
+        local.hour() as u8,
+        local.minute() as u8,
+        local.second() as u8,
+    ).ok()
+}
+
+#[allow(dead_code)]
+/// Converts zip time in local timezone to system time.
+/// 
+/// # Arguments
+/// 
+/// * `time` - Zip time to convert
+fn from_zip_time(time: zip::DateTime) -> Option<std::time::SystemTime> {
+    use chrono::{Local, TimeZone};
+
+    Local.with_ymd_and_hms(
+        time.year() as i32,
+        time.month() as u32,
+        time.day() as u32,
+        time.hour() as u32,
+        time.minute() as u32,
+        time.second() as u32,
+    ).earliest().map(std::time::SystemTime::from)
+}
+
+#[allow(dead_code)]
+/// Restores permissions and modification time of extracted item.
+/// 
+/// Permissions are only restored on Unix.
+/// 
+/// # Arguments
+/// 
+/// * `path` - Path of extracted item
+/// * `mode` - Unix mode recorded in archive
+/// * `modified` - Modification time recorded in archive
+fn restore_metadata(path: &std::path::Path, mode: Option<u32>, modified: Option<std::time::SystemTime>) -> Result<(), std::io::Error> {
+    use super::metadata::set_times;
+
+    #[cfg(unix)]
+    if let Some(mode) = mode {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode & 0o7777))?;
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+
+    if let Some(modified) = modified {
+        set_times(path, modified, modified)?;
+    }
+
+    Ok(())
+}
+
+#[allow(dead_code)]
+/// Extracts zip archive into directory, restoring modification time, permissions and symbolic links.
+/// 
+/// Entries with unsafe paths are skipped, encrypted entries fail with `PermissionDenied`
+/// if password is missing or wrong.
+/// 
+/// # Arguments
+/// 
+/// * `from` - A string slice that holds the archive path
+/// * `to` - A string slice that holds the directory to extract into
+/// * `password` - Password of encrypted entries
+/// 
+/// # Examples
+/// 
+/// ```
+/// use compress::compressor::extract_zip_archive;
+/// 
+/// let _ = extract_zip_archive("path\\for\\save.zip", "path\\for\\extract", None)?;
+/// ```
+fn extract_zip_archive(from: &str, to: &str, password: Option<&str>) -> Result<(), std::io::Error> {
+    use std::io::{Error, ErrorKind};
+    use zip::result::ZipError;
+    use std::path::Path;
+    use std::fs::{File, create_dir_all};
+    use super::metadata::set_times;
+
+    let mut archive = zip::ZipArchive::new(File::open(from)?)
+        .map_err(|_| Error::from(ErrorKind::InvalidData))?;
+    create_dir_all(to)?;
+    let root = Path::new(to).canonicalize()?;
+
+    // Directory times are restored last, after its items are written
+    let mut dirs = Vec::new();
+    // Symbolic links are created after all files, so no entry is written through them
+    #[cfg(unix)]
+    let mut links = Vec::new();
+    for idx in 0..archive.len() {
+        let file = match password {
+            Some(password) => archive.by_index_decrypt(idx, password.as_bytes()),
+            None => archive.by_index(idx),
+        };
+        let mut file = file.map_err(|error| match error {
+            ZipError::InvalidPassword | ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) => Error::from(ErrorKind::PermissionDenied),
+            _ => Error::from(ErrorKind::InvalidData),
+        })?;
+        let save = match file.enclosed_name() {
+            Some(name) => Path::new(to).join(name),
+            None => continue,
+        };
+        let mode = file.unix_mode();
+        let modified = file.last_modified().and_then(from_zip_time);
+
+        if file.is_dir() {
+            check_enclosed(&root, &save)?;
+            create_dir_all(&save)?;
+            dirs.push((save, mode, modified));
+            continue;
+        }
+        if let Some(parent) = save.parent() {
+            check_enclosed(&root, parent)?;
+            create_dir_all(parent)?;
+        }
+
+        #[cfg(unix)]
+        if mode.is_some_and(|mode| mode & 0o170000 == 0o120000) {
+            use std::io::Read;
+
+            let mut target = String::new();
+            file.read_to_string(&mut target)?;
+            links.push((save, target, modified));
+            continue;
+        }
+
+        // existing link is replaced, not written through
+        if save.symlink_metadata().is_ok_and(|meta| meta.file_type().is_symlink()) {
+            std::fs::remove_file(&save)?;
+        }
+        let mut save_file = File::create(&save)?;
+        std::io::copy(&mut file, &mut save_file)?;
+        drop(save_file);
+        restore_metadata(&save, mode, modified)?;
+    }
+
+    #[cfg(unix)]
+    for (save, target, modified) in links.iter() {
+        if let Some(parent) = save.parent() {
+            check_enclosed(&root, parent)?;
+        }
+        if save.symlink_metadata().is_ok() {
+            std::fs::remove_file(save)?;
+        }
+        std::os::unix::fs::symlink(target, save)?;
+        if let Some(modified) = modified {
+            set_times(save, *modified, *modified)?;
+        }
+    }
+
+    for (dir, mode, modified) in dirs.iter().rev() {
+        restore_metadata(dir, *mode, *modified)?;
+    }
+
+    Ok(())
+}
+
+/// Refuses path of extracted item if it resolves out of `root` through symbolic links.
+/// 
+/// `root` should be canonicalized, path not created yet is checked by its nearest existing ancestor.
+fn check_enclosed(root: &std::path::Path, path: &std::path::Path) -> Result<(), std::io::Error> {
+    use std::io::{Error, ErrorKind};
+
+    let mut existing = path;
+    while existing.symlink_metadata().is_err() {
+        match existing.parent() {
+            Some(parent) => existing = parent,
+            None => return Ok(()),
+        }
+    }
+
+    match existing.canonicalize() {
+        Ok(resolved) if resolved.starts_with(root) => Ok(()),
+        _ => Err(Error::new(ErrorKind::InvalidData, format!("{} is out of extract directory", path.display()))),
+    }
+}
+
+#[allow(dead_code)]
+/// Appends items of directory to tar archive, keeping metadata.
+/// 
+/// Symbolic links are stored as links, extended attributes are stored as pax extensions.
+/// 
+/// # Arguments
+/// 
+/// * `builder` - Tar archive builder
+/// * `from` - A string slice that holds the directory path
+/// 
+/// # Examples
+/// 
+/// ```
+/// use compress::compressor::append_tar_entries;
+/// 
+/// let mut builder = tar::Builder::new(std::fs::File::create("path\\for\\save.tar")?);
+/// append_tar_entries(&mut builder, "path\\for\\target")?;
+/// ```
+fn append_tar_entries<W: std::io::Write>(builder: &mut tar::Builder<W>, from: &str) -> Result<(), std::io::Error> {
+    use std::io::Error;
+    use walkdir::WalkDir;
+    use super::metadata::read_xattrs;
+
+    builder.mode(tar::HeaderMode::Complete);
+    builder.follow_symlinks(false);
+
+    for entry in WalkDir::new(from).min_depth(1).sort_by_file_name() {
+        let entry = entry.map_err(Error::from)?;
+        let name = entry.path().strip_prefix(from).map_err(Error::other)?;
+
+        let xattrs: Vec<(String, Vec<u8>)> = read_xattrs(entry.path())
+            .into_iter()
+            .map(|(key, value)| (format!("SCHILY.xattr.{}", key), value))
+            .collect();
+        builder.append_pax_extensions(xattrs.iter().map(|(key, value)| (key.as_str(), value.as_slice())))?;
+
+        builder.append_path_with_name(entry.path(), name)?;
+    }
+
+    builder.finish()
+}
+
+#[allow(dead_code)]
+/// Unpacks tar archive into directory, restoring metadata.
+/// 
+/// Ownership is restored only when privileged.
+/// 
+/// # Arguments
+/// 
+/// * `reader` - Reader of tar archive data
+/// * `to` - A string slice that holds the directory to extract into
+/// 
+/// # Examples
+/// 
+/// ```
+/// use compress::compressor::unpack_tar;
+/// 
+/// unpack_tar(std::fs::File::open("path\\for\\save.tar")?, "path\\for\\extract")?;
+/// ```
+fn unpack_tar<R: std::io::Read>(reader: R, to: &str) -> Result<(), std::io::Error> {
+    use std::path::Path;
+    use std::time::{Duration, UNIX_EPOCH};
+    use super::metadata::is_privileged;
+
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_permissions(true);
+    archive.set_preserve_mtime(true);
+    archive.set_unpack_xattrs(true);
+    archive.set_preserve_ownerships(is_privileged());
+
+    // Directory times are restored last, after its items are written
+    let mut dirs = Vec::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let is_dir = entry.header().entry_type().is_dir();
+        let save = Path::new(to).join(entry.path()?);
+        let mode = entry.header().mode().ok();
+        let modified = entry.header().mtime().ok().map(|mtime| UNIX_EPOCH + Duration::from_secs(mtime));
+
+        if !entry.unpack_in(to)? || !is_dir {
+            continue;
+        }
+
+        // Keep directory writable until its items are written
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut permissions = save.metadata()?.permissions();
+            permissions.set_mode(permissions.mode() | 0o700);
+            std::fs::set_permissions(&save, permissions)?;
+        }
+        dirs.push((save, mode, modified));
+    }
+
+    for (dir, mode, modified) in dirs.iter().rev() {
+        restore_metadata(dir, *mode, *modified)?;
+    }
+
+    Ok(())
+}
+
 #[allow(dead_code)]
 /// Creates zip archive for file or directory.
 /// 
@@ -180,6 +557,7 @@ where
 /// 
 /// * `from` - A string slice that holds the source path
 /// * `to` - A string slice that holds the save path
+/// * `options` - Compression and throttle of archive writing
 /// 
 /// # Examples
 /// 
@@ -189,19 +567,22 @@ where
 /// let src = "path\\for\\target";
 /// let dst = "path\\for\\save.zip";
 /// 
-/// let _ = create_zip_archive(src, dst)?;
+/// let _ = create_zip_archive(src, dst, &ArchiveOptions::default())?;
 /// assert_eq!(Path::new("path\\for\\save.zip").exists(), true);
 /// ```
-fn create_zip_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
+fn create_zip_archive(from: &str, to: &str, options: &ArchiveOptions) -> Result<(), std::io::Error> {
     use std::io::{Error, ErrorKind};
     use std::fs::OpenOptions;
+    use super::throttle::{RateLimiter, ThrottledWriter};
     if let Ok(src) = prepare_packing(from, to) {
         let prefix = src.clone();
         let walkdir = walkdir::WalkDir::new(src);
         let iter = walkdir.into_iter();
 
-        let save_file = OpenOptions::new().write(true).open(to)?;
-        if let Err(_) = zip_dir(&mut iter.filter_map(|e| e.ok()), &prefix, save_file, zip::CompressionMethod::Deflated) {
+        let limiter = RateLimiter::new(options.write_limit);
+        let save_file = ThrottledWriter::new(OpenOptions::new().write(true).open(to)?, &limiter);
+        let (method, level) = options.zip_method();
+        if zip_dir(&mut iter.filter_map(|e| e.ok()), &prefix, save_file, method, level, options.password.as_deref()).is_err() {
             return Err(Error::from(ErrorKind::Other));
         }
     }
@@ -218,6 +599,7 @@ fn create_zip_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// 
 /// * `from` - A string slice that holds the source path
 /// * `to` - A string slice that holds the save path
+/// * `options` - Compression and throttle of archive writing
 /// 
 /// # Examples
 /// 
@@ -227,17 +609,19 @@ fn create_zip_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// let src = "path\\for\\target";
 /// let dst = "path\\for\\save.tar";
 /// 
-/// let _ = create_tar_archive(src, dst)?;
+/// let _ = create_tar_archive(src, dst, &ArchiveOptions::default())?;
 /// assert_eq!(Path::new("path\\for\\save.tar").exists(), true);
 /// ```
-fn create_tar_package(from: &str, to: &str) -> Result<(), std::io::Error> {
+fn create_tar_package(from: &str, to: &str, options: &ArchiveOptions) -> Result<(), std::io::Error> {
     use std::io::{Error, ErrorKind};
     use std::fs::OpenOptions;
+    use super::throttle::{RateLimiter, ThrottledWriter};
     if let Ok(src) = prepare_packing(from, to) {
-        let save_file = OpenOptions::new().write(true).open(to)?;
+        let limiter = RateLimiter::new(options.write_limit);
+        let save_file = ThrottledWriter::new(OpenOptions::new().write(true).open(to)?, &limiter);
         let mut tar_builder = tar::Builder::new(save_file);
 
-        if let Err(_) = tar_builder.append_dir_all("", src) {
+        if append_tar_entries(&mut tar_builder, &src).is_err() {
             return Err(Error::from(ErrorKind::Other));
         }
     }
@@ -254,6 +638,7 @@ fn create_tar_package(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// 
 /// * `from` - A string slice that holds the source path
 /// * `to` - A string slice that holds the save path
+/// * `options` - Compression and throttle of archive writing
 /// 
 /// # Examples
 /// 
@@ -263,20 +648,23 @@ fn create_tar_package(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// let src = "path\\for\\target";
 /// let dst = "path\\for\\save.tar.gz";
 /// 
-/// let _ = create_tar_gz_archive(src, dst)?;
+/// let _ = create_tar_gz_archive(src, dst, &ArchiveOptions::default())?;
 /// assert_eq!(Path::new("path\\for\\save.tar.gz").exists(), true);
 /// ```
-fn create_tar_gz_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
+fn create_tar_gz_archive(from: &str, to: &str, options: &ArchiveOptions) -> Result<(), std::io::Error> {
     use std::io::{Error, ErrorKind};
     use std::fs::OpenOptions;
+    use super::throttle::{RateLimiter, ThrottledWriter};
     use flate2::write::GzEncoder;
 
     if let Ok(src) = prepare_packing(from, to) {
-        let save_file = OpenOptions::new().write(true).open(to)?;
-        let encoder = GzEncoder::new(save_file, flate2::Compression::default());
+        let limiter = RateLimiter::new(options.write_limit);
+        let save_file = ThrottledWriter::new(OpenOptions::new().write(true).open(to)?, &limiter);
+        let level = options.level.map_or(flate2::Compression::default(), |level| flate2::Compression::new(level.min(9)));
+        let encoder = GzEncoder::new(save_file, level);
         let mut tar_builder = tar::Builder::new(encoder);
 
-        if let Err(_) = tar_builder.append_dir_all("", src) {
+        if append_tar_entries(&mut tar_builder, &src).is_err() {
             return Err(Error::from(ErrorKind::Other));
         }
     }
@@ -293,6 +681,7 @@ fn create_tar_gz_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// 
 /// * `from` - A string slice that holds the source path
 /// * `to` - A string slice that holds the save path
+/// * `options` - Compression and throttle of archive writing
 /// 
 /// # Examples
 /// 
@@ -302,20 +691,23 @@ fn create_tar_gz_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// let src = "path\\for\\target";
 /// let dst = "path\\for\\save.tar.gz";
 /// 
-/// let _ = create_tar_bz2_archive(src, dst)?;
+/// let _ = create_tar_bz2_archive(src, dst, &ArchiveOptions::default())?;
 /// assert_eq!(Path::new("path\\for\\save.tar.bz2").exists(), true);
 /// ```
-fn create_tar_bz2_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
+fn create_tar_bz2_archive(from: &str, to: &str, options: &ArchiveOptions) -> Result<(), std::io::Error> {
     use std::io::{Error, ErrorKind};
     use std::fs::OpenOptions;
+    use super::throttle::{RateLimiter, ThrottledWriter};
     use bzip2::write::BzEncoder;
 
     if let Ok(src) = prepare_packing(from, to) {
-        let save_file = OpenOptions::new().write(true).open(to)?;
-        let encoder = BzEncoder::new(save_file, bzip2::Compression::best());
+        let limiter = RateLimiter::new(options.write_limit);
+        let save_file = ThrottledWriter::new(OpenOptions::new().write(true).open(to)?, &limiter);
+        let level = options.level.map_or(bzip2::Compression::best(), |level| bzip2::Compression::new(level.clamp(1, 9)));
+        let encoder = BzEncoder::new(save_file, level);
         let mut tar_builder = tar::Builder::new(encoder);
 
-        if let Err(_) = tar_builder.append_dir_all("", src) {
+        if append_tar_entries(&mut tar_builder, &src).is_err() {
             return Err(Error::from(ErrorKind::Other));
         }
     }
@@ -332,6 +724,7 @@ fn create_tar_bz2_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// 
 /// * `from` - A string slice that holds the source path
 /// * `to` - A string slice that holds the save path
+/// * `options` - Compression and throttle of archive writing
 /// 
 /// # Examples
 /// 
@@ -341,20 +734,127 @@ fn create_tar_bz2_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// let src = "path\\for\\target";
 /// let dst = "path\\for\\save.tar.xz";
 /// 
-/// let _ = create_tar_xz_archive(src, dst)?;
+/// let _ = create_tar_xz_archive(src, dst, &ArchiveOptions::default())?;
 /// assert_eq!(Path::new("path\\for\\save.tar.xz").exists(), true);
 /// ```
-fn create_tar_xz_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
+fn create_tar_xz_archive(from: &str, to: &str, options: &ArchiveOptions) -> Result<(), std::io::Error> {
     use std::io::{Error, ErrorKind};
     use std::fs::OpenOptions;
+    use super::throttle::{RateLimiter, ThrottledWriter};
     use xz2::write::XzEncoder;
+    use xz2::stream::{Check, Filters, LzmaOptions, MtStreamBuilder, Stream};
+
+    if let Ok(src) = prepare_packing(from, to) {
+        let limiter = RateLimiter::new(options.write_limit);
+        let save_file = ThrottledWriter::new(OpenOptions::new().write(true).open(to)?, &limiter);
+
+        let mut lzma_options = LzmaOptions::new_preset(options.lzma_preset())?;
+        if options.dict_size > 0 {
+            lzma_options.dict_size(options.dict_size);
+        }
+        let mut filters = Filters::new();
+        filters.lzma2(&lzma_options);
+        let stream = match options.threads > 1 {
+            true => MtStreamBuilder::new().threads(options.threads).filters(filters).check(Check::Crc64).encoder()?,
+            false => Stream::new_stream_encoder(&filters, Check::Crc64)?,
+        };
+        let encoder = XzEncoder::new_stream(save_file, stream);
+        let mut tar_builder = tar::Builder::new(encoder);
+
+        if append_tar_entries(&mut tar_builder, &src).is_err() {
+            return Err(Error::from(ErrorKind::Other));
+        }
+    }
+
+    finish_packing(from, to)?;
+
+    Ok(())
+}
+
+#[allow(dead_code)]
+/// Creates tar.zst archive for file or directory.
+/// 
+/// Compression level is `3` by default, `1` to `22` are accepted.
+/// 
+/// # Arguments
+/// 
+/// * `from` - A string slice that holds the source path
+/// * `to` - A string slice that holds the save path
+/// * `options` - Compression and throttle of archive writing
+/// 
+/// # Examples
+/// 
+/// ```
+/// use compress::compressor::create_tar_zst_archive;
+/// 
+/// let src = "path\\for\\target";
+/// let dst = "path\\for\\save.tar.zst";
+/// 
+/// let _ = create_tar_zst_archive(src, dst, &ArchiveOptions::default())?;
+/// assert_eq!(Path::new("path\\for\\save.tar.zst").exists(), true);
+/// ```
+fn create_tar_zst_archive(from: &str, to: &str, options: &ArchiveOptions) -> Result<(), std::io::Error> {
+    use std::io::{Error, ErrorKind};
+    use std::fs::OpenOptions;
+    use super::throttle::{RateLimiter, ThrottledWriter};
+    use zstd::stream::write::Encoder;
 
     if let Ok(src) = prepare_packing(from, to) {
-        let save_file = OpenOptions::new().write(true).open(to)?;
-        let encoder = XzEncoder::new(save_file, 6);
+        let limiter = RateLimiter::new(options.write_limit);
+        let save_file = ThrottledWriter::new(OpenOptions::new().write(true).open(to)?, &limiter);
+        let level = options.level.map_or(zstd::DEFAULT_COMPRESSION_LEVEL, |level| level.clamp(1, 22) as i32);
+        let encoder = Encoder::new(save_file, level)?;
         let mut tar_builder = tar::Builder::new(encoder);
 
-        if let Err(_) = tar_builder.append_dir_all("", src) {
+        if append_tar_entries(&mut tar_builder, &src).is_err() {
+            return Err(Error::from(ErrorKind::Other));
+        }
+        tar_builder.into_inner()?.finish()?;
+    }
+
+    finish_packing(from, to)?;
+
+    Ok(())
+}
+
+#[allow(dead_code)]
+/// Creates tar.lz4 archive for file or directory.
+/// 
+/// Archive is written in lz4 frame format, compression level is not adjustable.
+/// 
+/// # Arguments
+/// 
+/// * `from` - A string slice that holds the source path
+/// * `to` - A string slice that holds the save path
+/// * `options` - Compression and throttle of archive writing
+/// 
+/// # Examples
+/// 
+/// ```
+/// use compress::compressor::create_tar_lz4_archive;
+/// 
+/// let src = "path\\for\\target";
+/// let dst = "path\\for\\save.tar.lz4";
+/// 
+/// let _ = create_tar_lz4_archive(src, dst, &ArchiveOptions::default())?;
+/// assert_eq!(Path::new("path\\for\\save.tar.lz4").exists(), true);
+/// ```
+fn create_tar_lz4_archive(from: &str, to: &str, options: &ArchiveOptions) -> Result<(), std::io::Error> {
+    use std::io::{Error, ErrorKind};
+    use std::fs::OpenOptions;
+    use super::throttle::{RateLimiter, ThrottledWriter};
+    use lz4_flex::frame::FrameEncoder;
+
+    if let Ok(src) = prepare_packing(from, to) {
+        let limiter = RateLimiter::new(options.write_limit);
+        let save_file = ThrottledWriter::new(OpenOptions::new().write(true).open(to)?, &limiter);
+        let encoder = FrameEncoder::new(save_file);
+        let mut tar_builder = tar::Builder::new(encoder);
+
+        if append_tar_entries(&mut tar_builder, &src).is_err() {
+            return Err(Error::from(ErrorKind::Other));
+        }
+        if tar_builder.into_inner()?.finish().is_err() {
             return Err(Error::from(ErrorKind::Other));
         }
     }
@@ -371,6 +871,7 @@ fn create_tar_xz_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// 
 /// * `from` - A string slice that holds the source path
 /// * `to` - A string slice that holds the save path
+/// * `options` - Compression and throttle of archive writing
 /// 
 /// # Examples
 /// 
@@ -380,13 +881,61 @@ fn create_tar_xz_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// let src = "path\\for\\target";
 /// let dst = "path\\for\\save.7z";
 /// 
-/// let _ = create_7zip_archive(src, dst)?;
+/// let _ = create_7zip_archive(src, dst, &ArchiveOptions::default())?;
 /// assert_eq!(Path::new("path\\for\\save.7z").exists(), true);
 /// ```
-fn create_7zip_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
+fn create_7zip_archive(from: &str, to: &str, options: &ArchiveOptions) -> Result<(), std::io::Error> {
     use std::io::{Error, ErrorKind};
 
-    if let Err(_) = sevenz_rust::compress_to_path(from, to) {
+    use std::fs::{File, create_dir_all};
+    use std::path::Path;
+    use sevenz_rust::{AesEncoderOptions, Password, SevenZArchiveEntry, SevenZWriter, lzma::LZMA2Options};
+    use super::throttle::{RateLimiter, ThrottledWriter};
+
+    if let Some(parent) = Path::new(to).parent() {
+        create_dir_all(parent)?;
+    }
+    let limiter = RateLimiter::new(options.write_limit);
+    let save_file = ThrottledWriter::new(File::create(to)?, &limiter);
+    let mut writer = SevenZWriter::new(save_file).map_err(|_| Error::from(ErrorKind::Other))?;
+
+    let mut lzma_options = LZMA2Options::with_preset(options.lzma_preset());
+    if options.dict_size > 0 {
+        lzma_options.dict_size = options.dict_size;
+    }
+    match &options.password {
+        Some(password) => writer.set_content_methods(vec![AesEncoderOptions::new(Password::from(password.as_str())).into(), lzma_options.into()]),
+        None => writer.set_content_methods(vec![lzma_options.into()]),
+    };
+
+    // entries are named relative to directory, or the parent of file, same as `sevenz_rust::compress`
+    let src = Path::new(from);
+    let root = match src.is_dir() {
+        true => src,
+        false => src.parent().unwrap_or(src),
+    };
+    // 7z keeps no symbolic links, targets of links are archived instead and broken links fail
+    for entry in walkdir::WalkDir::new(src).follow_links(true).sort_by_file_name() {
+        let entry = entry?;
+        let path = entry.path();
+        let name = match path.strip_prefix(root) {
+            Ok(name) if !name.as_os_str().is_empty() => name.to_string_lossy().replace('\\', "/"),
+            _ => continue,
+        };
+
+        let file_type = entry.file_type();
+        let pushed = if file_type.is_dir() {
+            writer.push_archive_entry::<File>(SevenZArchiveEntry::from_path(path, name), None)
+        } else if file_type.is_file() {
+            writer.push_archive_entry(SevenZArchiveEntry::from_path(path, name), Some(File::open(path)?))
+        } else {
+            return Err(Error::new(ErrorKind::Unsupported, format!("unsupported file type for 7z: {}", path.display())));
+        };
+        if pushed.is_err() {
+            return Err(Error::from(ErrorKind::Other));
+        }
+    }
+    if writer.finish().is_err() {
         return Err(Error::from(ErrorKind::Other));
     }
     
@@ -413,8 +962,37 @@ fn create_7zip_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
 /// assert_eq!(Path::new("path\\for\\save.zip").exists(), true);
 /// ```
 pub fn create_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
+    create_archive_with(from, to, &ArchiveOptions::default())
+}
+
+#[allow(dead_code)]
+/// Creates archive for file or directory with throttle.
+/// 
+/// Compression follows level and method of `options`, writing is limited to
+/// `options.write_limit` bytes per second, and compression runs in a thread of `options.priority`.
+/// 
+/// # Arguments
+/// 
+/// * `from` - A string slice that holds the source path
+/// * `to` - A string slice that holds the save path
+/// * `options` - Compression and throttle of archive writing
+/// 
+/// # Examples
+/// 
+/// ```
+/// use compress::compressor::{ArchiveOptions, create_archive_with};
+/// 
+/// let src = "path\\for\\target";
+/// let dst = "path\\for\\save.zip";
+/// let options = ArchiveOptions { write_limit: 10 * 1024 * 1024, priority: 1, level: Some(9), ..Default::default() };
+/// 
+/// let _ = create_archive_with(src, dst, &options)?;
+/// assert_eq!(Path::new("path\\for\\save.zip").exists(), true);
+/// ```
+pub fn create_archive_with(from: &str, to: &str, options: &ArchiveOptions) -> Result<(), std::io::Error> {
     use std::io::{Error, ErrorKind};
     use std::path::Path;
+    use super::throttle::run_with_priority;
 
     let src_path = Path::new(from);
     let save_path = Path::new(to);
@@ -428,7 +1006,7 @@ pub fn create_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
     }
 
     let mut save_format: Option<&str> = None;
-    let support_formats = Vec::from(["zip", "tar.gz", "tar.bz2", "tar.xz", "7z"]);
+    let support_formats = Vec::from(["zip", "tar.gz", "tar.bz2", "tar.xz", "7z", "tar.zst", "tar.lz4"]);
     for item in &support_formats {
         if to.ends_with(item) {
             save_format = Some(item);
@@ -437,28 +1015,264 @@ pub fn create_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
     }        
 
     if let Some(format) = save_format {
-        match format {
-            "zip" => {
-                create_zip_archive(from, to)?;
-            },
-            "tar.gz" => {
-                create_tar_gz_archive(from, to)?;
-            },
-            "tar.bz2" => {
-                create_tar_bz2_archive(from, to)?;
-            },
-            "tar.xz" => {
-                create_tar_xz_archive(from, to)?;
+        run_with_priority(options.priority, || {
+            match format {
+                "zip" => create_zip_archive(from, to, options),
+                "tar.gz" => create_tar_gz_archive(from, to, options),
+                "tar.bz2" => create_tar_bz2_archive(from, to, options),
+                "tar.xz" => create_tar_xz_archive(from, to, options),
+                "tar.zst" => create_tar_zst_archive(from, to, options),
+                "tar.lz4" => create_tar_lz4_archive(from, to, options),
+                "7z" => create_7zip_archive(from, to, options),
+                _ => Err(Error::from(ErrorKind::Unsupported)),
+            }
+        })?;
+    }
+
+    Ok(())
+}
+
+#[allow(dead_code)]
+/// Whether the path is archive of supported formats, or first volume of it.
+/// 
+/// # Arguments
+/// 
+/// * `path` - A string slice that holds the archive path
+/// 
+/// # Examples
+/// 
+/// ```
+/// use compress::compressor::is_archive;
+/// 
+/// assert_eq!(is_archive("path\\for\\save.tar.gz"), true);
+/// assert_eq!(is_archive("path\\for\\save.zip.001"), true);
+/// assert_eq!(is_archive("path\\for\\save.txt"), false);
+/// ```
+pub fn is_archive(path: &str) -> bool {
+    let support_formats = Vec::from(["zip", "tar.gz", "tar.bz2", "tar.xz", "7z", "tar.zst", "tar.lz4"]);
+    let path = path.strip_suffix(".001").unwrap_or(path);
+
+    support_formats.iter().any(|format| path.ends_with(&format!(".{}", format)))
+}
+
+#[allow(dead_code)]
+/// Extracts archive into directory.
+/// 
+/// Volumes are joined before extracting if `from` is the first volume, see [split_volumes].
+/// 
+/// # Arguments
+/// 
+/// * `from` - A string slice that holds the archive path
+/// * `to` - A string slice that holds the directory to extract into
+/// 
+/// # Examples
+/// 
+/// ```
+/// use compress::compressor::extract_archive;
+/// 
+/// let src = "path\\for\\save.zip";
+/// let dst = "path\\for\\extract";
+/// 
+/// let _ = extract_archive(src, dst)?;
+/// assert_eq!(Path::new("path\\for\\extract").exists(), true);
+/// ```
+pub fn extract_archive(from: &str, to: &str) -> Result<(), std::io::Error> {
+    extract_archive_with(from, to, None)
+}
+
+#[allow(dead_code)]
+/// Extracts archive encrypted with password into directory, see [extract_archive].
+/// 
+/// Fails with `PermissionDenied` if password is missing or wrong.
+/// 
+/// # Arguments
+/// 
+/// * `from` - A string slice that holds the archive path
+/// * `to` - A string slice that holds the directory to extract into
+/// * `password` - Password of zip or 7z archive, unused for other formats
+/// 
+/// # Examples
+/// 
+/// ```
+/// use compress::compressor::extract_archive_with;
+/// 
+/// let src = "path\\for\\save.7z";
+/// let dst = "path\\for\\extract";
+/// 
+/// let _ = extract_archive_with(src, dst, Some("password"))?;
+/// assert_eq!(Path::new("path\\for\\extract").exists(), true);
+/// ```
+pub fn extract_archive_with(from: &str, to: &str, password: Option<&str>) -> Result<(), std::io::Error> {
+    use std::io::{Error, ErrorKind};
+    use std::path::Path;
+    use std::fs::{File, create_dir_all};
+    use flate2::read::GzDecoder;
+    use bzip2::read::BzDecoder;
+    use xz2::read::XzDecoder;
+    use zstd::stream::read::Decoder as ZstdDecoder;
+    use lz4_flex::frame::FrameDecoder;
+
+    if !Path::new(from).is_file() {
+        return Err(Error::from(ErrorKind::NotFound));
+    }
+    create_dir_all(to)?;
+
+    // volumes are joined beside themselves, as the archive may be too large for temp directory
+    if let Some(base) = from.strip_suffix(".001") {
+        let base = Path::new(base);
+        let join_dir = base.with_file_name("joining");
+        let joined = join_dir.join(base.file_name().unwrap_or_default());
+        create_dir_all(&join_dir)?;
+        let extracted = join_volumes(from, &joined.display().to_string())
+            .and_then(|_| extract_archive_with(&joined.display().to_string(), to, password));
+        let _ = std::fs::remove_dir_all(&join_dir);
+        return extracted;
+    }
+
+    if from.ends_with(".zip") {
+        extract_zip_archive(from, to, password)?;
+    } else if from.ends_with(".tar.gz") {
+        unpack_tar(GzDecoder::new(File::open(from)?), to)?;
+    } else if from.ends_with(".tar.bz2") {
+        unpack_tar(BzDecoder::new(File::open(from)?), to)?;
+    } else if from.ends_with(".tar.xz") {
+        unpack_tar(XzDecoder::new(File::open(from)?), to)?;
+    } else if from.ends_with(".tar.zst") {
+        unpack_tar(ZstdDecoder::new(File::open(from)?)?, to)?;
+    } else if from.ends_with(".tar.lz4") {
+        unpack_tar(FrameDecoder::new(File::open(from)?), to)?;
+    } else if from.ends_with(".7z") {
+        let extracted = match password {
+            Some(password) => sevenz_rust::decompress_file_with_password(from, to, password.into()),
+            None => sevenz_rust::decompress_file(from, to),
+        };
+        match extracted {
+            Err(sevenz_rust::Error::PasswordRequired) | Err(sevenz_rust::Error::MaybeBadPassword(_)) => {
+                return Err(Error::from(ErrorKind::PermissionDenied));
             },
-            "7z" => {
-                create_7zip_archive(from, to)?;
+            Err(_) => {
+                return Err(Error::from(ErrorKind::Other));
             },
-            _ => {
-                return Err(Error::from(ErrorKind::Unsupported));
-            }       
+            Ok(()) => {}
         }
+    } else {
+        return Err(Error::from(ErrorKind::Unsupported));
+    }
+
+    Ok(())
+}
+
+#[allow(dead_code)]
+/// Splits file into volumes of `volume_size` bytes, named `<path>.001`, `<path>.002`...
+/// 
+/// Volumes are plain byte ranges of the file, the same as volumes of 7-Zip, and can be joined
+/// by [join_volumes] or `copy /b` and `cat`. The original file is removed after splitting, and
+/// file not larger than one volume is kept as it is.
+/// 
+/// Returns paths of the volumes, or the path itself if not split.
+/// 
+/// # Arguments
+/// 
+/// * `path` - A string slice that holds the file path
+/// * `volume_size` - Max bytes of each volume, `0` means no split
+/// 
+/// # Examples
+/// 
+/// ```
+/// use compress::compressor::split_volumes;
+/// 
+/// let volumes = split_volumes("path\\for\\save.zip", 4 * 1024 * 1024 * 1024 - 1)?;
+/// assert_eq!(volumes[0], "path\\for\\save.zip.001".to_string());
+/// ```
+pub fn split_volumes(path: &str, volume_size: u64) -> Result<Vec<String>, std::io::Error> {
+    use std::io::{BufWriter, Read, Write, copy};
+    use std::fs::{File, remove_file};
+
+    let size = std::fs::metadata(path)?.len();
+    if volume_size == 0 || size <= volume_size {
+        return Ok(vec![path.to_string()]);
+    }
+
+    let mut volumes: Vec<String> = Vec::new();
+    let mut reader = File::open(path)?;
+    let mut remaining = size;
+    while remaining > 0 {
+        let volume = format!("{}.{:03}", path, volumes.len() + 1);
+        let mut writer = BufWriter::new(File::create(&volume)?);
+        let written = copy(&mut Read::by_ref(&mut reader).take(volume_size), &mut writer)?;
+        writer.flush()?;
+        volumes.push(volume);
+        remaining = remaining.saturating_sub(written.max(1));
+    }
+    drop(reader);
+    remove_file(path)?;
+
+    Ok(volumes)
+}
+
+#[allow(dead_code)]
+/// Lists volumes starting from `first`, stops at the first missing one.
+/// 
+/// Returns `first` itself if it is not the first volume.
+/// 
+/// # Arguments
+/// 
+/// * `first` - A string slice that holds path of the first volume, ends with `.001`
+/// 
+/// # Examples
+/// 
+/// ```
+/// use compress::compressor::list_volumes;
+/// 
+/// for volume in list_volumes("path\\for\\save.zip.001") {
+///     println!("{}", volume);
+/// }
+/// ```
+pub fn list_volumes(first: &str) -> Vec<String> {
+    use std::path::Path;
+
+    let base = match first.strip_suffix(".001") {
+        Some(base) => base,
+        None => {
+            return vec![first.to_string()];
+        }
+    };
+
+    (1..).map(|idx| format!("{}.{:03}", base, idx))
+        .take_while(|volume| Path::new(volume).is_file())
+        .collect()
+}
+
+#[allow(dead_code)]
+/// Joins volumes starting from `first` into file `to`.
+/// 
+/// # Arguments
+/// 
+/// * `first` - A string slice that holds path of the first volume, ends with `.001`
+/// * `to` - A string slice that holds the joined file path
+/// 
+/// # Examples
+/// 
+/// ```
+/// use compress::compressor::join_volumes;
+/// 
+/// join_volumes("path\\for\\save.zip.001", "path\\for\\joined\\save.zip")?;
+/// ```
+pub fn join_volumes(first: &str, to: &str) -> Result<(), std::io::Error> {
+    use std::io::{BufWriter, Error, ErrorKind, Write, copy};
+    use std::fs::File;
+
+    let volumes = list_volumes(first);
+    if volumes.is_empty() {
+        return Err(Error::from(ErrorKind::NotFound));
     }
 
+    let mut writer = BufWriter::new(File::create(to)?);
+    for volume in volumes.iter() {
+        copy(&mut File::open(volume)?, &mut writer)?;
+    }
+    writer.flush()?;
+
     Ok(())
 }
 
@@ -529,8 +1343,8 @@ mod test {
         use std::fs::remove_dir_all;
 
         if let Ok(to_compress) = test_build_pack("zip") {
-            let _ = create_zip_archive(&to_compress.file_from.as_str(), &to_compress.file_to.as_str());
-            let _ = create_zip_archive(&to_compress.dir_from.as_str(), &to_compress.dir_to.as_str());
+            let _ = create_zip_archive(&to_compress.file_from.as_str(), &to_compress.file_to.as_str(), &ArchiveOptions::default());
+            let _ = create_zip_archive(&to_compress.dir_from.as_str(), &to_compress.dir_to.as_str(), &ArchiveOptions::default());
         
             assert_eq!(Path::new(to_compress.file_to.as_str()).exists(), true);
             assert_eq!(Path::new(to_compress.dir_to.as_str()).exists(), true);
@@ -546,8 +1360,8 @@ mod test {
         use std::fs::remove_dir_all;
 
         if let Ok(to_compress) = test_build_pack("tar") {
-            let _ = create_tar_package(&to_compress.file_from.as_str(), &to_compress.file_to.as_str());
-            let _ = create_tar_package(&to_compress.dir_from.as_str(), &to_compress.dir_to.as_str());
+            let _ = create_tar_package(&to_compress.file_from.as_str(), &to_compress.file_to.as_str(), &ArchiveOptions::default());
+            let _ = create_tar_package(&to_compress.dir_from.as_str(), &to_compress.dir_to.as_str(), &ArchiveOptions::default());
         
             assert_eq!(Path::new(to_compress.file_to.as_str()).exists(), true);
             assert_eq!(Path::new(to_compress.dir_to.as_str()).exists(), true);
@@ -563,8 +1377,8 @@ mod test {
         use std::fs::remove_dir_all;
 
         if let Ok(to_compress) = test_build_pack("tar.gz") {
-            let _ = create_tar_gz_archive(&to_compress.file_from.as_str(), &to_compress.file_to.as_str());
-            let _ = create_tar_gz_archive(&to_compress.dir_from.as_str(), &to_compress.dir_to.as_str());
+            let _ = create_tar_gz_archive(&to_compress.file_from.as_str(), &to_compress.file_to.as_str(), &ArchiveOptions::default());
+            let _ = create_tar_gz_archive(&to_compress.dir_from.as_str(), &to_compress.dir_to.as_str(), &ArchiveOptions::default());
         
             assert_eq!(Path::new(to_compress.file_to.as_str()).exists(), true);
             assert_eq!(Path::new(to_compress.dir_to.as_str()).exists(), true);
@@ -580,8 +1394,8 @@ mod test {
         use std::fs::remove_dir_all;
 
         if let Ok(to_compress) = test_build_pack("tar.bz2") {
-            let _ = create_tar_bz2_archive(&to_compress.file_from.as_str(), &to_compress.file_to.as_str());
-            let _ = create_tar_bz2_archive(&to_compress.dir_from.as_str(),&to_compress.dir_to.as_str());
+            let _ = create_tar_bz2_archive(&to_compress.file_from.as_str(), &to_compress.file_to.as_str(), &ArchiveOptions::default());
+            let _ = create_tar_bz2_archive(&to_compress.dir_from.as_str(),&to_compress.dir_to.as_str(), &ArchiveOptions::default());
         
             assert_eq!(Path::new(to_compress.file_to.as_str()).exists(), true);
             assert_eq!(Path::new(to_compress.dir_to.as_str()).exists(), true);
@@ -597,8 +1411,8 @@ mod test {
         use std::fs::remove_dir_all;
 
         if let Ok(to_compress) = test_build_pack("tar.xz") {
-            let _ = create_tar_xz_archive(&to_compress.file_from.as_str(), &to_compress.file_to.as_str());
-            let _ = create_tar_xz_archive(&to_compress.dir_from.as_str(), &to_compress.dir_to.as_str());
+            let _ = create_tar_xz_archive(&to_compress.file_from.as_str(), &to_compress.file_to.as_str(), &ArchiveOptions::default());
+            let _ = create_tar_xz_archive(&to_compress.dir_from.as_str(), &to_compress.dir_to.as_str(), &ArchiveOptions::default());
         
             assert_eq!(Path::new(to_compress.file_to.as_str()).exists(), true);
             assert_eq!(Path::new(to_compress.dir_to.as_str()).exists(), true);
@@ -607,6 +1421,40 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_create_tar_zst_archive() {
+        use std::env::current_dir;
+        use std::path::Path;
+        use std::fs::remove_dir_all;
+
+        if let Ok(to_compress) = test_build_pack("tar.zst") {
+            let _ = create_tar_zst_archive(&to_compress.file_from.as_str(), &to_compress.file_to.as_str(), &ArchiveOptions::default());
+            let _ = create_tar_zst_archive(&to_compress.dir_from.as_str(), &to_compress.dir_to.as_str(), &ArchiveOptions::default());
+        
+            assert_eq!(Path::new(to_compress.file_to.as_str()).exists(), true);
+            assert_eq!(Path::new(to_compress.dir_to.as_str()).exists(), true);
+
+            let _ = remove_dir_all(current_dir().expect("").join(format!("test_compress_{}", "tar.zst"))).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_create_tar_lz4_archive() {
+        use std::env::current_dir;
+        use std::path::Path;
+        use std::fs::remove_dir_all;
+
+        if let Ok(to_compress) = test_build_pack("tar.lz4") {
+            let _ = create_tar_lz4_archive(&to_compress.file_from.as_str(), &to_compress.file_to.as_str(), &ArchiveOptions::default());
+            let _ = create_tar_lz4_archive(&to_compress.dir_from.as_str(), &to_compress.dir_to.as_str(), &ArchiveOptions::default());
+        
+            assert_eq!(Path::new(to_compress.file_to.as_str()).exists(), true);
+            assert_eq!(Path::new(to_compress.dir_to.as_str()).exists(), true);
+
+            let _ = remove_dir_all(current_dir().expect("").join(format!("test_compress_{}", "tar.lz4"))).unwrap();
+        }
+    }
+
     #[test]
     #[ignore]
     fn test_create_7zip_archive() {
@@ -615,8 +1463,8 @@ mod test {
         use std::fs::remove_dir_all;
 
         if let Ok(to_compress) = test_build_pack("7z") {
-            let _ = create_7zip_archive(&to_compress.file_from.as_str(), &to_compress.file_to.as_str());
-            let _ = create_7zip_archive(&to_compress.dir_from.as_str(), &to_compress.dir_to.as_str());
+            let _ = create_7zip_archive(&to_compress.file_from.as_str(), &to_compress.file_to.as_str(), &ArchiveOptions::default());
+            let _ = create_7zip_archive(&to_compress.dir_from.as_str(), &to_compress.dir_to.as_str(), &ArchiveOptions::default());
         
             assert_eq!(Path::new(to_compress.file_to.as_str()).exists(), true);
             assert_eq!(Path::new(to_compress.dir_to.as_str()).exists(), true);
@@ -641,4 +1489,322 @@ mod test {
             let _ = remove_dir_all(current_dir().expect("").join(format!("test_compress_{}", "7z"))).unwrap();
         }     
     }
+
+    #[test]
+    fn test_extract_archive() {
+        use std::env::current_dir;
+        use std::path::Path;
+        use std::fs::{read_to_string, remove_dir_all};
+
+        for format in ["zip", "tar.gz", "tar.bz2", "tar.xz", "7z", "tar.zst", "tar.lz4"] {
+            if let Ok(to_compress) = test_build_pack(&format!("extract_{}", format)) {
+                let dir_to = format!("{}.{}", to_compress.dir_from, format);
+                let _ = create_archive(&to_compress.dir_from.as_str(), &dir_to.as_str()).unwrap();
+
+                let extract_to = format!("{}_extracted", to_compress.dir_from);
+                let _ = extract_archive(&dir_to.as_str(), &extract_to.as_str()).unwrap();
+
+                let extracted = Path::new(&extract_to).join("test_file.txt");
+                assert_eq!(read_to_string(extracted).unwrap(), "Hello world!".to_string());
+
+                let _ = remove_dir_all(current_dir().expect("").join(format!("test_compress_extract_{}", format))).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_extract_archive_metadata() {
+        use std::env::current_dir;
+        use std::path::Path;
+        use std::fs::{create_dir_all, remove_dir_all, write};
+        use std::time::{Duration, SystemTime};
+        use crate::utils::metadata::set_times;
+
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        for format in ["zip", "tar.gz"] {
+            let root = current_dir().unwrap().join(format!("test_compress_metadata_{}", format));
+            let _ = remove_dir_all(&root);
+            let dir = root.join("test_dir");
+            create_dir_all(dir.join("sub")).unwrap();
+            write(dir.join("sub").join("test_file.txt"), "Hello world!").unwrap();
+            set_times(dir.join("sub").join("test_file.txt"), modified, modified).unwrap();
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(dir.join("sub").join("test_file.txt"), std::fs::Permissions::from_mode(0o640)).unwrap();
+                std::os::unix::fs::symlink("sub/test_file.txt", dir.join("test_link")).unwrap();
+            }
+            set_times(dir.join("sub"), modified, modified).unwrap();
+
+            let dir_to = format!("{}.{}", dir.display(), format);
+            create_archive(&dir.display().to_string(), &dir_to).unwrap();
+            let extract_to = root.join("extracted");
+            extract_archive(&dir_to, &extract_to.display().to_string()).unwrap();
+
+            let extracted = Path::new(&extract_to).join("sub").join("test_file.txt");
+            assert_eq!(extracted.metadata().unwrap().modified().unwrap(), modified);
+            assert_eq!(extract_to.join("sub").metadata().unwrap().modified().unwrap(), modified);
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                assert_eq!(extracted.metadata().unwrap().permissions().mode() & 0o777, 0o640);
+                let link = extract_to.join("test_link");
+                assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+                assert_eq!(std::fs::read_link(&link).unwrap(), Path::new("sub/test_file.txt"));
+            }
+
+            remove_dir_all(&root).unwrap();
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_zip_symlink_escape() {
+        use std::env::current_dir;
+        use std::fs::{File, create_dir_all, remove_dir_all};
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let root = current_dir().unwrap().join("test_compress_symlink_escape");
+        let _ = remove_dir_all(&root);
+        create_dir_all(root.join("outside")).unwrap();
+
+        // link in archive, then file written through it
+        let archive = root.join("escape.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        zip.add_symlink("evil", "../outside", SimpleFileOptions::default()).unwrap();
+        zip.start_file("evil/pwned.txt", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"Hello world!").unwrap();
+        zip.finish().unwrap();
+
+        let extract_to = root.join("extracted");
+        let _ = extract_archive(&archive.display().to_string(), &extract_to.display().to_string());
+        assert!(!root.join("outside").join("pwned.txt").exists());
+
+        // link already in extract directory
+        let archive = root.join("existing.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        zip.start_file("pre/pwned.txt", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"Hello world!").unwrap();
+        zip.finish().unwrap();
+
+        let extract_to = root.join("existing");
+        create_dir_all(&extract_to).unwrap();
+        create_dir_all(root.join("outside_pre")).unwrap();
+        std::os::unix::fs::symlink("../outside_pre", extract_to.join("pre")).unwrap();
+        let error = extract_archive(&archive.display().to_string(), &extract_to.display().to_string()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(!root.join("outside_pre").join("pwned.txt").exists());
+
+        remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_create_archive_with_options() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+
+        let root = current_dir().unwrap().join("test_compress_options");
+        let _ = remove_dir_all(&root);
+        let dir = root.join("test_dir");
+        create_dir_all(dir.join("sub")).unwrap();
+        write(dir.join("sub").join("test_file.txt"), "Hello world! ".repeat(4096)).unwrap();
+
+        let cases = [
+            ("stored.zip", ArchiveOptions { method: 3, ..Default::default() }),
+            ("fast.zip", ArchiveOptions { level: Some(0), ..Default::default() }),
+            ("deflate.zip", ArchiveOptions { level: Some(9), ..Default::default() }),
+            ("bzip2.zip", ArchiveOptions { method: 1, level: Some(20), ..Default::default() }),
+            ("zstd.zip", ArchiveOptions { method: 2, level: Some(19), ..Default::default() }),
+            ("fast.tar.gz", ArchiveOptions { level: Some(1), ..Default::default() }),
+            ("fast.tar.bz2", ArchiveOptions { level: Some(0), ..Default::default() }),
+            ("threads.tar.xz", ArchiveOptions { level: Some(1), dict_size: 1024 * 1024, threads: 2, ..Default::default() }),
+            ("best.tar.zst", ArchiveOptions { level: Some(30), ..Default::default() }),
+            ("dict.7z", ArchiveOptions { level: Some(9), dict_size: 1024 * 1024, ..Default::default() }),
+        ];
+        for (name, options) in cases.iter() {
+            let dir_to = root.join(name).display().to_string();
+            create_archive_with(&dir.display().to_string(), &dir_to, options).unwrap();
+
+            let extract_to = root.join(format!("extracted_{}", name));
+            extract_archive(&dir_to, &extract_to.display().to_string()).unwrap();
+            assert_eq!(read_to_string(extract_to.join("sub").join("test_file.txt")).unwrap(), "Hello world! ".repeat(4096), "{}", name);
+        }
+
+        let stored = root.join("stored.zip").metadata().unwrap().len();
+        let deflated = root.join("deflate.zip").metadata().unwrap().len();
+        assert!(deflated < stored / 10, "{} {}", deflated, stored);
+
+        remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_split_volumes() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, read, remove_dir_all, write};
+
+        let root = current_dir().unwrap().join("test_compress_volumes");
+        let _ = remove_dir_all(&root);
+        let dir = root.join("test_dir");
+        create_dir_all(&dir).unwrap();
+
+        // content hardly compressible, so archives are larger than one volume
+        let mut seed: u32 = 1;
+        let content: Vec<u8> = (0..16 * 1024).map(|_| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            (seed >> 16) as u8
+        }).collect();
+        write(dir.join("test_file.bin"), &content).unwrap();
+
+        for format in ["zip", "tar.gz", "7z"] {
+            let dir_to = root.join(format!("test_dir.{}", format)).display().to_string();
+            create_archive_with(&dir.display().to_string(), &dir_to, &ArchiveOptions { method: 3, level: Some(0), ..Default::default() }).unwrap();
+            let size = std::fs::metadata(&dir_to).unwrap().len();
+
+            let volumes = split_volumes(&dir_to, 1024).unwrap();
+            assert_eq!(volumes.len() as u64, size.div_ceil(1024), "{}", format);
+            assert_eq!(volumes[0], format!("{}.001", dir_to));
+            assert_eq!(list_volumes(&volumes[0]), volumes);
+            assert!(is_archive(&volumes[0]));
+            assert!(!std::path::Path::new(&dir_to).exists());
+
+            let extract_to = root.join(format!("extracted_{}", format));
+            extract_archive(&volumes[0], &extract_to.display().to_string()).unwrap();
+            assert_eq!(read(extract_to.join("test_file.bin")).unwrap(), content);
+            assert!(!root.join("joining").exists());
+        }
+
+        // file fits in one volume is kept
+        let single = root.join("single.txt").display().to_string();
+        write(&single, "Hello world!").unwrap();
+        assert_eq!(split_volumes(&single, 1024).unwrap(), vec![single.clone()]);
+
+        remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_archive_password() {
+        use std::env::current_dir;
+        use std::io::ErrorKind;
+        use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+
+        let root = current_dir().unwrap().join("test_compress_password");
+        let _ = remove_dir_all(&root);
+        let dir = root.join("test_dir");
+        create_dir_all(dir.join("sub")).unwrap();
+        write(dir.join("sub").join("test_file.txt"), "Hello world!").unwrap();
+
+        let options = ArchiveOptions { password: Some("p@ssw0rd".to_string()), ..Default::default() };
+        for format in ["zip", "7z"] {
+            let dir_to = root.join(format!("test_dir.{}", format)).display().to_string();
+            create_archive_with(&dir.display().to_string(), &dir_to, &options).unwrap();
+
+            let extract_to = root.join(format!("extracted_{}", format)).display().to_string();
+            assert_eq!(extract_archive(&dir_to, &extract_to).unwrap_err().kind(), ErrorKind::PermissionDenied, "{}", format);
+            assert!(extract_archive_with(&dir_to, &extract_to, Some("wrong")).is_err(), "{}", format);
+
+            let _ = remove_dir_all(&extract_to);
+            extract_archive_with(&dir_to, &extract_to, Some("p@ssw0rd")).unwrap();
+            assert_eq!(read_to_string(std::path::Path::new(&extract_to).join("sub").join("test_file.txt")).unwrap(), "Hello world!");
+        }
+
+        remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_7zip_symlinks() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, read_to_string, remove_dir_all, remove_file, write};
+        use std::os::unix::fs::symlink;
+
+        let root = current_dir().unwrap().join("test_compress_7zip_symlinks");
+        let _ = remove_dir_all(&root);
+        let dir = root.join("test_dir");
+        create_dir_all(dir.join("sub")).unwrap();
+        write(dir.join("sub").join("test_file.txt"), "Hello world!").unwrap();
+        symlink("sub/test_file.txt", dir.join("file_link")).unwrap();
+        symlink("sub", dir.join("dir_link")).unwrap();
+
+        let dir_to = root.join("test_dir.7z").display().to_string();
+        create_archive(&dir.display().to_string(), &dir_to).unwrap();
+
+        let extract_to = root.join("extracted");
+        extract_archive(&dir_to, &extract_to.display().to_string()).unwrap();
+        assert_eq!(read_to_string(extract_to.join("file_link")).unwrap(), "Hello world!");
+        assert_eq!(read_to_string(extract_to.join("dir_link").join("test_file.txt")).unwrap(), "Hello world!");
+
+        // broken link fails instead of being dropped
+        remove_file(dir.join("file_link")).unwrap();
+        symlink("missing.txt", dir.join("file_link")).unwrap();
+        assert!(create_archive(&dir.display().to_string(), &dir_to).is_err());
+
+        remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_zip_large_file() {
+        use std::env::current_dir;
+        use std::io::{copy, sink, Seek, SeekFrom, Write};
+        use std::fs::{create_dir_all, remove_dir_all, File};
+
+        let root = current_dir().unwrap().join("test_compress_large_file");
+        let _ = remove_dir_all(&root);
+        let dir = root.join("test_dir");
+        create_dir_all(&dir).unwrap();
+
+        // Sparse file takes no disk space but still crosses 4 GiB
+        let size = 0x100000000 + 4;
+        let mut file = File::create(dir.join("large.bin")).unwrap();
+        file.seek(SeekFrom::Start(size - 4)).unwrap();
+        file.write_all(b"tail").unwrap();
+        drop(file);
+
+        let dir_to = root.join("test_dir.zip").display().to_string();
+        create_archive(&dir.display().to_string(), &dir_to).unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&dir_to).unwrap()).unwrap();
+        let mut entry = archive.by_name("large.bin").unwrap();
+        assert_eq!(entry.size(), size);
+        assert_eq!(copy(&mut entry, &mut sink()).unwrap(), size);
+
+        remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_zip_many_entries() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, read_dir, remove_dir_all, File};
+
+        let root = current_dir().unwrap().join("test_compress_many_entries");
+        let _ = remove_dir_all(&root);
+        let dir = root.join("test_dir");
+
+        // More entries than the 16-bit count of a plain zip end record
+        let count: usize = 0x10000 + 10;
+        for idx in 0..count {
+            let sub = dir.join(format!("{}", idx / 1000));
+            if idx % 1000 == 0 {
+                create_dir_all(&sub).unwrap();
+            }
+            File::create(sub.join(format!("{}.txt", idx))).unwrap();
+        }
+
+        let dir_to = root.join("test_dir.zip").display().to_string();
+        create_archive(&dir.display().to_string(), &dir_to).unwrap();
+
+        let archive = zip::ZipArchive::new(File::open(&dir_to).unwrap()).unwrap();
+        let dirs = count.div_ceil(1000);
+        assert_eq!(archive.len(), count + dirs);
+
+        let extract_to = root.join("extracted").display().to_string();
+        extract_archive(&dir_to, &extract_to).unwrap();
+        let extracted: usize = read_dir(&extract_to).unwrap()
+            .map(|sub| read_dir(sub.unwrap().path()).unwrap().count())
+            .sum();
+        assert_eq!(extracted, count);
+
+        remove_dir_all(&root).unwrap();
+    }
 }
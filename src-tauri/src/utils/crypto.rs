@@ -100,8 +100,8 @@ pub fn decode_base64(data: &str, is_url: Option<bool>) -> Result<String, base64:
 /// ```
 pub fn encode_sha2_file(path: &str) -> Result<String, std::io::Error> {
     use sha2::{Sha256, Digest};
-    use std::io::{Error, ErrorKind};
-    use std::fs::read;
+    use std::io::{Error, ErrorKind, copy};
+    use std::fs::File;
     use std::path::Path;
     
     let input = Path::new(path);
@@ -111,9 +111,9 @@ pub fn encode_sha2_file(path: &str) -> Result<String, std::io::Error> {
 
     let mut hasher = Sha256::new();
 
-    match read(path) {
-        Ok(data) => {
-            hasher.update(data);
+    // read in chunks, large files are not loaded into memory at once
+    match File::open(path).and_then(|mut file| copy(&mut file, &mut hasher)) {
+        Ok(_) => {
             return Ok(format!("{:X}", hasher.finalize()));
         },
         Err(_error) => {
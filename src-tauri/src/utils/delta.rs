@@ -0,0 +1,412 @@
+//! The `delta` module contains functions about rsync-like delta transfer.
+//! 
+//! Signature of old file holds rolling and strong checksums of its blocks, new file is
+//! scanned with the rolling checksum to find blocks that can be copied from old file,
+//! only the rest is written into patch as literal data.
+
+use serde::{Serialize, Deserialize};
+
+/// Magic header of patch file.
+const PATCH_MAGIC: &[u8; 8] = b"MBDELTA1";
+
+/// Literal data is flushed into patch when pending data reaches this size.
+const LITERAL_LIMIT: usize = 1024 * 1024;
+
+/// Struct FileSignature
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileSignature {
+    /// File size in bytes
+    pub size: u64,
+
+    /// Last modification time in unix seconds
+    pub modified: u64,
+
+    /// Block size in bytes, the last block may be shorter
+    pub block_size: u64,
+
+    /// Rolling checksum of each block
+    pub weak: Vec<u32>,
+
+    /// Strong checksum of each block
+    pub strong: Vec<String>,
+
+    /// SHA256 of whole file in upper hex, empty in signatures saved without it
+    #[serde(default)]
+    pub hash: String,
+}
+
+/// Struct DeltaStats
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeltaStats {
+    /// Bytes copied from old file
+    pub matched: u64,
+
+    /// Bytes written into patch as literal data
+    pub literal: u64,
+}
+
+/// Rolling checksum of window, as the one used by rsync.
+#[derive(Debug, Clone, Copy, Default)]
+struct Rolling {
+    a: u32,
+    b: u32,
+    len: u32,
+}
+
+impl Rolling {
+    fn new(data: &[u8]) -> Self {
+        let mut rolling = Rolling { a: 0, b: 0, len: data.len() as u32 };
+        for (idx, byte) in data.iter().enumerate() {
+            rolling.a = rolling.a.wrapping_add(*byte as u32);
+            rolling.b = rolling.b.wrapping_add((data.len() - idx) as u32 * (*byte as u32));
+        }
+        rolling
+    }
+
+    /// Slides window by one byte.
+    fn roll(&mut self, out: u8, into: u8) {
+        self.a = self.a.wrapping_sub(out as u32).wrapping_add(into as u32);
+        self.b = self.b.wrapping_sub(self.len.wrapping_mul(out as u32)).wrapping_add(self.a);
+    }
+
+    fn digest(&self) -> u32 {
+        (self.a & 0xffff) | ((self.b & 0xffff) << 16)
+    }
+}
+
+/// Strong checksum of block, truncated SHA256 in hex.
+fn strong_checksum(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    hex::encode(&Sha256::digest(data)[..16])
+}
+
+/// Gets block size of file, about square root of file size like rsync.
+fn block_size_of(size: u64) -> u64 {
+    let root = (size as f64).sqrt() as u64;
+    (root / 1024 * 1024).clamp(2048, 128 * 1024)
+}
+
+#[allow(dead_code)]
+/// Computes signature of file.
+/// 
+/// # Arguments
+/// 
+/// * `path` - A string that holds the file path
+/// 
+/// # Examples
+/// 
+/// ```
+/// use delta::file_signature;
+/// 
+/// let signature = file_signature("path\\to\\file").unwrap();
+/// println!("file has {} blocks", signature.weak.len());
+/// ```
+pub fn file_signature(path: &str) -> Result<FileSignature, std::io::Error> {
+    use sha2::{Digest, Sha256};
+    use std::fs::File;
+    use std::io::{BufReader, Read};
+    use std::time::UNIX_EPOCH;
+
+    let file = File::open(path)?;
+    let meta = file.metadata()?;
+    let mut signature = FileSignature {
+        size: meta.len(),
+        modified: meta.modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_secs()),
+        block_size: block_size_of(meta.len()),
+        weak: Vec::new(),
+        strong: Vec::new(),
+        hash: String::new(),
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut block = Vec::with_capacity(signature.block_size as usize);
+    let mut hasher = Sha256::new();
+    loop {
+        block.clear();
+        (&mut reader).take(signature.block_size).read_to_end(&mut block)?;
+        if block.is_empty() {
+            break;
+        }
+        hasher.update(&block);
+        signature.weak.push(Rolling::new(&block).digest());
+        signature.strong.push(strong_checksum(&block));
+    }
+    signature.hash = format!("{:X}", hasher.finalize());
+
+    Ok(signature)
+}
+
+/// Writes copy operation into patch.
+fn write_copy(patch: &mut impl std::io::Write, index: u64, count: u64) -> Result<(), std::io::Error> {
+    patch.write_all(&[1u8])?;
+    patch.write_all(&index.to_le_bytes())?;
+    patch.write_all(&count.to_le_bytes())
+}
+
+/// Writes literal data into patch.
+fn write_literal(patch: &mut impl std::io::Write, data: &[u8]) -> Result<(), std::io::Error> {
+    if data.is_empty() {
+        return Ok(());
+    }
+    patch.write_all(&[2u8])?;
+    patch.write_all(&(data.len() as u64).to_le_bytes())?;
+    patch.write_all(data)
+}
+
+#[allow(dead_code)]
+/// Writes patch that turns file of signature into new file.
+/// 
+/// # Arguments
+/// 
+/// * `signature` - Signature of old file
+/// * `from` - A string that holds the new file path
+/// * `patch` - A string that holds the patch path
+/// 
+/// # Examples
+/// 
+/// ```
+/// use delta::{file_signature, write_delta};
+/// 
+/// let signature = file_signature("path\\to\\old").unwrap();
+/// let stats = write_delta(&signature, "path\\to\\new", "path\\to\\new.patch").unwrap();
+/// println!("{} bytes matched, {} bytes literal", stats.matched, stats.literal);
+/// ```
+pub fn write_delta(signature: &FileSignature, from: &str, patch: &str) -> Result<DeltaStats, std::io::Error> {
+    use std::collections::HashMap;
+    use std::fs::File;
+    use std::io::{BufWriter, Read, Write};
+
+    let block_size = signature.block_size.max(1) as usize;
+    let last_len = match signature.size % block_size as u64 {
+        0 => block_size,
+        rest => rest as usize,
+    };
+    let mut blocks: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (idx, weak) in signature.weak.iter().enumerate() {
+        blocks.entry(*weak).or_default().push(idx);
+    }
+    let find_block = |window: &[u8], weak: u32| -> Option<usize> {
+        let candidates = blocks.get(&weak)?;
+        let strong = strong_checksum(window);
+        candidates.iter()
+            .find(|idx| {
+                let len = if **idx + 1 == signature.weak.len() { last_len } else { block_size };
+                len == window.len() && signature.strong[**idx] == strong
+            })
+            .copied()
+    };
+
+    let mut reader = File::open(from)?;
+    let mut writer = BufWriter::new(File::create(patch)?);
+    writer.write_all(PATCH_MAGIC)?;
+    writer.write_all(&(block_size as u64).to_le_bytes())?;
+
+    let mut stats = DeltaStats::default();
+    let mut buf: Vec<u8> = Vec::new();
+    let mut chunk = vec![0u8; LITERAL_LIMIT];
+    let mut eof = false;
+    let mut start = 0;
+    let mut literal_start = 0;
+    let mut rolling: Option<Rolling> = None;
+    let mut pending_copy: Option<(u64, u64)> = None;
+
+    loop {
+        // keep a whole window in buffer
+        while !eof && buf.len() < start + block_size {
+            let read = reader.read(&mut chunk)?;
+            if read == 0 {
+                eof = true;
+            }
+            buf.extend_from_slice(&chunk[..read]);
+        }
+        let end = (start + block_size).min(buf.len());
+        if start >= end {
+            break;
+        }
+
+        // short window at the end can only match the last block
+        if end - start < block_size && end - start != last_len {
+            if end - start < last_len {
+                break;
+            }
+            start = end - last_len;
+            rolling = None;
+            continue;
+        }
+
+        let window = &buf[start..end];
+        let weak = match (rolling, window.len() == block_size) {
+            (Some(rolling), true) => rolling,
+            _ => Rolling::new(window),
+        };
+        rolling = Some(weak);
+
+        if let Some(idx) = find_block(window, weak.digest()) {
+            // literal skipped to the last block goes between copies
+            if start > literal_start {
+                if let Some((index, count)) = pending_copy.take() {
+                    write_copy(&mut writer, index, count)?;
+                }
+                write_literal(&mut writer, &buf[literal_start..start])?;
+                stats.literal += (start - literal_start) as u64;
+            }
+            pending_copy = match pending_copy {
+                Some((index, count)) if index + count == idx as u64 => Some((index, count + 1)),
+                Some((index, count)) => {
+                    write_copy(&mut writer, index, count)?;
+                    Some((idx as u64, 1))
+                },
+                None => Some((idx as u64, 1)),
+            };
+            stats.matched += window.len() as u64;
+            start = end;
+            literal_start = start;
+            rolling = None;
+        } else {
+            if let Some((index, count)) = pending_copy.take() {
+                write_copy(&mut writer, index, count)?;
+            }
+            if end < buf.len() {
+                if let Some(rolling) = rolling.as_mut() {
+                    rolling.roll(buf[start], buf[end]);
+                }
+            } else {
+                rolling = None;
+            }
+            start += 1;
+        }
+
+        // flush long literal and drop consumed data
+        if start - literal_start >= LITERAL_LIMIT {
+            write_literal(&mut writer, &buf[literal_start..start])?;
+            stats.literal += (start - literal_start) as u64;
+            literal_start = start;
+        }
+        if literal_start >= LITERAL_LIMIT {
+            buf.drain(..literal_start);
+            start -= literal_start;
+            literal_start = 0;
+        }
+    }
+
+    if let Some((index, count)) = pending_copy {
+        write_copy(&mut writer, index, count)?;
+    }
+    write_literal(&mut writer, &buf[literal_start..])?;
+    stats.literal += (buf.len() - literal_start) as u64;
+    writer.write_all(&[0u8])?;
+    writer.flush()?;
+
+    Ok(stats)
+}
+
+#[allow(dead_code)]
+/// Applies patch on old file and writes the new file.
+/// 
+/// # Arguments
+/// 
+/// * `base` - A string that holds the old file path
+/// * `patch` - A string that holds the patch path
+/// * `to` - A string that holds the new file path
+/// 
+/// # Examples
+/// 
+/// ```
+/// use delta::apply_delta;
+/// 
+/// apply_delta("path\\to\\old", "path\\to\\new.patch", "path\\to\\new").unwrap();
+/// ```
+pub fn apply_delta(base: &str, patch: &str, to: &str) -> Result<(), std::io::Error> {
+    use std::fs::File;
+    use std::io::{BufReader, BufWriter, Error, ErrorKind, Read, Seek, SeekFrom, Write, copy};
+
+    let mut base = File::open(base)?;
+    let mut reader = BufReader::new(File::open(patch)?);
+    let mut writer = BufWriter::new(File::create(to)?);
+
+    let mut magic = [0u8; 8];
+    let mut number = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != PATCH_MAGIC {
+        return Err(Error::from(ErrorKind::InvalidData));
+    }
+    reader.read_exact(&mut number)?;
+    let block_size = u64::from_le_bytes(number);
+
+    loop {
+        let mut op = [0u8; 1];
+        reader.read_exact(&mut op)?;
+        match op[0] {
+            0 => break,
+            1 => {
+                reader.read_exact(&mut number)?;
+                let index = u64::from_le_bytes(number);
+                reader.read_exact(&mut number)?;
+                let count = u64::from_le_bytes(number);
+                base.seek(SeekFrom::Start(index * block_size))?;
+                copy(&mut (&mut base).take(count * block_size), &mut writer)?;
+            },
+            2 => {
+                reader.read_exact(&mut number)?;
+                let len = u64::from_le_bytes(number);
+                copy(&mut (&mut reader).take(len), &mut writer)?;
+            },
+            _ => {
+                return Err(Error::from(ErrorKind::InvalidData));
+            }
+        }
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_delta() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, write, read, remove_dir_all};
+
+        let test_path = current_dir().expect("").join("test_delta");
+        create_dir_all(&test_path).unwrap();
+        let old: Vec<u8> = (0..200_000u32).map(|idx| (idx.wrapping_mul(2654435761) >> 13) as u8).collect();
+        let mut new = old.clone();
+        new.splice(50_000..50_000, b"Hello world!".iter().cloned());
+        new.drain(120_000..121_000);
+        new.extend_from_slice(b"tail");
+        write(test_path.join("old.bin"), &old).unwrap();
+        write(test_path.join("new.bin"), &new).unwrap();
+
+        let path_of = |name: &str| test_path.join(name).display().to_string();
+        let signature = file_signature(&path_of("old.bin")).unwrap();
+        assert_eq!(signature.size, 200_000);
+        assert_eq!(signature.block_size, 2048);
+        assert_eq!(signature.hash, crate::utils::crypto::encode_sha2_file(&path_of("old.bin")).unwrap());
+
+        let stats = write_delta(&signature, &path_of("new.bin"), &path_of("new.patch")).unwrap();
+        assert_eq!(stats.matched + stats.literal, new.len() as u64);
+        // blocks around the insertion, the deletion and the changed last block
+        assert_eq!(stats.literal, 2048 + 12 + 2 * 2048 - 1000 + 1344 + 4);
+
+        apply_delta(&path_of("old.bin"), &path_of("new.patch"), &path_of("restored.bin")).unwrap();
+        assert_eq!(read(test_path.join("restored.bin")).unwrap(), new);
+
+        // literal after the copied blocks at the end
+        let mut appended = old[..198_656].to_vec();
+        appended.extend_from_slice(b"tail");
+        write(test_path.join("appended.bin"), &appended).unwrap();
+        let stats = write_delta(&signature, &path_of("appended.bin"), &path_of("appended.patch")).unwrap();
+        assert_eq!(stats.literal, 4);
+        apply_delta(&path_of("old.bin"), &path_of("appended.patch"), &path_of("restored.bin")).unwrap();
+        assert_eq!(read(test_path.join("restored.bin")).unwrap(), appended);
+
+        let _ = remove_dir_all(test_path).unwrap();
+    }
+}
@@ -183,6 +183,168 @@ pub fn create_all(path: &str, meta_type: &str) -> Result<(), std::io::Error> {
     Ok(())
 }
 
+/// Item skipped because of error when copying.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct SkippedItem {
+    /// Path of source item
+    pub path: String,
+
+    /// Why item is skipped
+    pub reason: String,
+}
+
+/// Count of files copied by each method.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct CopyStats {
+    /// Files cloned with reflink, sharing data blocks with source
+    pub reflinked: u64,
+
+    /// Files copied byte by byte
+    pub copied: u64,
+
+    /// Items skipped by error policy, see [run_with_policy]
+    #[serde(default)]
+    pub skipped: Vec<SkippedItem>,
+}
+
+impl CopyStats {
+    /// Adds counts of another copy.
+    pub fn merge(&mut self, other: CopyStats) {
+        self.reflinked += other.reflinked;
+        self.copied += other.copied;
+        self.skipped.extend(other.skipped);
+    }
+
+    /// Gets method used by all files, `reflink`, `copy`, `mixed`, or empty if nothing copied.
+    pub fn method(&self) -> String {
+        match (self.reflinked, self.copied) {
+            (0, 0) => "",
+            (_, 0) => "reflink",
+            (0, _) => "copy",
+            _ => "mixed",
+        }.to_string()
+    }
+}
+
+#[allow(dead_code)]
+/// Copies file, clones it with reflink when source and save path share a copy-on-write filesystem.
+/// 
+/// Existing file in save path is replaced. Falls back to regular copy otherwise.
+/// Symbolic links are copied as links, metadata is kept, see [copy_metadata](super::metadata::copy_metadata).
+/// Special files like fifo, socket or device are refused with [ErrorKind::Unsupported](std::io::ErrorKind::Unsupported).
+/// 
+/// # Arguments
+/// 
+/// * `from` - Path of source file
+/// * `to` - Path of save file
+/// 
+/// # Examples
+/// 
+/// ```
+/// use meta::copy_file;
+/// 
+/// let stats = copy_file("to\\copy\\file", "to\\save\\file").unwrap();
+/// println!("file copied by {}", stats.method());
+/// ```
+pub fn copy_file(from: impl AsRef<std::path::Path>, to: impl AsRef<std::path::Path>) -> Result<CopyStats, std::io::Error> {
+    use super::throttle::RateLimiter;
+
+    copy_file_limited(from, to, &RateLimiter::default(), &RateLimiter::default())
+}
+
+#[allow(dead_code)]
+/// Copies file like [copy_file], with limited bytes per second of reading and writing.
+/// 
+/// Limiters can be shared by files copied at the same time. Reflink is still used if
+/// possible, as it reads and writes no data.
+/// 
+/// # Arguments
+/// 
+/// * `from` - Path of source file
+/// * `to` - Path of save file
+/// * `read` - Limiter of reading source file
+/// * `write` - Limiter of writing save file
+/// 
+/// # Examples
+/// 
+/// ```
+/// use meta::copy_file_limited;
+/// use throttle::RateLimiter;
+/// 
+/// let limiter = RateLimiter::new(10 * 1024 * 1024);
+/// copy_file_limited("to\\copy\\file", "to\\save\\file", &limiter, &RateLimiter::default()).unwrap();
+/// ```
+pub fn copy_file_limited(
+    from: impl AsRef<std::path::Path>,
+    to: impl AsRef<std::path::Path>,
+    read: &super::throttle::RateLimiter,
+    write: &super::throttle::RateLimiter
+) -> Result<CopyStats, std::io::Error> {
+    use std::fs::remove_file;
+    use std::io::{Error, ErrorKind};
+    use super::metadata::{copy_metadata, copy_symlink};
+
+    // reflink only creates new file
+    if to.as_ref().symlink_metadata().map(|meta| !meta.is_dir()).unwrap_or(false) {
+        remove_file(&to)?;
+    }
+
+    let file_type = from.as_ref().symlink_metadata()?.file_type();
+    if file_type.is_symlink() {
+        copy_symlink(&from, &to)?;
+        copy_metadata(&from, &to)?;
+        return Ok(CopyStats { copied: 1, ..CopyStats::default() });
+    }
+    // reading fifo or device blocks or never ends
+    if !file_type.is_file() {
+        return Err(Error::new(ErrorKind::Unsupported, "special file"));
+    }
+
+    let stats = if read.is_limited() || write.is_limited() {
+        match reflink_copy::reflink(&from, &to) {
+            Ok(()) => CopyStats { reflinked: 1, ..CopyStats::default() },
+            Err(_) => {
+                copy_content_limited(from.as_ref(), to.as_ref(), read, write)?;
+                CopyStats { copied: 1, ..CopyStats::default() }
+            },
+        }
+    } else {
+        match reflink_copy::reflink_or_copy(&from, &to)? {
+            None => CopyStats { reflinked: 1, ..CopyStats::default() },
+            Some(_) => CopyStats { copied: 1, ..CopyStats::default() },
+        }
+    };
+    copy_metadata(&from, &to)?;
+
+    Ok(stats)
+}
+
+/// Copies content of file chunk by chunk, each chunk waits for its turn in limiters.
+fn copy_content_limited(
+    from: &std::path::Path,
+    to: &std::path::Path,
+    read: &super::throttle::RateLimiter,
+    write: &super::throttle::RateLimiter
+) -> Result<(), std::io::Error> {
+    use std::fs::File;
+    use std::io::{Read, Write};
+
+    let mut reader = File::open(from)?;
+    let mut writer = File::create(to)?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let count = reader.read(&mut buffer)?;
+        if count == 0 {
+            break;
+        }
+        read.consume(count as u64);
+        writer.write_all(&buffer[..count])?;
+        write.consume(count as u64);
+    }
+
+    writer.flush()
+}
+
 #[allow(dead_code)]
 /// Recursively copies the whole directory.
 /// 
@@ -200,25 +362,31 @@ pub fn create_all(path: &str, meta_type: &str) -> Result<(), std::io::Error> {
 /// 
 /// copy_dir_all("to\\copy\\directory", "to\\save\\directory").unwrap();
 /// ```
-fn copy_dir_all(from: impl AsRef<std::path::Path>, to: impl AsRef<std::path::Path>) -> Result<(), std::io::Error> {
+fn copy_dir_all(from: impl AsRef<std::path::Path>, to: impl AsRef<std::path::Path>) -> Result<CopyStats, std::io::Error> {
     use std::fs;
+    use super::metadata::copy_metadata;
     
+    let mut stats = CopyStats::default();
     fs::create_dir_all(&to)?;
-    for entry in fs::read_dir(from)? {
+    for entry in fs::read_dir(&from)? {
         let entry = entry?;
         let ty = entry.file_type()?;
         if ty.is_dir() {
-            copy_dir_all(entry.path(), to.as_ref().join(entry.file_name()))?;
+            stats.merge(copy_dir_all(entry.path(), to.as_ref().join(entry.file_name()))?);
         } else {
-            fs::copy(entry.path(), to.as_ref().join(entry.file_name()))?;
+            stats.merge(copy_file(entry.path(), to.as_ref().join(entry.file_name()))?);
         }
     }
-    Ok(())
+    // Directory times change when its items are written
+    copy_metadata(&from, &to)?;
+    Ok(stats)
 }
 
 #[allow(dead_code)]
 /// Copies the whole file or directory.
 /// 
+/// Files are cloned with reflink if possible, see [copy_file].
+/// 
 /// # Arguments
 /// 
 /// * `from` - A string that holds the source path of file or directory
@@ -232,10 +400,10 @@ fn copy_dir_all(from: impl AsRef<std::path::Path>, to: impl AsRef<std::path::Pat
 /// copy_all("to\\copy\\file", "to\\save\\file").unwrap();
 /// copy_all("to\\copy\\directory", "to\\save\\directory").unwrap();
 /// ```
-pub fn copy_all(from: &str, to: &str) -> Result<(), std::io::Error> {
+pub fn copy_all(from: &str, to: &str) -> Result<CopyStats, std::io::Error> {
     use std::path::Path;
     use std::io::{Error, ErrorKind};
-    use std::fs::{copy, OpenOptions, create_dir_all};
+    use std::fs::create_dir_all;
     
     let src = Path::new(from);
     let save = Path::new(to);
@@ -246,107 +414,772 @@ pub fn copy_all(from: &str, to: &str) -> Result<(), std::io::Error> {
         return Err(Error::from(ErrorKind::AlreadyExists));
     }
 
-    if src.is_file() {
-        if !save.exists() {
-            if let Some(prefix) = save.parent() {
-                if !prefix.exists() {
-                    create_dir_all(prefix)?;
-                }
+    let mut stats = CopyStats::default();
+    if src.is_file() || src.is_symlink() {
+        if let Some(prefix) = save.parent() {
+            if !prefix.exists() {
+                create_dir_all(prefix)?;
             }
-
-            let _ = OpenOptions::new().write(true)
-                        .create_new(true)
-                        .open(to)?;
         }
 
-        copy(from, to)?;
+        stats = copy_file(from, to)?;
     } else if src.is_dir() {
-        copy_dir_all(from, to)?;
+        stats = copy_dir_all(from, to)?;
+    }
+
+    Ok(stats)
+}
+
+#[allow(dead_code)]
+/// Copies metadata of walked directories to their copies, deepest first.
+/// 
+/// Directories not created in save path are skipped.
+/// 
+/// # Arguments
+/// 
+/// * `dirs` - Source and save path of each directory, in walk order
+fn set_dirs_metadata(dirs: &[(std::path::PathBuf, std::path::PathBuf)]) -> Result<(), std::io::Error> {
+    use super::metadata::copy_metadata;
+
+    for (from, to) in dirs.iter().rev() {
+        if to.is_dir() {
+            copy_metadata(from, to)?;
+        }
     }
 
     Ok(())
 }
 
 #[allow(dead_code)]
-/// Copies the whole directory with build in .gitignore file.
+/// Copies the whole file or directory into the save path, overwriting existing items.
+/// 
+/// Items only exist in save path are kept.
 /// 
 /// # Arguments
 /// 
-/// * `from` - A string that holds the source path of  directory
-/// * `to` - A string that holds the save path of directory
+/// * `from` - A string that holds the source path of file or directory
+/// * `to` - A string that holds the save path of file or directory
 /// 
 /// # Examples
 /// 
 /// ```
-/// use meta::copy_dir_with_build_in_ignore;
+/// use meta::merge_all;
 /// 
-/// copy_dir_with_build_in_ignore("to\\copy\\directory", "to\\save\\directory").unwrap();
+/// merge_all("to\\copy\\directory", "to\\existed\\directory").unwrap();
 /// ```
-pub fn copy_dir_with_build_in_ignore(from: &str, to: &str) -> Result<(), std::io::Error> {
-    use ignore::Walk;
+pub fn merge_all(from: &str, to: &str) -> Result<(), std::io::Error> {
+    use walkdir::WalkDir;
     use std::path::Path;
     use std::io::{Error, ErrorKind};
-    use std::fs::{copy, create_dir_all};
+    use std::fs::create_dir_all;
 
     let src = Path::new(from);
     let save = Path::new(to);
     if !src.exists() {
         return Err(Error::from(ErrorKind::NotFound));
     }
-    if save.exists() {
-        return Err(Error::from(ErrorKind::AlreadyExists));
+
+    let mut dirs = Vec::new();
+    for entry in WalkDir::new(src) {
+        let entry = entry.map_err(Error::other)?;
+        let entry_save = match entry.path().strip_prefix(src) {
+            Ok(relative) if !relative.as_os_str().is_empty() => save.join(relative),
+            _ => save.to_path_buf(),
+        };
+        if entry.file_type().is_dir() {
+            create_dir_all(&entry_save)?;
+            dirs.push((entry.path().to_path_buf(), entry_save));
+        } else {
+            if entry_save.is_dir() {
+                remove_all(entry_save.display().to_string().as_str())?;
+            }
+            if let Some(prefix) = entry_save.parent() {
+                create_dir_all(prefix)?;
+            }
+            copy_file(entry.path(), &entry_save)?;
+        }
     }
+    set_dirs_metadata(&dirs)?;
 
-    for result in Walk::new(src) {
-        match result {
-            Ok(entry) => {
-                let cur_entry_path = entry.path();
-                let entry_save = save.join(cur_entry_path.strip_prefix(from).unwrap_or(src));
-                let entry_save_path = entry_save.as_path();
-
-                if cur_entry_path.is_file() {
-                    let _ = copy(cur_entry_path, entry_save_path)?;
-                } else if cur_entry_path.is_dir() {
-                    let _ = create_dir_all(entry_save_path)?;
-
-                    let ignore_file = cur_entry_path.join(".gitignore");
-                    if ignore_file.exists() {
-                        let _ = copy(ignore_file, entry_save_path.join(".gitignore"))?;
-                    }
+    Ok(())
+}
+
+/// Rules to filter items when copying directory.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    /// Whether respect .gitignore and .ignore files in directory
+    pub build_in: bool,
+
+    /// Custom exclude patterns in gitignore syntax
+    pub excludes: Vec<String>,
+
+    /// Custom include patterns in gitignore syntax, only matched files will be copied if not empty
+    pub includes: Vec<String>,
+
+    /// Skip files larger than this size in bytes, `0` means no limit
+    pub max_size: u64,
+
+    /// Skip files not modified within these days, `0` means no limit
+    pub max_days: u64,
+
+    /// How error of single item is handled, see [run_with_policy]
+    /// 
+    /// `0` - abort
+    /// 
+    /// `1` - skip and record
+    /// 
+    /// `2` - retry, then skip and record
+    pub on_error: i16,
+
+    /// Times to retry failed item when `on_error` is `2`
+    pub retries: u32,
+
+    /// Count of threads copying files, `0` means by available cpus, `1` means no extra thread
+    pub workers: usize,
+
+    /// Bytes per second read from sources by all copy threads, `0` means no limit
+    pub read_limit: u64,
+
+    /// Bytes per second written to save path by all copy threads, `0` means no limit
+    pub write_limit: u64,
+}
+
+impl IgnoreRules {
+    /// Whether no rule will filter any item.
+    pub fn is_empty(&self) -> bool {
+        !self.build_in
+            && self.excludes.is_empty()
+            && self.includes.is_empty()
+            && self.max_size == 0
+            && self.max_days == 0
+    }
+
+    /// Reason if file is skipped by the size or age filter.
+    fn filter_reason(&self, meta: &std::fs::Metadata) -> Option<String> {
+        use std::time::Duration;
+
+        if self.max_size > 0 && meta.len() > self.max_size {
+            return Some(format!("size: larger than {} bytes", self.max_size));
+        }
+
+        if self.max_days > 0 {
+            if let Ok(Ok(elapsed)) = meta.modified().map(|time| time.elapsed()) {
+                if elapsed > Duration::from_secs(self.max_days * 24 * 60 * 60) {
+                    return Some(format!("age: not modified in {} days", self.max_days));
                 }
+            }
+        }
+
+        None
+    }
+}
+
+#[allow(dead_code)]
+/// Runs action on single item with error policy of rules.
+/// 
+/// Returns `None` if the item is skipped, the reason is recorded in `skipped`.
+/// Error is returned as it is if policy is abort.
+/// 
+/// # Arguments
+/// 
+/// * `rules` - Rules that holds error policy
+/// * `path` - Path of item
+/// * `skipped` - Skipped items
+/// * `action` - Action on item, called again on retry
+/// 
+/// # Examples
+/// 
+/// ```
+/// use meta::{IgnoreRules, run_with_policy};
+/// 
+/// let rules = IgnoreRules { on_error: 2, retries: 3, ..IgnoreRules::default() };
+/// let mut skipped = Vec::new();
+/// let copied = run_with_policy(&rules, "to\\copy\\file", &mut skipped, || std::fs::copy("to\\copy\\file", "to\\save\\file")).unwrap();
+/// if copied.is_none() {
+///     println!("skipped: {:?}", skipped);
+/// }
+/// ```
+pub fn run_with_policy<T, F>(
+    rules: &IgnoreRules,
+    path: impl AsRef<std::path::Path>,
+    skipped: &mut Vec<SkippedItem>,
+    mut action: F
+) -> Result<Option<T>, std::io::Error>
+where
+    F: FnMut() -> Result<T, std::io::Error>,
+{
+    use std::thread::sleep;
+    use std::time::Duration;
+    use log::warn;
+
+    let mut attempt = 0;
+    loop {
+        let error = match action() {
+            Ok(value) => return Ok(Some(value)),
+            Err(error) => error,
+        };
+
+        match rules.on_error {
+            1 => {},
+            2 if attempt < rules.retries => {
+                attempt += 1;
+                sleep(Duration::from_millis(500 * attempt as u64));
+                continue;
             },
-            Err(_error) => {
-                // println!("Failed to parse ignore file in dir {}, errMsg: {:?}", from, error);
-                return Err(Error::from(ErrorKind::InvalidData));
+            2 => {},
+            _ => return Err(error),
+        }
+
+        warn!("skip {}, errMsg: {:?}", path.as_ref().display(), error);
+        skipped.push(SkippedItem {
+            path: path.as_ref().display().to_string(),
+            reason: error.to_string(),
+        });
+        return Ok(None);
+    }
+}
+
+/// Upper limit of copy threads when count is decided by available cpus.
+const MAX_COPY_WORKERS: usize = 8;
+
+#[allow(dead_code)]
+/// Copies files queued by `produce` with a bounded pool of threads.
+/// 
+/// `produce` gets a callback to queue a file copy from source path to save path, the parent
+/// directory of save path must exist. Queue holds a few files per thread, so `produce` waits
+/// when threads fall behind instead of piling up pending copies. All threads share the read and
+/// write limits of rules. Errors are handled by error policy of rules, see [run_with_policy],
+/// counts of each copy are added to `stats`.
+/// 
+/// Returns result of `produce`, or the first error that aborts copying.
+/// 
+/// # Arguments
+/// 
+/// * `rules` - Rules that holds count of threads and error policy
+/// * `stats` - Counts of copies
+/// * `produce` - Queues files to copy
+/// 
+/// # Examples
+/// 
+/// ```
+/// use meta::{IgnoreRules, CopyStats, copy_in_pool};
+/// use std::path::PathBuf;
+/// 
+/// let rules = IgnoreRules { workers: 4, ..IgnoreRules::default() };
+/// let mut stats = CopyStats::default();
+/// copy_in_pool(&rules, &mut stats, |copy| {
+///     copy(PathBuf::from("to\\copy\\a.txt"), PathBuf::from("to\\save\\a.txt"))?;
+///     copy(PathBuf::from("to\\copy\\b.txt"), PathBuf::from("to\\save\\b.txt"))
+/// }).unwrap();
+/// ```
+pub fn copy_in_pool<T, F>(rules: &IgnoreRules, stats: &mut CopyStats, produce: F) -> Result<T, std::io::Error>
+where
+    F: FnOnce(&mut dyn FnMut(std::path::PathBuf, std::path::PathBuf) -> Result<(), std::io::Error>) -> Result<T, std::io::Error>,
+{
+    use std::path::PathBuf;
+    use std::sync::{Mutex, mpsc::sync_channel};
+    use std::io::{Error, ErrorKind};
+    use std::thread;
+    use super::throttle::RateLimiter;
+
+    let read = RateLimiter::new(rules.read_limit);
+    let write = RateLimiter::new(rules.write_limit);
+    let workers = match rules.workers {
+        0 => thread::available_parallelism().map_or(1, |count| count.get()).min(MAX_COPY_WORKERS),
+        count => count,
+    };
+    if workers <= 1 {
+        return produce(&mut |from, to| {
+            if let Some(copied) = run_with_policy(rules, &from, &mut stats.skipped, || copy_file_limited(&from, &to, &read, &write))? {
+                stats.merge(copied);
+            }
+            Ok(())
+        });
+    }
+
+    let (sender, receiver) = sync_channel::<(PathBuf, PathBuf)>(workers * 4);
+    let receiver = Mutex::new(receiver);
+    let failure: Mutex<Option<Error>> = Mutex::new(None);
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers).map(|_| scope.spawn(|| {
+            let mut stats = CopyStats::default();
+            loop {
+                let job = match receiver.lock() {
+                    Ok(receiver) => receiver.recv(),
+                    Err(_) => break,
+                };
+                let (from, to) = match job {
+                    Ok(job) => job,
+                    Err(_) => break,
+                };
+
+                // keep draining queue after failure, so producer is never blocked
+                if failure.lock().map_or(true, |failure| failure.is_some()) {
+                    continue;
+                }
+                match run_with_policy(rules, &from, &mut stats.skipped, || copy_file_limited(&from, &to, &read, &write)) {
+                    Ok(Some(copied)) => stats.merge(copied),
+                    Ok(None) => {},
+                    Err(error) => {
+                        if let Ok(mut failure) = failure.lock() {
+                            failure.get_or_insert(error);
+                        }
+                    },
+                }
+            }
+            stats
+        })).collect();
+
+        let produced = produce(&mut |from, to| {
+            if failure.lock().map_or(true, |failure| failure.is_some()) {
+                return Err(Error::new(ErrorKind::Interrupted, "copy aborted"));
             }
+            sender.send((from, to)).map_err(|error| Error::new(ErrorKind::BrokenPipe, error.to_string()))
+        });
+        drop(sender);
+
+        for handle in handles {
+            match handle.join() {
+                Ok(copied) => stats.merge(copied),
+                Err(_) => return Err(Error::other("copy thread panicked")),
+            }
+        }
+        if let Some(error) = failure.lock().ok().and_then(|mut failure| failure.take()) {
+            return Err(error);
+        }
+
+        produced
+    })
+}
+
+#[allow(dead_code)]
+/// Copies the whole directory with build in .gitignore file.
+/// 
+/// # Arguments
+/// 
+/// * `from` - A string that holds the source path of  directory
+/// * `to` - A string that holds the save path of directory
+/// 
+/// # Examples
+/// 
+/// ```
+/// use meta::copy_dir_with_build_in_ignore;
+/// 
+/// copy_dir_with_build_in_ignore("to\\copy\\directory", "to\\save\\directory").unwrap();
+/// ```
+pub fn copy_dir_with_build_in_ignore(from: &str, to: &str) -> Result<(), std::io::Error> {
+    let rules = IgnoreRules {
+        build_in: true,
+        ..IgnoreRules::default()
+    };
+
+    copy_dir_with_rules(from, to, &rules).map(|_| ())
+}
+
+
+#[allow(dead_code)]
+/// Builds matcher for custom ignore patterns.
+/// 
+/// Patterns follow the gitignore syntax and are relative to `root`,
+/// including negation `!`, anchored `/` and `**`.
+/// 
+/// # Arguments
+/// 
+/// * `root` - A string that holds the root path of patterns
+/// * `ignores` - A vec that contains the custom ignore patterns
+/// 
+/// # Examples
+/// 
+/// ```
+/// use meta::build_ignore_matcher;
+/// 
+/// let ignores = vec!["*.log".to_string(), "!keep.log".to_string()];
+/// let matcher = build_ignore_matcher("to\\copy\\directory", &ignores).unwrap();
+/// assert_eq!(matcher.matched("to\\copy\\directory\\debug.log", false).is_ignore(), true);
+/// assert_eq!(matcher.matched("to\\copy\\directory\\keep.log", false).is_ignore(), false);
+/// ```
+pub fn build_ignore_matcher(root: &str, ignores: &[String]) -> Result<ignore::gitignore::Gitignore, std::io::Error> {
+    use ignore::gitignore::GitignoreBuilder;
+    use std::io::{Error, ErrorKind};
+    use log::error;
+
+    let mut builder = GitignoreBuilder::new(root);
+    for item in ignores.iter() {
+        let pattern = item.trim();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            continue;
+        }
+
+        if let Err(error) = builder.add_line(None, pattern) {
+            error!("invalid ignore pattern {}, errMsg: {:?}", pattern, error);
+            return Err(Error::from(ErrorKind::InvalidInput));
         }
     }
 
-    Ok(())
+    match builder.build() {
+        Ok(matcher) => Ok(matcher),
+        Err(error) => {
+            error!("failed to build ignore matcher, errMsg: {:?}", error);
+            Err(Error::from(ErrorKind::InvalidInput))
+        }
+    }
 }
 
 #[allow(dead_code)]
-/// Copies the whole directory with custom ignore strings.
+/// Copies the whole directory with custom ignore patterns.
+/// 
+/// See [build_ignore_matcher] for the pattern syntax.
 /// 
 /// # Arguments
 /// 
 /// * `from` - A string that holds the source path of  directory
 /// * `to` - A string that holds the save path of directory
-/// * `ignores` - A vec that contains the custom ignore strings
+/// * `ignores` - A vec that contains the custom ignore patterns
 /// 
 /// # Examples
 /// 
 /// ```
 /// use meta::copy_dir_with_custom_ignores;
 /// 
-/// let ignores = vec!["debug", "bin", "target"];
+/// let ignores = vec!["debug/", "/bin", "**/target"];
 /// copy_dir_with_custom_ignores("to\\copy\\directory", "to\\save\\directory", ignores).unwrap();
 /// ```
 pub fn copy_dir_with_custom_ignores(from: &str, to: &str, ignores: &Vec<String>) -> Result<(), std::io::Error> {
-    use walkdir::WalkDir;
+    let rules = IgnoreRules {
+        excludes: ignores.clone(),
+        ..IgnoreRules::default()
+    };
+
+    copy_dir_with_rules(from, to, &rules).map(|_| ())
+}
+
+#[allow(dead_code)]
+/// Builds matcher for custom include patterns.
+/// 
+/// Files not matched by any pattern will be ignored, directories are always walked.
+/// 
+/// # Arguments
+/// 
+/// * `root` - A string that holds the root path of patterns
+/// * `includes` - A vec that contains the custom include patterns
+/// 
+/// # Examples
+/// 
+/// ```
+/// use meta::build_include_matcher;
+/// 
+/// let includes = vec!["*.psd".to_string(), "*.blend".to_string()];
+/// let matcher = build_include_matcher("to\\copy\\directory", &includes).unwrap();
+/// assert_eq!(matcher.matched("to\\copy\\directory\\scene.blend", false).is_ignore(), false);
+/// assert_eq!(matcher.matched("to\\copy\\directory\\notes.txt", false).is_ignore(), true);
+/// ```
+pub fn build_include_matcher(root: &str, includes: &[String]) -> Result<ignore::overrides::Override, std::io::Error> {
+    use ignore::overrides::OverrideBuilder;
+    use std::io::{Error, ErrorKind};
+    use log::error;
+
+    // Override globs are whitelists already, so patterns are added as they are.
+    let mut builder = OverrideBuilder::new(root);
+    for item in includes.iter() {
+        let pattern = item.trim();
+        if pattern.is_empty() || pattern.starts_with('#') {
+            continue;
+        }
+
+        if let Err(error) = builder.add(pattern) {
+            error!("invalid include pattern {}, errMsg: {:?}", pattern, error);
+            return Err(Error::from(ErrorKind::InvalidInput));
+        }
+    }
+
+    match builder.build() {
+        Ok(matcher) => Ok(matcher),
+        Err(error) => {
+            error!("failed to build include matcher, errMsg: {:?}", error);
+            Err(Error::from(ErrorKind::InvalidInput))
+        }
+    }
+}
+
+#[allow(dead_code)]
+/// Builds matcher for .gitignore and .ignore files in directory.
+/// 
+/// Patterns in .ignore file take precedence over .gitignore file.
+fn build_dir_matcher(dir: &std::path::Path) -> Option<ignore::gitignore::Gitignore> {
+    use ignore::gitignore::GitignoreBuilder;
+    use log::warn;
+
+    let mut builder = GitignoreBuilder::new(dir);
+    let mut has_file = false;
+    for name in [".gitignore", ".ignore"] {
+        let file = dir.join(name);
+        if !file.is_file() {
+            continue;
+        }
+
+        has_file = true;
+        if let Some(error) = builder.add(&file) {
+            warn!("failed to parse ignore file {}, errMsg: {:?}", file.display(), error);
+        }
+    }
+
+    if !has_file {
+        return None;
+    }
+
+    builder.build().ok().filter(|matcher| !matcher.is_empty())
+}
+
+/// Directory walked with ignore rules, with its children not yielded by walker yet.
+struct WalkFrame {
+    /// Depth of directory in walk
+    depth: usize,
+
+    /// Path of directory
+    path: std::path::PathBuf,
+
+    /// Children of directory not yielded by walker yet
+    unseen: std::collections::BTreeMap<std::ffi::OsString, std::fs::FileType>,
+
+    /// Matcher of .gitignore and .ignore files in directory, built when first needed
+    matcher: Option<Option<ignore::gitignore::Gitignore>>,
+}
+
+impl WalkFrame {
+    /// Lists children of directory, nothing is listed if directory can not be read.
+    fn new(depth: usize, path: &std::path::Path) -> Self {
+        let mut unseen = std::collections::BTreeMap::new();
+        if let Ok(entries) = std::fs::read_dir(path) {
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                if let Ok(file_type) = entry.file_type() {
+                    unseen.insert(entry.file_name(), file_type);
+                }
+            }
+        }
+
+        Self { depth, path: path.to_path_buf(), unseen, matcher: None }
+    }
+}
+
+/// Finds the ignore file pattern that excluded item, searching from the nearest directory.
+fn ignore_file_reason(frames: &mut [WalkFrame], path: &std::path::Path, is_dir: bool) -> String {
+    use ignore::Match;
+
+    for frame in frames.iter_mut().rev() {
+        let matcher = frame.matcher.get_or_insert_with(|| build_dir_matcher(&frame.path));
+        match matcher.as_ref().map(|matcher| matcher.matched(path, is_dir)) {
+            Some(Match::Ignore(glob)) => {
+                let file = glob.from().map_or(String::new(), |file| file.display().to_string());
+                return format!("{}: {}", file, glob.original());
+            },
+            Some(Match::Whitelist(_)) => break,
+            _ => {},
+        }
+    }
+
+    // excluded by ignore files out of directory, like parents or .git/info/exclude
+    "gitignore".to_string()
+}
+
+/// Closes frames not deeper than `depth`, children they never yielded are excluded by ignore files.
+fn close_walk_frames<F>(frames: &mut Vec<WalkFrame>, depth: usize, visit: &mut F) -> Result<(), std::io::Error>
+where
+    F: FnMut(&std::path::Path, std::fs::FileType, Option<String>) -> Result<(), std::io::Error>,
+{
+    while frames.last().is_some_and(|frame| frame.depth >= depth) {
+        let last = frames.len() - 1;
+        let unseen = std::mem::take(&mut frames[last].unseen);
+        for (name, file_type) in unseen {
+            let path = frames[last].path.join(name);
+            let reason = ignore_file_reason(frames, &path, file_type.is_dir());
+            visit(&path, file_type, Some(reason))?;
+        }
+        frames.pop();
+    }
+
+    Ok(())
+}
+
+/// Marks item as yielded by walker in frame of its parent.
+fn mark_walk_frame(frames: &mut [WalkFrame], path: &std::path::Path, depth: usize) {
+    if let (Some(frame), Some(name)) = (frames.last_mut(), path.file_name()) {
+        if frame.depth + 1 == depth {
+            frame.unseen.remove(name);
+        }
+    }
+}
+
+/// Path of item that walker failed to read.
+fn walk_error_path(error: &ignore::Error) -> Option<&std::path::Path> {
+    match error {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::WithDepth { err, .. } => walk_error_path(err),
+        ignore::Error::Loop { child, .. } => Some(child),
+        _ => None,
+    }
+}
+
+#[allow(dead_code)]
+/// Walks the directory with ignore rules.
+/// 
+/// Rules are layered in order: .gitignore and .ignore files, custom excludes, custom includes, 
+/// then size and age filters. Ignore files are applied by [ignore::WalkBuilder], hidden items 
+/// are walked as any other item. `visit` is called with every kept item and the reason 
+/// of every excluded item, excluded directories are not walked into.
+/// 
+/// Items failed to read are returned as skipped unless error policy of rules is abort.
+/// 
+/// # Arguments
+/// 
+/// * `from` - A string that holds the path of directory
+/// * `rules` - Rules to filter items
+/// * `visit` - Callback with the path, the file type and the reason if excluded
+/// 
+/// # Examples
+/// 
+/// ```
+/// use meta::{IgnoreRules, walk_dir_with_rules};
+/// 
+/// let rules = IgnoreRules { build_in: true, ..IgnoreRules::default() };
+/// walk_dir_with_rules("to\\walk\\directory", &rules, |path, _, reason| {
+///     println!("{} excluded by {:?}", path.display(), reason);
+///     Ok(())
+/// }).unwrap();
+/// ```
+pub fn walk_dir_with_rules<F>(from: &str, rules: &IgnoreRules, mut visit: F) -> Result<Vec<SkippedItem>, std::io::Error>
+where
+    F: FnMut(&std::path::Path, std::fs::FileType, Option<String>) -> Result<(), std::io::Error>,
+{
+    use ignore::{Match, WalkBuilder};
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+    use std::io::{Error, ErrorKind};
+    use log::warn;
+
+    let src = Path::new(from);
+    if !src.exists() {
+        return Err(Error::from(ErrorKind::NotFound));
+    }
+
+    let excludes = build_ignore_matcher(from, &rules.excludes)?;
+    let includes = build_include_matcher(from, &rules.includes)?;
+
+    // items excluded by custom rules in `filter_entry`, reported before the next walked item
+    let excluded: Arc<Mutex<Vec<(ignore::DirEntry, String)>>> = Arc::default();
+
+    // Keeps the standard filters of `ignore::Walk` when respecting ignore files, except for
+    // hidden items, and the .gitignore files no longer need a git repository.
+    let mut builder = WalkBuilder::new(src);
+    builder.standard_filters(rules.build_in).hidden(false).require_git(false);
+    let filter_rules = rules.clone();
+    let filter_excluded = excluded.clone();
+    builder.filter_entry(move |entry| {
+        let Some(file_type) = entry.file_type() else { return true };
+        if entry.depth() == 0 {
+            return true;
+        }
+
+        let path = entry.path();
+        let is_dir = file_type.is_dir();
+        let mut reason = None;
+        if let Match::Ignore(glob) = excludes.matched(path, is_dir) {
+            reason = Some(format!("exclude: {}", glob.original()));
+        } else if !is_dir {
+            if includes.matched(path, false).is_ignore() {
+                reason = Some("include: no pattern matched".to_string());
+            } else if let Ok(meta) = entry.metadata() {
+                reason = filter_rules.filter_reason(&meta);
+            }
+        }
+
+        match reason {
+            Some(reason) => {
+                if let Ok(mut items) = filter_excluded.lock() {
+                    items.push((entry.clone(), reason));
+                }
+                false
+            },
+            None => true,
+        }
+    });
+
+    // directories being walked, to find the items excluded by ignore files
+    let mut frames: Vec<WalkFrame> = Vec::new();
+    let mut skipped: Vec<SkippedItem> = Vec::new();
+    let mut walker = builder.build();
+    loop {
+        let result = walker.next();
+        let pending = match excluded.lock() {
+            Ok(mut items) => std::mem::take(&mut *items),
+            Err(_) => Vec::new(),
+        };
+        for (entry, reason) in pending {
+            let Some(file_type) = entry.file_type() else { continue };
+            close_walk_frames(&mut frames, entry.depth(), &mut visit)?;
+            mark_walk_frame(&mut frames, entry.path(), entry.depth());
+            visit(entry.path(), file_type, Some(reason))?;
+        }
+
+        let entry = match result {
+            Some(Ok(entry)) => entry,
+            Some(Err(error)) if error.io_error().is_none() => {
+                warn!("failed to parse ignore file in {}, errMsg: {:?}", from, error);
+                continue;
+            },
+            Some(Err(error)) if rules.on_error != 0 => {
+                let path = walk_error_path(&error).map_or(from.to_string(), |path| path.display().to_string());
+                skipped.push(SkippedItem { path, reason: error.to_string() });
+                continue;
+            },
+            Some(Err(error)) => return Err(Error::other(error)),
+            None => break,
+        };
+        let Some(file_type) = entry.file_type() else { continue };
+
+        close_walk_frames(&mut frames, entry.depth(), &mut visit)?;
+        mark_walk_frame(&mut frames, entry.path(), entry.depth());
+        if rules.build_in && file_type.is_dir() {
+            frames.push(WalkFrame::new(entry.depth(), entry.path()));
+        }
+
+        visit(entry.path(), file_type, None)?;
+    }
+    close_walk_frames(&mut frames, 0, &mut visit)?;
+
+    Ok(skipped)
+}
+
+#[allow(dead_code)]
+/// Copies the whole directory with ignore rules.
+/// 
+/// See [walk_dir_with_rules] for how rules are applied, files are copied in parallel by
+/// `rules.workers`, see [copy_in_pool].
+/// 
+/// # Arguments
+/// 
+/// * `from` - A string that holds the source path of  directory
+/// * `to` - A string that holds the save path of directory
+/// * `rules` - Rules to filter items
+/// 
+/// # Examples
+/// 
+/// ```
+/// use meta::{IgnoreRules, copy_dir_with_rules};
+/// 
+/// let rules = IgnoreRules {
+///     build_in: true,
+///     excludes: vec!["/cache".to_string()],
+///     includes: vec!["*.psd".to_string(), "*.blend".to_string()],
+///     max_size: 100 * 1024 * 1024,
+///     max_days: 0,
+///     ..IgnoreRules::default()
+/// };
+/// copy_dir_with_rules("to\\copy\\directory", "to\\save\\directory", &rules).unwrap();
+/// ```
+pub fn copy_dir_with_rules(from: &str, to: &str, rules: &IgnoreRules) -> Result<CopyStats, std::io::Error> {
     use std::path::Path;
     use std::io::{Error, ErrorKind};
-    use std::fs::{copy, create_dir_all};
+    use std::fs::create_dir_all;
 
     let src = Path::new(from);
     let save = Path::new(to);
@@ -357,40 +1190,208 @@ pub fn copy_dir_with_custom_ignores(from: &str, to: &str, ignores: &Vec<String>)
         return Err(Error::from(ErrorKind::AlreadyExists));
     }
 
-    for result in WalkDir::new(src) {
-        match result {
-            Ok(entry) => {
-                let cur_entry_path = entry.path();
-                let entry_save = save.join(cur_entry_path.strip_prefix(from).unwrap_or(src));
-                let entry_save_path = entry_save.as_path();
-
-                if let Some(path_str) = entry.path().to_str() {
-                    if ignores.iter().any(
-                        |item| path_str.contains(item.as_str())
-                    ) {
-                        continue;
-                    }
-                } 
+    let mut stats = CopyStats::default();
+    let mut dirs = Vec::new();
+    let mut special = Vec::new();
+    create_dir_all(save)?;
+    let walk_skipped = copy_in_pool(rules, &mut stats, |copy| walk_dir_with_rules(from, rules, |cur_entry_path, file_type, reason| {
+        if reason.is_some() {
+            return Ok(());
+        }
+
+        if !file_type.is_dir() && !file_type.is_file() && !file_type.is_symlink() {
+            special.push(SkippedItem {
+                path: cur_entry_path.display().to_string(),
+                reason: "special file".to_string(),
+            });
+            return Ok(());
+        }
+
+        let entry_save = save.join(cur_entry_path.strip_prefix(src).unwrap_or(Path::new("")));
+        if file_type.is_dir() {
+            // Only keep the directory structure when all files are wanted
+            if rules.includes.is_empty() {
+                create_dir_all(&entry_save)?;
+            }
+            dirs.push((cur_entry_path.to_path_buf(), entry_save));
+        } else {
+            if let Some(prefix) = entry_save.parent() {
+                create_dir_all(prefix)?;
+            }
+            copy(cur_entry_path.to_path_buf(), entry_save)?;
+        }
+
+        Ok(())
+    }))?;
+    stats.skipped.extend(special);
+    stats.skipped.extend(walk_skipped);
+    // directory times are set after all files are written
+    set_dirs_metadata(&dirs)?;
+
+    Ok(stats)
+}
+
+/// Item listed in the preview of ignore rules.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct PreviewItem {
+    /// Path relative to the previewed directory
+    pub path: String,
+
+    /// Whether item is directory
+    pub is_dir: bool,
+
+    /// Size of file, or total size of excluded directory
+    pub size: u64,
+
+    /// Rule that excluded the item, empty for included item
+    pub reason: String,
+}
+
+/// Preview of ignore rules on directory.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct RulesPreview {
+    /// Files to be backed up
+    pub included: Vec<PreviewItem>,
+
+    /// Files and directories skipped by rules
+    pub excluded: Vec<PreviewItem>,
 
-                if cur_entry_path.is_file() {
-                    let _ = copy(cur_entry_path, entry_save_path)?;
-                } else if cur_entry_path.is_dir() {
-                    let _ = create_dir_all(entry_save_path)?;
+    /// Count of included files
+    pub included_count: u64,
+
+    /// Total size of included files
+    pub included_size: u64,
+
+    /// Count of excluded files, including those in excluded directories
+    pub excluded_count: u64,
+
+    /// Total size of excluded files
+    pub excluded_size: u64,
+}
+
+#[allow(dead_code)]
+/// Previews which items will be copied with ignore rules, without copying anything.
+/// 
+/// # Arguments
+/// 
+/// * `from` - A string that holds the path of directory
+/// * `rules` - Rules to filter items
+/// 
+/// # Examples
+/// 
+/// ```
+/// use meta::{IgnoreRules, preview_dir_with_rules};
+/// 
+/// let rules = IgnoreRules { build_in: true, ..IgnoreRules::default() };
+/// let preview = preview_dir_with_rules("to\\preview\\directory", &rules).unwrap();
+/// println!("{} files, {} bytes to backup", preview.included_count, preview.included_size);
+/// ```
+pub fn preview_dir_with_rules(from: &str, rules: &IgnoreRules) -> Result<RulesPreview, std::io::Error> {
+    use walkdir::WalkDir;
+    use std::path::Path;
+    use std::fs::symlink_metadata;
+
+    let src = Path::new(from);
+    let mut preview = RulesPreview::default();
+    walk_dir_with_rules(from, rules, |entry_path, file_type, reason| {
+        let is_dir = file_type.is_dir();
+        let path = entry_path.strip_prefix(src).unwrap_or(Path::new("")).display().to_string();
+
+        match reason {
+            Some(reason) => {
+                let (mut count, mut size) = (0, 0);
+                if is_dir {
+                    for sub in WalkDir::new(entry_path).into_iter().filter_map(|sub| sub.ok()) {
+                        if sub.file_type().is_file() {
+                            count += 1;
+                            size += sub.metadata().map_or(0, |meta| meta.len());
+                        }
+                    }
+                } else {
+                    count = 1;
+                    size = symlink_metadata(entry_path).map_or(0, |meta| meta.len());
                 }
+
+                preview.excluded_count += count;
+                preview.excluded_size += size;
+                preview.excluded.push(PreviewItem { path, is_dir, size, reason });
             },
-            Err(_error) => {
-                // println!("Failed to parse ignore file in dir {}, errMsg: {:?}", from, error);
-                return Err(Error::from(ErrorKind::InvalidData));
+            None => {
+                if is_dir {
+                    return Ok(());
+                }
+
+                let size = symlink_metadata(entry_path).map_or(0, |meta| meta.len());
+                preview.included_count += 1;
+                preview.included_size += size;
+                preview.included.push(PreviewItem { path, is_dir, size, reason: String::new() });
             }
         }
+
+        Ok(())
+    })?;
+
+    Ok(preview)
+}
+
+/// Identity of a hard linked file, as device and inode numbers.
+type LinkedFileId = (u64, u64);
+
+/// Gets identity of file shared by hard links, `None` for file without other links.
+fn linked_file_id(path: &std::path::Path, meta: &std::fs::Metadata) -> Option<LinkedFileId> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let _ = path;
+        if meta.nlink() > 1 {
+            return Some((meta.dev(), meta.ino()));
+        }
+        None
     }
 
-    Ok(())
+    #[cfg(windows)]
+    {
+        use winapi_util::{Handle, file::information};
+
+        let _ = meta;
+        let info = information(Handle::from_path_any(path).ok()?).ok()?;
+        if info.number_of_links() > 1 {
+            return Some((info.volume_serial_number(), info.file_index()));
+        }
+        None
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = (path, meta);
+        None
+    }
+}
+
+/// Lists files under path with their sizes and identities of hard links.
+fn list_path_files(path: &str) -> Result<Vec<(Option<LinkedFileId>, u64)>, std::io::Error> {
+    use walkdir::WalkDir;
+    use std::io::Error;
+
+    let mut files = Vec::new();
+    for entry in WalkDir::new(path) {
+        let entry = entry.map_err(Error::other)?;
+        if entry.file_type().is_dir() {
+            continue;
+        }
+        let meta = entry.metadata().map_err(Error::other)?;
+        files.push((linked_file_id(entry.path(), &meta), meta.len()));
+    }
+
+    Ok(files)
 }
 
 #[allow(dead_code)]
 /// Get path size.
 /// 
+/// Files hard linked from multiple paths are counted once.
+/// 
 /// # Arguments
 /// 
 /// * `path` - A string that holds the target path
@@ -404,29 +1405,24 @@ pub fn copy_dir_with_custom_ignores(from: &str, to: &str, ignores: &Vec<String>)
 /// println!("target path size is: {}", size);
 /// ```
 pub fn get_path_size(path: &str) -> Result<u64, std::io::Error> {
+    use std::collections::HashSet;
     use std::path::Path;
     use std::io::{Error, ErrorKind};
-    use std::fs;
-    use fs_extra::dir::get_size;
 
     let target = Path::new(path);
     if !target.exists() {
         return Err(Error::from(ErrorKind::NotFound));
     }
 
-    if target.is_dir() {
-        if let Ok(dir_size) = get_size(path) {
-            return Ok(dir_size);
-        } else {
-            return Err(Error::from(ErrorKind::Other));
-        }
-    } else {
-        if let Ok(file) = fs::metadata(path) {
-            return Ok(file.len());
-        } else {
-            return Err(Error::from(ErrorKind::Other));
+    let mut linked: HashSet<LinkedFileId> = HashSet::new();
+    let mut total = 0;
+    for (id, size) in list_path_files(path)? {
+        if id.is_none_or(|id| linked.insert(id)) {
+            total += size;
         }
     }
+
+    Ok(total)
 }
 
 #[allow(dead_code)]
@@ -494,7 +1490,7 @@ pub fn restrict_dir_subitems_count(path: &str, count: usize) -> Result<(), std::
 #[allow(dead_code)]
 /// Restricts the directory to wanted size.
 /// 
-/// This will not recursively walk the children directory!
+/// Will delete the ealiest created items, files hard linked from other items are not counted as freed.
 /// 
 /// # Arguments
 /// 
@@ -509,24 +1505,25 @@ pub fn restrict_dir_subitems_count(path: &str, count: usize) -> Result<(), std::
 /// restrict_dir_subitems_size("to\\restrict\\directory", 3 * 1024).unwrap();
 /// ```
 pub fn restrict_dir_subitems_size(path: &str, size: u64) -> Result<(), std::io::Error> {
+    use std::collections::HashMap;
     use std::path::Path;
     use std::io::{Error, ErrorKind};
     use std::fs::read_dir;
-    use fs_extra::dir::get_size;
 
     let target = Path::new(path);
     if !target.exists() {
         return Err(Error::from(ErrorKind::NotFound));
     }
 
+    let mut dir_items = Vec::new();
     for result in read_dir(path)? {
         match result {
             Ok(entry) => {
-                if let Ok(dir_size) = get_size(path) {
-                    if dir_size > size {
-                        let _ = remove_all(entry.path().display().to_string().as_str())?;
-                    }
-                }
+                let meta = entry.metadata()?;
+                let created = meta.created().or(meta.modified())?;
+                let item_path = entry.path().display().to_string();
+                let files = list_path_files(&item_path)?;
+                dir_items.push((created, item_path, files));
             },
             Err(error) => {
                 println!("Failed to read dir {}, errMsg: {:?}", path, error);
@@ -534,6 +1531,44 @@ pub fn restrict_dir_subitems_size(path: &str, size: u64) -> Result<(), std::io::
             }
         }
     }
+    dir_items.sort_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)));
+
+    // linked file only takes space once, and is freed with its last link
+    let mut links: HashMap<LinkedFileId, usize> = HashMap::new();
+    let mut total = 0;
+    for (_, _, files) in dir_items.iter() {
+        for (id, file_size) in files.iter() {
+            match id {
+                Some(id) => {
+                    let count = links.entry(*id).or_insert(0);
+                    if *count == 0 {
+                        total += file_size;
+                    }
+                    *count += 1;
+                },
+                None => total += file_size,
+            }
+        }
+    }
+
+    for (_, item_path, files) in dir_items.iter() {
+        if total <= size {
+            break;
+        }
+
+        remove_all(item_path)?;
+        for (id, file_size) in files.iter() {
+            match id.and_then(|id| links.get_mut(&id)) {
+                Some(count) => {
+                    *count -= 1;
+                    if *count == 0 {
+                        total -= file_size;
+                    }
+                },
+                None => total -= file_size,
+            }
+        }
+    }
 
     Ok(())
 }
@@ -696,6 +1731,145 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_copy_dir_with_custom_ignores() {
+        use std::env::current_dir;
+        use std::path::Path;
+        use std::fs::{remove_dir_all, create_dir_all, write};
+
+        let test_path = current_dir().expect("").join("test_explorer_ignores");
+        let src = test_path.join("robin");
+        let dst = test_path.join("robin_copy");
+        create_dir_all(src.join("bin")).unwrap();
+        create_dir_all(src.join("logs").join("bin")).unwrap();
+        write(src.join("cabinet.txt"), "Hello world!").unwrap();
+        write(src.join("bin").join("app.exe"), "Hello world!").unwrap();
+        write(src.join("logs").join("debug.log"), "Hello world!").unwrap();
+        write(src.join("logs").join("keep.log"), "Hello world!").unwrap();
+        write(src.join("logs").join("bin").join("app.exe"), "Hello world!").unwrap();
+
+        let ignores = vec!["/bin".to_string(), "**/*.log".to_string(), "!keep.log".to_string()];
+        let _ = copy_dir_with_custom_ignores(
+            src.display().to_string().as_str(), 
+            dst.display().to_string().as_str(), 
+            &ignores
+        ).unwrap();
+
+        assert_eq!(Path::new(&dst.join("cabinet.txt")).exists(), true);
+        assert_eq!(Path::new(&dst.join("bin")).exists(), false);
+        assert_eq!(Path::new(&dst.join("logs").join("bin").join("app.exe")).exists(), true);
+        assert_eq!(Path::new(&dst.join("logs").join("debug.log")).exists(), false);
+        assert_eq!(Path::new(&dst.join("logs").join("keep.log")).exists(), true);
+
+        let _ = remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_copy_dir_with_rules() {
+        use std::env::current_dir;
+        use std::path::Path;
+        use std::fs::{remove_dir_all, create_dir_all, write};
+
+        let test_path = current_dir().expect("").join("test_explorer_rules");
+        let src = test_path.join("robin");
+        let dst = test_path.join("robin_copy");
+        create_dir_all(src.join("scenes").join("cache")).unwrap();
+        create_dir_all(src.join("notes")).unwrap();
+        write(src.join("scenes").join("city.blend"), "Hello world!").unwrap();
+        write(src.join("scenes").join("huge.blend"), "Hello world! Hello world!").unwrap();
+        write(src.join("scenes").join("cache").join("city.blend"), "Hello world!").unwrap();
+        write(src.join("notes").join("todo.txt"), "Hello world!").unwrap();
+
+        let rules = IgnoreRules {
+            build_in: false,
+            excludes: vec!["cache/".to_string()],
+            includes: vec!["*.blend".to_string()],
+            max_size: 20,
+            max_days: 0,
+            ..IgnoreRules::default()
+        };
+        let _ = copy_dir_with_rules(
+            src.display().to_string().as_str(),
+            dst.display().to_string().as_str(),
+            &rules
+        ).unwrap();
+
+        assert_eq!(Path::new(&dst.join("scenes").join("city.blend")).exists(), true);
+        assert_eq!(Path::new(&dst.join("scenes").join("huge.blend")).exists(), false);
+        assert_eq!(Path::new(&dst.join("scenes").join("cache")).exists(), false);
+        assert_eq!(Path::new(&dst.join("notes")).exists(), false);
+
+        let _ = remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_preview_dir_with_rules() {
+        use std::env::current_dir;
+        use std::fs::{remove_dir_all, create_dir_all, write};
+
+        let test_path = current_dir().expect("").join("test_explorer_preview");
+        let src = test_path.join("robin");
+        create_dir_all(src.join("target").join("debug")).unwrap();
+        write(src.join(".gitignore"), "target/\n").unwrap();
+        write(src.join("main.rs"), "Hello world!").unwrap();
+        write(src.join("debug.log"), "Hello world!").unwrap();
+        write(src.join("target").join("debug").join("app.exe"), "Hello world!").unwrap();
+
+        let rules = IgnoreRules {
+            build_in: true,
+            excludes: vec!["*.log".to_string()],
+            ..IgnoreRules::default()
+        };
+        let preview = preview_dir_with_rules(src.display().to_string().as_str(), &rules).unwrap();
+
+        assert_eq!(preview.included_count, 2);
+        assert_eq!(preview.included_size, 20);
+        assert_eq!(preview.excluded_count, 2);
+        assert_eq!(preview.excluded_size, 24);
+        let reasons: Vec<(String, String)> = preview.excluded.iter()
+            .map(|item| (item.path.clone(), item.reason.clone()))
+            .collect();
+        assert!(reasons.contains(&("debug.log".to_string(), "exclude: *.log".to_string())));
+        assert!(reasons.iter().any(|(path, reason)| path == "target" && reason.ends_with(".gitignore: target/")));
+
+        let _ = remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_walk_dir_with_rules_hidden() {
+        use std::env::current_dir;
+        use std::fs::{remove_dir_all, create_dir_all, write};
+
+        let test_path = current_dir().expect("").join("test_explorer_hidden");
+        let src = test_path.join("robin");
+        create_dir_all(src.join(".config")).unwrap();
+        write(src.join(".gitignore"), ".cache\n").unwrap();
+        write(src.join(".env"), "Hello world!").unwrap();
+        write(src.join(".cache"), "Hello world!").unwrap();
+        write(src.join(".config").join("settings.json"), "Hello world!").unwrap();
+
+        // hidden items are only excluded by ignore files
+        let rules = IgnoreRules { build_in: true, ..IgnoreRules::default() };
+        let mut kept = Vec::new();
+        let mut excluded = Vec::new();
+        walk_dir_with_rules(src.display().to_string().as_str(), &rules, |path, _, reason| {
+            let relative = path.strip_prefix(&src).unwrap().to_string_lossy().replace('\\', "/");
+            match reason {
+                Some(reason) => excluded.push((relative, reason)),
+                None => kept.push(relative),
+            }
+            Ok(())
+        }).unwrap();
+        kept.sort();
+
+        assert_eq!(kept, vec!["", ".config", ".config/settings.json", ".env", ".gitignore"]);
+        assert_eq!(excluded.len(), 1);
+        assert_eq!(excluded[0].0, ".cache".to_string());
+        assert!(excluded[0].1.ends_with(".gitignore: .cache"));
+
+        remove_dir_all(test_path).unwrap();
+    }
+
     #[test]
     fn test_restrict_dir_subitems_count() {
         use std::env::current_dir;
@@ -711,6 +1885,78 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_copy_file() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, write, read_to_string, remove_dir_all};
+
+        let test_path = current_dir().expect("").join("test_explorer_reflink");
+        create_dir_all(&test_path).unwrap();
+        write(test_path.join("from.txt"), "Hello world!").unwrap();
+        write(test_path.join("to.txt"), "Existed").unwrap();
+
+        // reflink or copy depends on filesystem of test path
+        let stats = copy_file(test_path.join("from.txt"), test_path.join("to.txt")).unwrap();
+        assert_eq!(stats.reflinked + stats.copied, 1);
+        assert_eq!(read_to_string(test_path.join("to.txt")).unwrap(), "Hello world!");
+
+        let mut total = CopyStats { reflinked: 1, ..CopyStats::default() };
+        assert_eq!(total.method(), "reflink");
+        total.merge(CopyStats { copied: 2, ..CopyStats::default() });
+        assert_eq!(total.method(), "mixed");
+        assert_eq!(CopyStats::default().method(), "");
+
+        let _ = remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_copy_file_fallback() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, write, read_to_string, remove_dir_all};
+
+        let test_path = current_dir().expect("").join("test_explorer_reflink_fallback");
+        let from = test_path.join("from");
+        create_dir_all(&from).unwrap();
+        write(from.join("a.txt"), "Hello").unwrap();
+        write(from.join("b.txt"), "world!").unwrap();
+
+        // files are copied byte by byte where filesystem can not clone them
+        let is_supported = reflink_copy::reflink(from.join("a.txt"), test_path.join("probe.txt")).is_ok();
+        let stats = copy_all(&from.display().to_string(), &test_path.join("to").display().to_string()).unwrap();
+        match is_supported {
+            true => assert_eq!((stats.reflinked, stats.copied, stats.method().as_str()), (2, 0, "reflink")),
+            false => assert_eq!((stats.reflinked, stats.copied, stats.method().as_str()), (0, 2, "copy")),
+        }
+        assert_eq!(read_to_string(test_path.join("to").join("a.txt")).unwrap(), "Hello");
+        assert_eq!(read_to_string(test_path.join("to").join("b.txt")).unwrap(), "world!");
+
+        remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_linked_path_size() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, hard_link, write, remove_dir_all};
+
+        let test_path = current_dir().expect("").join("test_explorer_linked");
+        for name in ["first", "second", "third"] {
+            create_dir_all(test_path.join(name)).unwrap();
+        }
+        write(test_path.join("first").join("save.txt"), "Hello world!").unwrap();
+        hard_link(test_path.join("first").join("save.txt"), test_path.join("second").join("save.txt")).unwrap();
+        write(test_path.join("third").join("new.txt"), "Hello").unwrap();
+
+        assert_eq!(get_path_size(test_path.display().to_string().as_str()).unwrap(), 17);
+
+        // removing the first item frees nothing, file is still linked from the second
+        restrict_dir_subitems_size(test_path.display().to_string().as_str(), 10).unwrap();
+        assert_eq!(test_path.join("first").exists(), false);
+        assert_eq!(test_path.join("second").exists(), false);
+        assert_eq!(get_path_size(test_path.display().to_string().as_str()).unwrap(), 5);
+
+        let _ = remove_dir_all(test_path).unwrap();
+    }
+
     #[test]
     fn test_restrict_dir_subitems_size() {
         use std::env::current_dir;
@@ -739,4 +1985,132 @@ mod test {
             let _ = remove_dir_all(current_dir().expect("").join("test_explorer_size")).unwrap();
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_run_with_policy() {
+        use std::env::current_dir;
+        use std::io::{Error, ErrorKind};
+        use std::fs::{remove_dir_all, create_dir_all, write};
+
+        let mut skipped = Vec::new();
+        let abort = IgnoreRules::default();
+        assert!(run_with_policy(&abort, "locked.txt", &mut skipped, || Err::<(), _>(Error::from(ErrorKind::PermissionDenied))).is_err());
+
+        let skip = IgnoreRules { on_error: 1, ..IgnoreRules::default() };
+        assert_eq!(run_with_policy(&skip, "locked.txt", &mut skipped, || Err::<(), _>(Error::from(ErrorKind::PermissionDenied))).unwrap(), None);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].path, "locked.txt".to_string());
+
+        let mut attempts = 0;
+        let retry = IgnoreRules { on_error: 2, retries: 2, ..IgnoreRules::default() };
+        let result = run_with_policy(&retry, "busy.txt", &mut skipped, || {
+            attempts += 1;
+            match attempts {
+                1 | 2 => Err(Error::from(ErrorKind::WouldBlock)),
+                _ => Ok(attempts),
+            }
+        }).unwrap();
+        assert_eq!(result, Some(3));
+        assert_eq!(skipped.len(), 1);
+
+        // fifo would block the copy forever
+        let test_path = current_dir().expect("").join("test_explorer_policy");
+        let src = test_path.join("robin");
+        let dst = test_path.join("robin_copy");
+        create_dir_all(&src).unwrap();
+        write(src.join("notes.txt"), "Hello world!").unwrap();
+        #[cfg(unix)]
+        {
+            let fifo = std::ffi::CString::new(src.join("pipe").display().to_string()).unwrap();
+            assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o644) }, 0);
+        }
+
+        let stats = copy_dir_with_rules(
+            src.display().to_string().as_str(),
+            dst.display().to_string().as_str(),
+            &skip
+        ).unwrap();
+        assert!(dst.join("notes.txt").exists());
+        #[cfg(unix)]
+        {
+            assert_eq!(stats.skipped.len(), 1);
+            assert_eq!(stats.skipped[0].reason, "special file".to_string());
+            assert!(!dst.join("pipe").exists());
+        }
+
+        let _ = remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    fn test_copy_in_pool() {
+        use std::env::current_dir;
+        use std::fs::{remove_dir_all, create_dir_all, read_to_string, write};
+
+        let test_path = current_dir().expect("").join("test_explorer_pool");
+        let src = test_path.join("robin");
+        let dst = test_path.join("robin_copy");
+        for dir in 0..4 {
+            create_dir_all(src.join(format!("dir_{}", dir))).unwrap();
+            for file in 0..25 {
+                write(src.join(format!("dir_{}", dir)).join(format!("{}.txt", file)), format!("{}/{}", dir, file)).unwrap();
+            }
+        }
+
+        let rules = IgnoreRules { workers: 4, ..IgnoreRules::default() };
+        let stats = copy_dir_with_rules(
+            src.display().to_string().as_str(),
+            dst.display().to_string().as_str(),
+            &rules
+        ).unwrap();
+        assert_eq!(stats.reflinked + stats.copied, 100);
+        assert_eq!(read_to_string(dst.join("dir_3").join("24.txt")).unwrap(), "3/24".to_string());
+
+        // abort on first failed copy
+        let mut stats = CopyStats::default();
+        let result = copy_in_pool(&rules, &mut stats, |copy| {
+            for file in 0..25 {
+                copy(src.join("missing").join(format!("{}.txt", file)), dst.join(format!("{}.txt", file)))?;
+            }
+            Ok(())
+        });
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotFound);
+
+        let _ = remove_dir_all(test_path).unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn bench_copy_dir_parallel() {
+        use std::env::current_dir;
+        use std::fs::{remove_dir_all, create_dir_all, write};
+        use std::time::Instant;
+
+        let test_path = current_dir().expect("").join("test_explorer_bench");
+        let src = test_path.join("robin");
+        for dir in 0..50 {
+            let sub_dir = src.join(format!("dir_{}", dir / 10)).join(format!("dir_{}", dir));
+            create_dir_all(&sub_dir).unwrap();
+            for file in 0..200 {
+                write(sub_dir.join(format!("{}.txt", file)), "Hello world!".repeat(file + 1)).unwrap();
+            }
+        }
+
+        let start = Instant::now();
+        let _ = copy_dir_all(&src, test_path.join("sequential")).unwrap();
+        println!("copy_dir_all: {:?}", start.elapsed());
+
+        for workers in [1, 2, 4, 8] {
+            let rules = IgnoreRules { workers, ..IgnoreRules::default() };
+            let start = Instant::now();
+            let stats = copy_dir_with_rules(
+                src.display().to_string().as_str(),
+                test_path.join(format!("parallel_{}", workers)).display().to_string().as_str(),
+                &rules
+            ).unwrap();
+            println!("copy_dir_with_rules, {} workers: {:?}", workers, start.elapsed());
+            assert_eq!(stats.reflinked + stats.copied, 10_000);
+        }
+
+        let _ = remove_dir_all(test_path).unwrap();
+    }
+}
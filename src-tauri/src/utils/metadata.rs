@@ -0,0 +1,245 @@
+//! The `metadata` module keeps file metadata when copying or restoring items.
+//! The following metadata is preserved: access and modification time, permissions,
+//! ownership (only when privileged), symbolic links and extended attributes (Unix only).
+
+#[allow(dead_code)]
+/// Whether current process is allowed to change ownership of files.
+/// 
+/// # Examples
+/// 
+/// ```
+/// use metadata::is_privileged;
+/// 
+/// if is_privileged() {
+///     println!("ownership will be restored");
+/// }
+/// ```
+pub fn is_privileged() -> bool {
+    #[cfg(unix)]
+    return unsafe { libc::geteuid() == 0 };
+
+    #[cfg(not(unix))]
+    return false;
+}
+
+#[allow(dead_code)]
+/// Reads extended attributes of path, symbolic links are not followed.
+/// 
+/// Returns empty list on platforms without extended attributes support.
+/// 
+/// # Arguments
+/// 
+/// * `path` - Path of file, directory or symbolic link
+/// 
+/// # Examples
+/// 
+/// ```
+/// use metadata::read_xattrs;
+/// 
+/// for (name, value) in read_xattrs("path\\to\\file") {
+///     println!("{}: {} bytes", name, value.len());
+/// }
+/// ```
+pub fn read_xattrs(path: impl AsRef<std::path::Path>) -> Vec<(String, Vec<u8>)> {
+    #[allow(unused_mut)]
+    let mut attrs: Vec<(String, Vec<u8>)> = Vec::new();
+
+    #[cfg(unix)]
+    if let Ok(names) = xattr::list(&path) {
+        for name in names {
+            if let Ok(Some(value)) = xattr::get(&path, &name) {
+                attrs.push((name.to_string_lossy().to_string(), value));
+            }
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = path;
+
+    attrs
+}
+
+#[allow(dead_code)]
+/// Writes extended attributes to path, symbolic links are not followed.
+/// 
+/// Attributes the filesystem refuses are skipped.
+/// 
+/// # Arguments
+/// 
+/// * `path` - Path of file, directory or symbolic link
+/// * `attrs` - Extended attributes as name and value pairs
+/// 
+/// # Examples
+/// 
+/// ```
+/// use metadata::write_xattrs;
+/// 
+/// write_xattrs("path\\to\\file", &vec![("user.comment".to_string(), b"hello".to_vec())]);
+/// ```
+pub fn write_xattrs(path: impl AsRef<std::path::Path>, attrs: &[(String, Vec<u8>)]) {
+    #[cfg(unix)]
+    for (name, value) in attrs {
+        let _ = xattr::set(&path, name, value);
+    }
+
+    #[cfg(not(unix))]
+    let _ = (path, attrs);
+}
+
+#[allow(dead_code)]
+/// Creates a symbolic link at `to` pointing to the target of `from`.
+/// 
+/// On Windows, falls back to copying the linked item when symbolic link is not permitted.
+/// 
+/// # Arguments
+/// 
+/// * `from` - Path of source symbolic link
+/// * `to` - Path of new symbolic link
+/// 
+/// # Examples
+/// 
+/// ```
+/// use metadata::copy_symlink;
+/// 
+/// copy_symlink("path\\to\\link", "path\\to\\save\\link").unwrap();
+/// ```
+pub fn copy_symlink(from: impl AsRef<std::path::Path>, to: impl AsRef<std::path::Path>) -> Result<(), std::io::Error> {
+    use std::fs::{read_link, remove_file};
+
+    let target = read_link(&from)?;
+    if to.as_ref().symlink_metadata().is_ok() {
+        remove_file(&to)?;
+    }
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&target, &to)?;
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::{symlink_dir, symlink_file};
+
+        let linked = if from.as_ref().is_dir() { symlink_dir(&target, &to) } else { symlink_file(&target, &to) };
+        if linked.is_err() {
+            if from.as_ref().is_dir() {
+                return Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+            }
+            std::fs::copy(&from, &to)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[allow(dead_code)]
+/// Sets file times, symbolic links are not followed.
+/// 
+/// # Arguments
+/// 
+/// * `path` - Path of file, directory or symbolic link
+/// * `accessed` - Last access time
+/// * `modified` - Last modification time
+/// 
+/// # Examples
+/// 
+/// ```
+/// use metadata::set_times;
+/// use std::time::SystemTime;
+/// 
+/// set_times("path\\to\\file", SystemTime::now(), SystemTime::now()).unwrap();
+/// ```
+pub fn set_times(path: impl AsRef<std::path::Path>, accessed: std::time::SystemTime, modified: std::time::SystemTime) -> Result<(), std::io::Error> {
+    use filetime::{FileTime, set_symlink_file_times};
+
+    set_symlink_file_times(path, FileTime::from_system_time(accessed), FileTime::from_system_time(modified))
+}
+
+#[allow(dead_code)]
+/// Copies metadata of `from` to `to`, content is not touched.
+/// 
+/// Times, permissions and extended attributes are copied, ownership is copied only
+/// when privileged. Symbolic links are handled as links, not their targets.
+/// 
+/// # Arguments
+/// 
+/// * `from` - Path of source item
+/// * `to` - Path of copied item
+/// 
+/// # Examples
+/// 
+/// ```
+/// use metadata::copy_metadata;
+/// 
+/// std::fs::copy("path\\to\\file", "path\\to\\save\\file").unwrap();
+/// copy_metadata("path\\to\\file", "path\\to\\save\\file").unwrap();
+/// ```
+pub fn copy_metadata(from: impl AsRef<std::path::Path>, to: impl AsRef<std::path::Path>) -> Result<(), std::io::Error> {
+    use std::fs::set_permissions;
+
+    let meta = from.as_ref().symlink_metadata()?;
+    let is_symlink = meta.file_type().is_symlink();
+
+    write_xattrs(&to, &read_xattrs(&from));
+
+    #[cfg(unix)]
+    if is_privileged() {
+        use std::os::unix::fs::{MetadataExt, lchown};
+
+        let _ = lchown(&to, Some(meta.uid()), Some(meta.gid()));
+    }
+
+    if !is_symlink {
+        set_permissions(&to, meta.permissions())?;
+    }
+
+    let accessed = meta.accessed().or(meta.modified())?;
+    set_times(&to, accessed, meta.modified()?)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_copy_metadata() {
+        use std::env::current_dir;
+        use std::fs::{create_dir_all, remove_dir_all, write};
+        use std::time::{Duration, SystemTime};
+
+        let root = current_dir().unwrap().join("test_metadata_copy");
+        let _ = remove_dir_all(&root);
+        create_dir_all(&root).unwrap();
+
+        let from = root.join("from.txt");
+        let to = root.join("to.txt");
+        write(&from, "metadata").unwrap();
+        write(&to, "metadata").unwrap();
+
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        set_times(&from, modified, modified).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&from, std::fs::Permissions::from_mode(0o640)).unwrap();
+        }
+
+        copy_metadata(&from, &to).unwrap();
+        assert_eq!(to.metadata().unwrap().modified().unwrap(), modified);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(to.metadata().unwrap().permissions().mode() & 0o777, 0o640);
+
+            let link = root.join("link");
+            let link_copy = root.join("link_copy");
+            std::os::unix::fs::symlink("from.txt", &link).unwrap();
+            copy_symlink(&link, &link_copy).unwrap();
+            copy_metadata(&link, &link_copy).unwrap();
+            assert!(link_copy.symlink_metadata().unwrap().file_type().is_symlink());
+            assert_eq!(std::fs::read_link(&link_copy).unwrap(), std::path::PathBuf::from("from.txt"));
+        }
+
+        remove_dir_all(&root).unwrap();
+    }
+}
@@ -156,7 +156,7 @@ fn extract_ignores(src: &MissionConfig, procedure: &crate::db::procedure::Proced
             for item in src.ignores.iter() {
                 let mut ignore = crate::db::ignore::Ignore::default();
                 ignore.procedure_id = procedure.procedure_id.clone();
-                ignore.keyword = item.clone();
+                ignore.keyword = crate::db::ignore::keyword_to_pattern(item);
                 data.push(ignore);
             }
         },
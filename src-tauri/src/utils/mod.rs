@@ -1,6 +1,10 @@
 pub mod common;
 pub mod compress;
 pub mod crypto;
+pub mod delta;
 pub mod explorer;
 pub mod logger;
+pub mod metadata;
 pub mod migrate;
+pub mod secret;
+pub mod throttle;
@@ -0,0 +1,97 @@
+//! The `secret` module contains functions about credentials saved in system keyring.
+//! 
+//! Credentials of remote storages are never saved in database.
+
+/// Service name of credentials in system keyring.
+const SECRET_SERVICE: &str = "MissionBackup";
+
+/// Converts keyring error to io error.
+fn to_io_error(error: keyring::Error) -> std::io::Error {
+    use std::io::{Error, ErrorKind};
+
+    match error {
+        keyring::Error::NoEntry => Error::from(ErrorKind::NotFound),
+        _ => Error::other(format!("{:?}", error)),
+    }
+}
+
+#[allow(dead_code)]
+/// Saves secret into system keyring, overwriting the existing one.
+/// 
+/// # Arguments
+/// 
+/// * `key` - A string that identifies the secret
+/// * `secret` - A string that holds the secret
+/// 
+/// # Examples
+/// 
+/// ```
+/// use secret::set_secret;
+/// 
+/// set_secret("s3:backups@s3.us-east-1.amazonaws.com", "{\"access_key\":\"\",\"secret_key\":\"\"}").unwrap();
+/// ```
+pub fn set_secret(key: &str, secret: &str) -> Result<(), std::io::Error> {
+    let entry = keyring::Entry::new(SECRET_SERVICE, key).map_err(to_io_error)?;
+
+    entry.set_password(secret).map_err(to_io_error)
+}
+
+#[allow(dead_code)]
+/// Gets secret from system keyring.
+/// 
+/// # Arguments
+/// 
+/// * `key` - A string that identifies the secret
+/// 
+/// # Examples
+/// 
+/// ```
+/// use secret::get_secret;
+/// 
+/// match get_secret("s3:backups@s3.us-east-1.amazonaws.com") {
+///     Ok(secret) => println!("get secret with length {}", secret.len()),
+///     Err(error) => println!("failed to get secret, errMsg: {:?}", error),
+/// }
+/// ```
+pub fn get_secret(key: &str) -> Result<String, std::io::Error> {
+    let entry = keyring::Entry::new(SECRET_SERVICE, key).map_err(to_io_error)?;
+
+    entry.get_password().map_err(to_io_error)
+}
+
+#[allow(dead_code)]
+/// Deletes secret from system keyring.
+/// 
+/// # Arguments
+/// 
+/// * `key` - A string that identifies the secret
+/// 
+/// # Examples
+/// 
+/// ```
+/// use secret::delete_secret;
+/// 
+/// let _ = delete_secret("s3:backups@s3.us-east-1.amazonaws.com");
+/// ```
+pub fn delete_secret(key: &str) -> Result<(), std::io::Error> {
+    let entry = keyring::Entry::new(SECRET_SERVICE, key).map_err(to_io_error)?;
+
+    entry.delete_password().map_err(to_io_error)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Needs a running system keyring, like secret service on linux.
+    #[test]
+    #[ignore]
+    fn test_secret() {
+        let key = "test:mission-backup";
+        set_secret(key, "Hello world!").unwrap();
+        assert_eq!(get_secret(key).unwrap(), "Hello world!".to_string());
+
+        delete_secret(key).unwrap();
+        assert_eq!(get_secret(key).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+    }
+}
@@ -0,0 +1,231 @@
+//! The `throttle` module limits resources used by backups running in background.
+//! It provides rate limiters for reading and writing, and lowers priority of compression threads.
+
+/// Limits bytes per second shared by all threads using it.
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Bytes per second, `0` means no limit
+    rate: u64,
+
+    /// Time of last refill and bytes available since then, negative if overdrawn
+    state: std::sync::Mutex<(std::time::Instant, f64)>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        RateLimiter::new(0)
+    }
+}
+
+impl RateLimiter {
+    /// Creates limiter of `rate` bytes per second, `0` means no limit.
+    pub fn new(rate: u64) -> Self {
+        RateLimiter {
+            rate,
+            state: std::sync::Mutex::new((std::time::Instant::now(), rate as f64)),
+        }
+    }
+
+    /// Whether limiter will slow anything down.
+    pub fn is_limited(&self) -> bool {
+        self.rate > 0
+    }
+
+    /// Takes `bytes` from limiter, sleeps until they are affordable.
+    /// 
+    /// Up to one second of bytes is allowed in a burst.
+    pub fn consume(&self, bytes: u64) {
+        use std::time::{Duration, Instant};
+
+        if self.rate == 0 || bytes == 0 {
+            return;
+        }
+
+        let wait = match self.state.lock() {
+            Ok(mut state) => {
+                let now = Instant::now();
+                let refill = now.duration_since(state.0).as_secs_f64() * self.rate as f64;
+                state.0 = now;
+                state.1 = (state.1 + refill).min(self.rate as f64) - bytes as f64;
+                match state.1 < 0.0 {
+                    true => -state.1 / self.rate as f64,
+                    false => 0.0,
+                }
+            },
+            Err(_) => 0.0,
+        };
+
+        if wait > 0.0 {
+            std::thread::sleep(Duration::from_secs_f64(wait));
+        }
+    }
+}
+
+/// Writer that limits bytes per second written to inner writer.
+pub struct ThrottledWriter<'a, W: std::io::Write> {
+    inner: W,
+    limiter: &'a RateLimiter,
+}
+
+impl<'a, W: std::io::Write> ThrottledWriter<'a, W> {
+    /// Wraps `inner` with `limiter`.
+    pub fn new(inner: W, limiter: &'a RateLimiter) -> Self {
+        ThrottledWriter { inner, limiter }
+    }
+}
+
+impl<'a, W: std::io::Write> std::io::Write for ThrottledWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.limiter.consume(written as u64);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<'a, W: std::io::Write + std::io::Seek> std::io::Seek for ThrottledWriter<'a, W> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[allow(dead_code)]
+/// Lowers priority of current thread, it can not be raised back on some systems.
+/// 
+/// Uses nice value on linux, QoS class on macos and thread priority on windows,
+/// does nothing on other unix systems where nice value applies to whole process.
+/// 
+/// # Arguments
+/// 
+/// * `priority` - Priority level
+/// 
+///   `0` - normal, nothing changed
+/// 
+///   `1` - below normal
+/// 
+///   `2` - lowest
+/// 
+/// # Examples
+/// 
+/// ```
+/// use throttle::lower_thread_priority;
+/// 
+/// std::thread::spawn(|| {
+///     lower_thread_priority(1);
+///     println!("running in background");
+/// });
+/// ```
+pub fn lower_thread_priority(priority: i16) {
+    use log::warn;
+
+    if priority <= 0 {
+        return;
+    }
+
+    // nice value of calling thread on linux
+    #[cfg(target_os = "linux")]
+    {
+        let nice = if priority == 1 { 10 } else { 19 };
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+            warn!("failed to lower thread priority, errMsg: {:?}", std::io::Error::last_os_error());
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use libc::qos_class_t::{QOS_CLASS_BACKGROUND, QOS_CLASS_UTILITY};
+
+        let class = if priority == 1 { QOS_CLASS_UTILITY } else { QOS_CLASS_BACKGROUND };
+        let code = unsafe { libc::pthread_set_qos_class_self_np(class, 0) };
+        if code != 0 {
+            warn!("failed to lower thread priority, errMsg: {:?}", std::io::Error::from_raw_os_error(code));
+        }
+    }
+
+    #[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+    {
+        use log::debug;
+
+        debug!("thread priority is not lowered on this system, priority: {}", priority);
+    }
+
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::System::Threading::{
+            GetCurrentThread, SetThreadPriority, THREAD_PRIORITY_BELOW_NORMAL, THREAD_PRIORITY_LOWEST
+        };
+
+        let level = if priority == 1 { THREAD_PRIORITY_BELOW_NORMAL } else { THREAD_PRIORITY_LOWEST };
+        if unsafe { SetThreadPriority(GetCurrentThread(), level) } == 0 {
+            warn!("failed to lower thread priority, errMsg: {:?}", std::io::Error::last_os_error());
+        }
+    }
+}
+
+#[allow(dead_code)]
+/// Runs `task` in a new thread with lowered priority, see [lower_thread_priority].
+/// 
+/// `task` runs in current thread if priority is normal.
+/// 
+/// # Arguments
+/// 
+/// * `priority` - Priority level
+/// * `task` - Task to run
+/// 
+/// # Examples
+/// 
+/// ```
+/// use throttle::run_with_priority;
+/// 
+/// let sum = run_with_priority(2, || Ok((1..100).sum::<u64>())).unwrap();
+/// ```
+pub fn run_with_priority<T, F>(priority: i16, task: F) -> Result<T, std::io::Error>
+where
+    T: Send,
+    F: FnOnce() -> Result<T, std::io::Error> + Send,
+{
+    use std::io::Error;
+
+    if priority <= 0 {
+        return task();
+    }
+
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            lower_thread_priority(priority);
+            task()
+        }).join().unwrap_or_else(|_| Err(Error::other("background thread panicked")))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        use std::io::Write;
+        use std::time::{Duration, Instant};
+
+        let unlimited = RateLimiter::default();
+        let start = Instant::now();
+        unlimited.consume(1024 * 1024 * 1024);
+        assert!(start.elapsed() < Duration::from_millis(100));
+
+        // first second is allowed in a burst, the rest waits
+        let limiter = RateLimiter::new(4096);
+        let start = Instant::now();
+        let mut writer = ThrottledWriter::new(Vec::new(), &limiter);
+        for _ in 0..6 {
+            writer.write_all(&[0u8; 1024]).unwrap();
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(400), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(1500), "{:?}", elapsed);
+
+        assert_eq!(run_with_priority(1, || Ok(42)).unwrap(), 42);
+    }
+}
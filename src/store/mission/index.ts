@@ -151,6 +151,7 @@ function defaultIgnore(): Ignore {
     ignoreId: '',
     procedureId: '',
     keyword: '',
+    ruleType: 0,
     reserved0: '',
     reserved1: '',
     reserved2: '',
@@ -179,6 +180,8 @@ function defaultProcedure(): Procedure {
     restrict: 0,
     restrictDays: 3,
     restrictSize: 1024,
+    maxFileSize: 0,
+    maxFileDays: 0,
     reserved0: '',
     reserved1: '',
     reserved2: '',
@@ -1,10 +1,19 @@
 /**
- * Enum for IgnoreMethod.
+ * Enum for IgnoreMethod, flags can be combined.
  */
 enum IgnoreMethod {
   None,
   Custom,
   Gitignore,
+  CustomAndGitignore,
+}
+
+/**
+ * Enum for IgnoreRuleType.
+ */
+enum IgnoreRuleType {
+  Exclude,
+  Include,
 }
 
 /**
@@ -17,6 +26,8 @@ enum CompressFormat {
   Tarbz2,
   Tarxz,
   Sevenz,
+  Tarzst,
+  Tarlz4,
 }
 
 /**
@@ -80,6 +91,11 @@ interface Ignore {
    */
   keyword: string
 
+  /**
+   * The rule type of the ignore, exclude or include matched paths.
+   */
+  ruleType: number
+
   /**
    * Reserved value field
    */
@@ -180,6 +196,16 @@ interface Procedure {
    */
   restrictSize: number
 
+  /**
+   * Skip files larger than this size, in bytes, 0 for no limit.
+   */
+  maxFileSize: number
+
+  /**
+   * Skip files not modified within these days, 0 for no limit.
+   */
+  maxFileDays: number
+
   /**
    * Reserved value field
    */
@@ -424,6 +450,7 @@ interface Record {
 
 export {
   IgnoreMethod,
+  IgnoreRuleType,
   CompressFormat,
   MissionTrigger,
   BackupRestrict,